@@ -0,0 +1,367 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+
+pub fn is_syscall(syscall_name: &str) -> bool {
+    for (_, name) in SYSCALLS.iter() {
+        if *name == syscall_name {
+            return true;
+        }
+    }
+    false
+}
+
+pub fn to_syscall_number(syscall_name: &str) -> Vec<u32> {
+    SYSCALLS
+        .iter()
+        .filter(|(_, name)| *name == &syscall_name)
+        .map(|(number, _)| *number)
+        .collect()
+}
+
+lazy_static! {
+    pub static ref SYSCALLS: HashMap<u32, &'static str> = {
+        let mut s = HashMap::new();
+        s.insert(0, "io_setup");
+        s.insert(1, "io_destroy");
+        s.insert(2, "io_submit");
+        s.insert(3, "io_cancel");
+        s.insert(4, "io_getevents");
+        s.insert(5, "setxattr");
+        s.insert(6, "lsetxattr");
+        s.insert(7, "fsetxattr");
+        s.insert(8, "getxattr");
+        s.insert(9, "lgetxattr");
+        s.insert(10, "fgetxattr");
+        s.insert(11, "listxattr");
+        s.insert(12, "llistxattr");
+        s.insert(13, "flistxattr");
+        s.insert(14, "removexattr");
+        s.insert(15, "lremovexattr");
+        s.insert(16, "fremovexattr");
+        s.insert(17, "getcwd");
+        s.insert(18, "lookup_dcookie");
+        s.insert(19, "eventfd2");
+        s.insert(20, "epoll_create1");
+        s.insert(21, "epoll_ctl");
+        s.insert(22, "epoll_pwait");
+        s.insert(23, "dup");
+        s.insert(24, "dup3");
+        s.insert(25, "fcntl");
+        s.insert(26, "inotify_init1");
+        s.insert(27, "inotify_add_watch");
+        s.insert(28, "inotify_rm_watch");
+        s.insert(29, "ioctl");
+        s.insert(30, "ioprio_set");
+        s.insert(31, "ioprio_get");
+        s.insert(32, "flock");
+        s.insert(33, "mknodat");
+        s.insert(34, "mkdirat");
+        s.insert(35, "unlinkat");
+        s.insert(36, "symlinkat");
+        s.insert(37, "linkat");
+        s.insert(38, "renameat");
+        s.insert(39, "umount2");
+        s.insert(40, "mount");
+        s.insert(41, "pivot_root");
+        s.insert(42, "nfsservctl");
+        s.insert(43, "statfs");
+        s.insert(44, "fstatfs");
+        s.insert(45, "truncate");
+        s.insert(46, "ftruncate");
+        s.insert(47, "fallocate");
+        s.insert(48, "faccessat");
+        s.insert(49, "chdir");
+        s.insert(50, "fchdir");
+        s.insert(51, "chroot");
+        s.insert(52, "fchmod");
+        s.insert(53, "fchmodat");
+        s.insert(54, "fchownat");
+        s.insert(55, "fchown");
+        s.insert(56, "openat");
+        s.insert(57, "close");
+        s.insert(58, "vhangup");
+        s.insert(59, "pipe2");
+        s.insert(60, "quotactl");
+        s.insert(61, "getdents64");
+        s.insert(62, "lseek");
+        s.insert(63, "read");
+        s.insert(64, "write");
+        s.insert(65, "readv");
+        s.insert(66, "writev");
+        s.insert(67, "pread64");
+        s.insert(68, "pwrite64");
+        s.insert(69, "preadv");
+        s.insert(70, "pwritev");
+        s.insert(71, "sendfile");
+        s.insert(72, "pselect6");
+        s.insert(73, "ppoll");
+        s.insert(74, "signalfd4");
+        s.insert(75, "vmsplice");
+        s.insert(76, "splice");
+        s.insert(77, "tee");
+        s.insert(78, "readlinkat");
+        s.insert(79, "newfstatat");
+        s.insert(80, "fstat");
+        s.insert(81, "sync");
+        s.insert(82, "fsync");
+        s.insert(83, "fdatasync");
+        s.insert(84, "sync_file_range");
+        s.insert(85, "timerfd_create");
+        s.insert(86, "timerfd_settime");
+        s.insert(87, "timerfd_gettime");
+        s.insert(88, "utimensat");
+        s.insert(89, "acct");
+        s.insert(90, "capget");
+        s.insert(91, "capset");
+        s.insert(92, "personality");
+        s.insert(93, "exit");
+        s.insert(94, "exit_group");
+        s.insert(95, "waitid");
+        s.insert(96, "set_tid_address");
+        s.insert(97, "unshare");
+        s.insert(98, "futex");
+        s.insert(99, "set_robust_list");
+        s.insert(100, "get_robust_list");
+        s.insert(101, "nanosleep");
+        s.insert(102, "getitimer");
+        s.insert(103, "setitimer");
+        s.insert(104, "kexec_load");
+        s.insert(105, "init_module");
+        s.insert(106, "delete_module");
+        s.insert(107, "timer_create");
+        s.insert(108, "timer_gettime");
+        s.insert(109, "timer_getoverrun");
+        s.insert(110, "timer_settime");
+        s.insert(111, "timer_delete");
+        s.insert(112, "clock_settime");
+        s.insert(113, "clock_gettime");
+        s.insert(114, "clock_getres");
+        s.insert(115, "clock_nanosleep");
+        s.insert(116, "syslog");
+        s.insert(117, "ptrace");
+        s.insert(118, "sched_setparam");
+        s.insert(119, "sched_setscheduler");
+        s.insert(120, "sched_getscheduler");
+        s.insert(121, "sched_getparam");
+        s.insert(122, "sched_setaffinity");
+        s.insert(123, "sched_getaffinity");
+        s.insert(124, "sched_yield");
+        s.insert(125, "sched_get_priority_max");
+        s.insert(126, "sched_get_priority_min");
+        s.insert(127, "sched_rr_get_interval");
+        s.insert(128, "restart_syscall");
+        s.insert(129, "kill");
+        s.insert(130, "tkill");
+        s.insert(131, "tgkill");
+        s.insert(132, "sigaltstack");
+        s.insert(133, "rt_sigsuspend");
+        s.insert(134, "rt_sigaction");
+        s.insert(135, "rt_sigprocmask");
+        s.insert(136, "rt_sigpending");
+        s.insert(137, "rt_sigtimedwait");
+        s.insert(138, "rt_sigqueueinfo");
+        s.insert(139, "rt_sigreturn");
+        s.insert(140, "setpriority");
+        s.insert(141, "getpriority");
+        s.insert(142, "reboot");
+        s.insert(143, "setregid");
+        s.insert(144, "setgid");
+        s.insert(145, "setreuid");
+        s.insert(146, "setuid");
+        s.insert(147, "setresuid");
+        s.insert(148, "getresuid");
+        s.insert(149, "setresgid");
+        s.insert(150, "getresgid");
+        s.insert(151, "setfsuid");
+        s.insert(152, "setfsgid");
+        s.insert(153, "times");
+        s.insert(154, "setpgid");
+        s.insert(155, "getpgid");
+        s.insert(156, "getsid");
+        s.insert(157, "setsid");
+        s.insert(158, "getgroups");
+        s.insert(159, "setgroups");
+        s.insert(160, "uname");
+        s.insert(161, "sethostname");
+        s.insert(162, "setdomainname");
+        s.insert(163, "getrlimit");
+        s.insert(164, "setrlimit");
+        s.insert(165, "getrusage");
+        s.insert(166, "umask");
+        s.insert(167, "prctl");
+        s.insert(168, "getcpu");
+        s.insert(169, "gettimeofday");
+        s.insert(170, "settimeofday");
+        s.insert(171, "adjtimex");
+        s.insert(172, "getpid");
+        s.insert(173, "getppid");
+        s.insert(174, "getuid");
+        s.insert(175, "geteuid");
+        s.insert(176, "getgid");
+        s.insert(177, "getegid");
+        s.insert(178, "gettid");
+        s.insert(179, "sysinfo");
+        s.insert(180, "mq_open");
+        s.insert(181, "mq_unlink");
+        s.insert(182, "mq_timedsend");
+        s.insert(183, "mq_timedreceive");
+        s.insert(184, "mq_notify");
+        s.insert(185, "mq_getsetattr");
+        s.insert(186, "msgget");
+        s.insert(187, "msgctl");
+        s.insert(188, "msgrcv");
+        s.insert(189, "msgsnd");
+        s.insert(190, "semget");
+        s.insert(191, "semctl");
+        s.insert(192, "semtimedop");
+        s.insert(193, "semop");
+        s.insert(194, "shmget");
+        s.insert(195, "shmctl");
+        s.insert(196, "shmat");
+        s.insert(197, "shmdt");
+        s.insert(198, "socket");
+        s.insert(199, "socketpair");
+        s.insert(200, "bind");
+        s.insert(201, "listen");
+        s.insert(202, "accept");
+        s.insert(203, "connect");
+        s.insert(204, "getsockname");
+        s.insert(205, "getpeername");
+        s.insert(206, "sendto");
+        s.insert(207, "recvfrom");
+        s.insert(208, "setsockopt");
+        s.insert(209, "getsockopt");
+        s.insert(210, "shutdown");
+        s.insert(211, "sendmsg");
+        s.insert(212, "recvmsg");
+        s.insert(213, "readahead");
+        s.insert(214, "brk");
+        s.insert(215, "munmap");
+        s.insert(216, "mremap");
+        s.insert(217, "add_key");
+        s.insert(218, "request_key");
+        s.insert(219, "keyctl");
+        s.insert(220, "clone");
+        s.insert(221, "execve");
+        s.insert(222, "mmap");
+        s.insert(223, "fadvise64");
+        s.insert(224, "swapon");
+        s.insert(225, "swapoff");
+        s.insert(226, "mprotect");
+        s.insert(227, "msync");
+        s.insert(228, "mlock");
+        s.insert(229, "munlock");
+        s.insert(230, "mlockall");
+        s.insert(231, "munlockall");
+        s.insert(232, "mincore");
+        s.insert(233, "madvise");
+        s.insert(234, "remap_file_pages");
+        s.insert(235, "mbind");
+        s.insert(236, "get_mempolicy");
+        s.insert(237, "set_mempolicy");
+        s.insert(238, "migrate_pages");
+        s.insert(239, "move_pages");
+        s.insert(240, "rt_tgsigqueueinfo");
+        s.insert(241, "perf_event_open");
+        s.insert(242, "accept4");
+        s.insert(243, "recvmmsg");
+        s.insert(260, "wait4");
+        s.insert(261, "prlimit64");
+        s.insert(262, "fanotify_init");
+        s.insert(263, "fanotify_mark");
+        s.insert(264, "name_to_handle_at");
+        s.insert(265, "open_by_handle_at");
+        s.insert(266, "clock_adjtime");
+        s.insert(267, "syncfs");
+        s.insert(268, "setns");
+        s.insert(269, "sendmmsg");
+        s.insert(270, "process_vm_readv");
+        s.insert(271, "process_vm_writev");
+        s.insert(272, "kcmp");
+        s.insert(273, "finit_module");
+        s.insert(274, "sched_setattr");
+        s.insert(275, "sched_getattr");
+        s.insert(276, "renameat2");
+        s.insert(277, "seccomp");
+        s.insert(278, "getrandom");
+        s.insert(279, "memfd_create");
+        s.insert(280, "bpf");
+        s.insert(281, "execveat");
+        s.insert(282, "userfaultfd");
+        s.insert(283, "membarrier");
+        s.insert(284, "mlock2");
+        s.insert(285, "copy_file_range");
+        s.insert(286, "preadv2");
+        s.insert(287, "pwritev2");
+        s.insert(288, "pkey_mprotect");
+        s.insert(289, "pkey_alloc");
+        s.insert(290, "pkey_free");
+        s.insert(291, "statx");
+        s.insert(292, "io_pgetevents");
+        s.insert(293, "rseq");
+        s.insert(294, "kexec_file_load");
+        s.insert(424, "pidfd_send_signal");
+        s.insert(425, "io_uring_setup");
+        s.insert(426, "io_uring_enter");
+        s.insert(427, "io_uring_register");
+        s.insert(428, "open_tree");
+        s.insert(429, "move_mount");
+        s.insert(430, "fsopen");
+        s.insert(431, "fsconfig");
+        s.insert(432, "fsmount");
+        s.insert(433, "fspick");
+        s.insert(434, "pidfd_open");
+        s.insert(435, "clone3");
+        s.insert(436, "close_range");
+        s.insert(437, "openat2");
+        s.insert(438, "pidfd_getfd");
+        s.insert(439, "faccessat2");
+        s.insert(440, "process_madvise");
+        s.insert(441, "epoll_pwait2");
+        s.insert(442, "mount_setattr");
+        s.insert(443, "quotactl_fd");
+        s.insert(444, "landlock_create_ruleset");
+        s.insert(445, "landlock_add_rule");
+        s.insert(446, "landlock_restrict_self");
+        s.insert(447, "memfd_secret");
+        s.insert(448, "process_mrelease");
+        s.insert(449, "futex_waitv");
+        s.insert(450, "set_mempolicy_home_node");
+        s.insert(451, "cachestat");
+        s.insert(452, "fchmodat2");
+        s.insert(453, "map_shadow_stack");
+        s.insert(454, "futex_wake");
+        s.insert(455, "futex_wait");
+        s.insert(456, "futex_requeue");
+        s.insert(457, "statmount");
+        s.insert(458, "listmount");
+        s.insert(459, "lsm_get_self_attr");
+        s.insert(460, "lsm_set_self_attr");
+        s.insert(461, "lsm_list_modules");
+        s.insert(462, "mseal");
+        s.insert(463, "setxattrat");
+        s.insert(464, "getxattrat");
+        s.insert(465, "listxattrat");
+        s.insert(466, "removexattrat");
+        s.insert(467, "open_tree_attr");
+        s.insert(468, "file_getattr");
+        s.insert(469, "file_setattr");
+        s
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generic_syscall_numbers() {
+        assert_eq!(SYSCALLS.get(&56), Some(&"openat"));
+        assert_eq!(SYSCALLS.get(&243), Some(&"recvmmsg"));
+        assert_eq!(SYSCALLS.get(&294), Some(&"kexec_file_load"));
+        assert_eq!(SYSCALLS.get(&435), Some(&"clone3"));
+        assert!(!is_syscall("open"));
+        assert_eq!(to_syscall_number("io_uring_setup"), vec![425]);
+    }
+}
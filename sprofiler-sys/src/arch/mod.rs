@@ -1 +1,2 @@
+pub mod aarch64;
 pub mod x86_64;
@@ -168,9 +168,9 @@ lazy_static! {
         s.insert(143, "sched_getparam");
         s.insert(144, "sched_setscheduler");
         s.insert(145, "sched_getscheduler");
-        s.insert(146, "sched_get_priority_m");
-        s.insert(147, "sched_get_priority_m");
-        s.insert(148, "sched_rr_get_interva");
+        s.insert(146, "sched_get_priority_max");
+        s.insert(147, "sched_get_priority_min");
+        s.insert(148, "sched_rr_get_interval");
         s.insert(149, "mlock");
         s.insert(150, "munlock");
         s.insert(151, "mlockall");
@@ -369,9 +369,60 @@ lazy_static! {
         s.insert(433, "fspick");
         s.insert(434, "pidfd_open");
         s.insert(435, "clone3");
+        s.insert(436, "close_range");
         s.insert(437, "openat2");
         s.insert(438, "pidfd_getfd");
         s.insert(439, "faccessat2");
+        s.insert(440, "process_madvise");
+        s.insert(441, "epoll_pwait2");
+        s.insert(442, "mount_setattr");
+        s.insert(443, "quotactl_fd");
+        s.insert(444, "landlock_create_ruleset");
+        s.insert(445, "landlock_add_rule");
+        s.insert(446, "landlock_restrict_self");
+        s.insert(447, "memfd_secret");
+        s.insert(448, "process_mrelease");
+        s.insert(449, "futex_waitv");
+        s.insert(450, "set_mempolicy_home_node");
+        s.insert(451, "cachestat");
+        s.insert(452, "fchmodat2");
+        s.insert(453, "map_shadow_stack");
+        s.insert(454, "futex_wake");
+        s.insert(455, "futex_wait");
+        s.insert(456, "futex_requeue");
+        s.insert(457, "statmount");
+        s.insert(458, "listmount");
+        s.insert(459, "lsm_get_self_attr");
+        s.insert(460, "lsm_set_self_attr");
+        s.insert(461, "lsm_list_modules");
+        s.insert(462, "mseal");
+        s.insert(463, "setxattrat");
+        s.insert(464, "getxattrat");
+        s.insert(465, "listxattrat");
+        s.insert(466, "removexattrat");
+        s.insert(467, "open_tree_attr");
+        s.insert(468, "file_getattr");
+        s.insert(469, "file_setattr");
         s
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn syscall_numbers_after_clone3() {
+        assert_eq!(SYSCALLS.get(&436), Some(&"close_range"));
+        assert_eq!(SYSCALLS.get(&440), Some(&"process_madvise"));
+        assert_eq!(SYSCALLS.get(&449), Some(&"futex_waitv"));
+        assert_eq!(to_syscall_number("close_range"), vec![436]);
+    }
+
+    #[test]
+    fn sched_syscall_names() {
+        assert_eq!(SYSCALLS.get(&146), Some(&"sched_get_priority_max"));
+        assert_eq!(SYSCALLS.get(&147), Some(&"sched_get_priority_min"));
+        assert_eq!(SYSCALLS.get(&148), Some(&"sched_rr_get_interval"));
+    }
+}
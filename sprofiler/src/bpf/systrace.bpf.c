@@ -5,8 +5,14 @@
 #include <bpf/bpf_helpers.h>
 
 #define TASK_COMM_LEN 32
+#define PATH_LEN 256
+#define SOCKADDR_LEN 128
 
 const volatile u64 target_cgid = 51097;
+const volatile bool trace_file_access = false;
+const volatile bool trace_capabilities = false;
+const volatile bool trace_network = false;
+const volatile bool trace_io_uring = false;
 
 enum FILTER_STATE { Prestart, Running, Exited };
 volatile enum FILTER_STATE state = Prestart;
@@ -24,6 +30,74 @@ struct {
   __uint(value_size, sizeof(u32));
 } sys_enter_events SEC(".maps");
 
+struct file_event_t {
+  long syscall_nr;
+  long flags;
+  char comm[TASK_COMM_LEN];
+  char path[PATH_LEN];
+};
+
+struct {
+  __uint(type, BPF_MAP_TYPE_PERCPU_ARRAY);
+  __uint(max_entries, 1);
+  __type(key, u32);
+  __type(value, struct file_event_t);
+} file_event_heap SEC(".maps");
+
+struct {
+  __uint(type, BPF_MAP_TYPE_PERF_EVENT_ARRAY);
+  __uint(key_size, sizeof(int));
+  __uint(value_size, sizeof(u32));
+} file_events SEC(".maps");
+
+struct net_event_t {
+  u32 pid;
+  u32 addrlen;
+  long syscall_nr;
+  long args[3];
+  char comm[TASK_COMM_LEN];
+  unsigned char addr[SOCKADDR_LEN];
+};
+
+struct {
+  __uint(type, BPF_MAP_TYPE_PERCPU_ARRAY);
+  __uint(max_entries, 1);
+  __type(key, u32);
+  __type(value, struct net_event_t);
+} net_event_heap SEC(".maps");
+
+struct {
+  __uint(type, BPF_MAP_TYPE_PERF_EVENT_ARRAY);
+  __uint(key_size, sizeof(int));
+  __uint(value_size, sizeof(u32));
+} net_events SEC(".maps");
+
+struct cap_event_t {
+  int cap;
+  unsigned int opts;
+  int ret;
+  char comm[TASK_COMM_LEN];
+};
+
+struct cap_check_t {
+  int cap;
+  unsigned int opts;
+};
+
+// cap_capable() arguments by thread until it returns
+struct {
+  __uint(type, BPF_MAP_TYPE_HASH);
+  __uint(max_entries, 10240);
+  __type(key, u64);
+  __type(value, struct cap_check_t);
+} cap_checks SEC(".maps");
+
+struct {
+  __uint(type, BPF_MAP_TYPE_PERF_EVENT_ARRAY);
+  __uint(key_size, sizeof(int));
+  __uint(value_size, sizeof(u32));
+} cap_events SEC(".maps");
+
 static __always_inline bool is_trace_target() {
   u64 cgid = bpf_get_current_cgroup_id();
 
@@ -49,6 +123,217 @@ static __always_inline bool is_started(u64 nr) {
   }
 }
 
+// ctx only allows constant offset access
+static __always_inline unsigned long
+syscall_arg(struct trace_event_raw_sys_enter *ctx, int idx) {
+  switch (idx) {
+  case 0:
+    return ctx->args[0];
+  case 1:
+    return ctx->args[1];
+  case 2:
+    return ctx->args[2];
+  case 3:
+    return ctx->args[3];
+  case 4:
+    return ctx->args[4];
+  case 5:
+    return ctx->args[5];
+  }
+
+  return 0;
+}
+
+// Argument index of the pathname (and open flags) of file related syscalls.
+// Syscall numbers are x86_64.
+static __always_inline bool file_syscall_args(long nr, int *path_idx,
+                                              int *flags_idx) {
+  *flags_idx = -1;
+
+  switch (nr) {
+  case 2: // open
+    *path_idx = 0;
+    *flags_idx = 1;
+    return true;
+  case 257: // openat
+  case 437: // openat2
+    *path_idx = 1;
+    *flags_idx = 2;
+    return true;
+  case 4:   // stat
+  case 6:   // lstat
+  case 21:  // access
+  case 59:  // execve
+  case 76:  // truncate
+  case 80:  // chdir
+  case 82:  // rename
+  case 83:  // mkdir
+  case 84:  // rmdir
+  case 85:  // creat
+  case 87:  // unlink
+  case 89:  // readlink
+  case 90:  // chmod
+  case 92:  // chown
+  case 94:  // lchown
+  case 133: // mknod
+    *path_idx = 0;
+    return true;
+  case 86:  // link
+  case 88:  // symlink
+  case 258: // mkdirat
+  case 259: // mknodat
+  case 260: // fchownat
+  case 262: // newfstatat
+  case 263: // unlinkat
+  case 264: // renameat
+  case 267: // readlinkat
+  case 268: // fchmodat
+  case 269: // faccessat
+  case 316: // renameat2
+  case 322: // execveat
+  case 332: // statx
+  case 439: // faccessat2
+    *path_idx = 1;
+    return true;
+  case 266: // symlinkat
+    *path_idx = 2;
+    return true;
+  case 265: // linkat
+    *path_idx = 3;
+    return true;
+  }
+
+  return false;
+}
+
+static __always_inline void
+output_file_event(struct trace_event_raw_sys_enter *ctx) {
+  int path_idx = 0, flags_idx = -1;
+  u32 zero = 0;
+
+  if (!file_syscall_args(ctx->id, &path_idx, &flags_idx))
+    return;
+
+  struct file_event_t *event = bpf_map_lookup_elem(&file_event_heap, &zero);
+  if (!event)
+    return;
+
+  event->syscall_nr = ctx->id;
+  event->flags = 0;
+
+  if (ctx->id == 437) {
+    // openat2 passes flags in the first member of struct open_how
+    bpf_probe_read_user(&event->flags, sizeof(event->flags),
+                        (void *)ctx->args[2]);
+  } else if (flags_idx >= 0) {
+    event->flags = syscall_arg(ctx, flags_idx);
+  }
+
+  bpf_get_current_comm(&event->comm, TASK_COMM_LEN);
+  bpf_probe_read_user_str(event->path, PATH_LEN,
+                          (const char *)syscall_arg(ctx, path_idx));
+
+  bpf_perf_event_output(ctx, &file_events, BPF_F_CURRENT_CPU, event,
+                        sizeof(*event));
+}
+
+// socket(41), connect(42), bind(49) and listen(50) on x86_64
+static __always_inline void
+output_net_event(struct trace_event_raw_sys_enter *ctx) {
+  u32 zero = 0;
+  long nr = ctx->id;
+
+  if (nr != 41 && nr != 42 && nr != 49 && nr != 50)
+    return;
+
+  struct net_event_t *event = bpf_map_lookup_elem(&net_event_heap, &zero);
+  if (!event)
+    return;
+
+  event->pid = bpf_get_current_pid_tgid() >> 32;
+  event->addrlen = 0;
+  event->syscall_nr = nr;
+  event->args[0] = ctx->args[0];
+  event->args[1] = ctx->args[1];
+  event->args[2] = ctx->args[2];
+
+  if (nr == 42 || nr == 49) {
+    u32 len = ctx->args[2];
+    if (len > SOCKADDR_LEN)
+      len = SOCKADDR_LEN;
+    if (bpf_probe_read_user(event->addr, len, (void *)ctx->args[1]) == 0)
+      event->addrlen = len;
+  }
+
+  bpf_get_current_comm(&event->comm, TASK_COMM_LEN);
+
+  bpf_perf_event_output(ctx, &net_events, BPF_F_CURRENT_CPU, event,
+                        sizeof(*event));
+}
+
+struct uring_event_t {
+  u32 opcode;
+  char comm[TASK_COMM_LEN];
+};
+
+struct {
+  __uint(type, BPF_MAP_TYPE_PERF_EVENT_ARRAY);
+  __uint(key_size, sizeof(int));
+  __uint(value_size, sizeof(u32));
+} uring_events SEC(".maps");
+
+// The layouts of the io_uring submit tracepoints differ between kernel
+// versions (req is missing before 5.15 and opcode moved in 6.0), so opcode is
+// read through the BTF of the running kernel. The ___ flavors keep these
+// definitions apart from the ones vmlinux.h may have.
+struct trace_event_raw_io_uring_submit_req___new {
+  u8 opcode;
+} __attribute__((preserve_access_index));
+
+struct trace_event_raw_io_uring_submit_sqe___old {
+  u8 opcode;
+} __attribute__((preserve_access_index));
+
+static __always_inline int output_uring_event(void *ctx, u8 opcode) {
+  if (!trace_io_uring)
+    return 0;
+
+  if (!is_trace_target())
+    return 0;
+
+  if (state != Running)
+    return 0;
+
+  struct uring_event_t event = {};
+
+  event.opcode = opcode;
+  bpf_get_current_comm(&event.comm, TASK_COMM_LEN);
+
+  bpf_perf_event_output(ctx, &uring_events, BPF_F_CURRENT_CPU, &event,
+                        sizeof(event));
+  return 0;
+}
+
+SEC("tracepoint/io_uring/io_uring_submit_req")
+int tracepoint__io_uring__io_uring_submit_req(void *ctx) {
+  struct trace_event_raw_io_uring_submit_req___new *raw = ctx;
+
+  if (!bpf_core_field_exists(raw->opcode))
+    return 0;
+
+  return output_uring_event(ctx, BPF_CORE_READ(raw, opcode));
+}
+
+SEC("tracepoint/io_uring/io_uring_submit_sqe")
+int tracepoint__io_uring__io_uring_submit_sqe(void *ctx) {
+  struct trace_event_raw_io_uring_submit_sqe___old *raw = ctx;
+
+  if (!bpf_core_field_exists(raw->opcode))
+    return 0;
+
+  return output_uring_event(ctx, BPF_CORE_READ(raw, opcode));
+}
+
 SEC("tracepoint/raw_syscalls/sys_enter")
 int tracepoint__raw_syscalls__sys_enter(struct trace_event_raw_sys_enter *ctx) {
 
@@ -68,6 +353,62 @@ int tracepoint__raw_syscalls__sys_enter(struct trace_event_raw_sys_enter *ctx) {
 
   bpf_perf_event_output(ctx, &sys_enter_events, BPF_F_CURRENT_CPU, &event,
                         sizeof(event));
+
+  if (trace_file_access)
+    output_file_event(ctx);
+
+  if (trace_network)
+    output_net_event(ctx);
+
+  return 0;
+}
+
+SEC("kprobe/cap_capable")
+int BPF_KPROBE(kprobe__cap_capable, const struct cred *cred,
+               struct user_namespace *targ_ns, int cap, unsigned int opts) {
+  if (!trace_capabilities)
+    return 0;
+
+  if (!is_trace_target())
+    return 0;
+
+  if (state != Running)
+    return 0;
+
+  u64 id = bpf_get_current_pid_tgid();
+  struct cap_check_t check = {};
+
+  check.cap = cap;
+  check.opts = opts;
+
+  bpf_map_update_elem(&cap_checks, &id, &check, BPF_ANY);
+  return 0;
+}
+
+// Only granted checks are reported, denied and probing checks don't make a
+// capability necessary
+SEC("kretprobe/cap_capable")
+int BPF_KRETPROBE(kretprobe__cap_capable, int ret) {
+  u64 id = bpf_get_current_pid_tgid();
+  struct cap_check_t *check = bpf_map_lookup_elem(&cap_checks, &id);
+
+  if (!check)
+    return 0;
+
+  struct cap_event_t event = {};
+
+  event.cap = check->cap;
+  event.opts = check->opts;
+  event.ret = ret;
+  bpf_map_delete_elem(&cap_checks, &id);
+
+  if (ret != 0)
+    return 0;
+
+  bpf_get_current_comm(&event.comm, TASK_COMM_LEN);
+
+  bpf_perf_event_output(ctx, &cap_events, BPF_F_CURRENT_CPU, &event,
+                        sizeof(event));
   return 0;
 }
 
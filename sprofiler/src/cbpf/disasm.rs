@@ -0,0 +1,120 @@
+use std::fmt::Write;
+
+use super::*;
+
+fn field_name(offset: u32) -> String {
+    match offset {
+        NR_OFFSET => "nr".to_string(),
+        ARCH_OFFSET => "arch".to_string(),
+        IP_OFFSET => "instruction_pointer.lo".to_string(),
+        12 => "instruction_pointer.hi".to_string(),
+        _ => {
+            let index = (offset - ARGS_OFFSET) / 8;
+            let word = match offset % 8 {
+                0 => "lo",
+                _ => "hi",
+            };
+            format!("args[{}].{}", index, word)
+        }
+    }
+}
+
+fn instruction(pc: usize, filter: &SockFilter) -> String {
+    let target = |offset: u8| pc + 1 + offset as usize;
+    let jump = |mnemonic: &str| {
+        format!(
+            "{} {:#x} true:{:04} false:{:04}",
+            mnemonic,
+            filter.k,
+            target(filter.jt),
+            target(filter.jf)
+        )
+    };
+
+    match filter.code {
+        code if code == BPF_LD | BPF_W | BPF_ABS => format!("ld $data[{}]", field_name(filter.k)),
+        code if code == BPF_ALU | BPF_AND | BPF_K => format!("and {:#x}", filter.k),
+        code if code == BPF_JMP | BPF_JA => format!("jmp {:04}", pc + 1 + filter.k as usize),
+        code if code == BPF_JMP | BPF_JEQ | BPF_K => jump("jeq"),
+        code if code == BPF_JMP | BPF_JGT | BPF_K => jump("jgt"),
+        code if code == BPF_JMP | BPF_JGE | BPF_K => jump("jge"),
+        code if code == BPF_JMP | BPF_JSET | BPF_K => jump("jset"),
+        code if code == BPF_RET | BPF_K => format!("ret {}", action_name(filter.k)),
+        _ => "???".to_string(),
+    }
+}
+
+/// Listing in the layout of libseccomp's scmp_bpf_disasm
+pub fn disasm(program: &Program) -> String {
+    let mut listing = String::new();
+    for arch in &program.skipped {
+        let _ = writeln!(
+            listing,
+            "# {:?} has no syscall table, its calls hit KILL_THREAD",
+            arch
+        );
+    }
+    listing.push_str(" line  OP   JT   JF   K\n");
+    listing.push_str("=================================\n");
+
+    for (pc, filter) in program.filters.iter().enumerate() {
+        let _ = writeln!(
+            listing,
+            " {:04}: {:#04x} {:#04x} {:#04x} {:#010x}   {}",
+            pc,
+            filter.code,
+            filter.jt,
+            filter.jf,
+            filter.k,
+            instruction(pc, filter)
+        );
+    }
+
+    listing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oci_spec::runtime::{LinuxSeccompBuilder, LinuxSyscallBuilder};
+
+    #[test]
+    fn disasm_program() -> Result<()> {
+        let profile = LinuxSeccompBuilder::default()
+            .default_action(LinuxSeccompAction::ScmpActErrno)
+            .architectures(vec![Arch::ScmpArchX86_64])
+            .syscalls(vec![LinuxSyscallBuilder::default()
+                .names(vec!["write".to_string()])
+                .action(LinuxSeccompAction::ScmpActAllow)
+                .build()?])
+            .build()?;
+
+        let listing = disasm(&compile(&profile)?);
+        let lines: Vec<&str> = listing.lines().skip(2).collect();
+        assert_eq!(
+            lines,
+            vec![
+                " 0000: 0x20 0x00 0x00 0x00000004   ld $data[arch]",
+                " 0001: 0x15 0x00 0x06 0xc000003e   jeq 0xc000003e true:0002 false:0008",
+                " 0002: 0x20 0x00 0x00 0x00000000   ld $data[nr]",
+                " 0003: 0x35 0x01 0x00 0x00000002   jge 0x2 true:0005 false:0004",
+                " 0004: 0x35 0x01 0x02 0x00000001   jge 0x1 true:0006 false:0007",
+                " 0005: 0x35 0x02 0x01 0x40000000   jge 0x40000000 true:0008 false:0007",
+                " 0006: 0x06 0x00 0x00 0x7fff0000   ret ALLOW",
+                " 0007: 0x06 0x00 0x00 0x00050001   ret ERRNO(1)",
+                " 0008: 0x06 0x00 0x00 0x00000000   ret KILL_THREAD",
+            ]
+        );
+
+        let mut generated = profile.clone();
+        generated.set_architectures(Some(vec![Arch::ScmpArchX86, Arch::ScmpArchX86_64]));
+        let program = compile(&generated)?;
+        assert_eq!(program.skipped, vec![Arch::ScmpArchX86]);
+        assert_eq!(
+            disasm(&program).lines().next(),
+            Some("# ScmpArchX86 has no syscall table, its calls hit KILL_THREAD")
+        );
+
+        Ok(())
+    }
+}
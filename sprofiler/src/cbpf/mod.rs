@@ -0,0 +1,679 @@
+pub mod disasm;
+
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{bail, Context, Result};
+use oci_spec::runtime::{
+    Arch, LinuxSeccomp, LinuxSeccompAction, LinuxSeccompArg, LinuxSeccompOperator,
+};
+use sprofiler_sys::arch::{aarch64, x86_64};
+
+use crate::profile_util::EPERM;
+
+// Instruction fields of linux/filter.h used by seccomp filters
+pub const BPF_LD: u16 = 0x00;
+pub const BPF_ALU: u16 = 0x04;
+pub const BPF_JMP: u16 = 0x05;
+pub const BPF_RET: u16 = 0x06;
+pub const BPF_W: u16 = 0x00;
+pub const BPF_ABS: u16 = 0x20;
+pub const BPF_AND: u16 = 0x50;
+pub const BPF_JA: u16 = 0x00;
+pub const BPF_JEQ: u16 = 0x10;
+pub const BPF_JGT: u16 = 0x20;
+pub const BPF_JGE: u16 = 0x30;
+pub const BPF_JSET: u16 = 0x40;
+pub const BPF_K: u16 = 0x00;
+
+/// The kernel rejects longer filters
+pub const BPF_MAXINSNS: usize = 4096;
+
+// Return values of linux/seccomp.h
+pub const SECCOMP_RET_KILL_PROCESS: u32 = 0x8000_0000;
+pub const SECCOMP_RET_KILL_THREAD: u32 = 0x0000_0000;
+pub const SECCOMP_RET_TRAP: u32 = 0x0003_0000;
+pub const SECCOMP_RET_ERRNO: u32 = 0x0005_0000;
+pub const SECCOMP_RET_USER_NOTIF: u32 = 0x7fc0_0000;
+pub const SECCOMP_RET_TRACE: u32 = 0x7ff0_0000;
+pub const SECCOMP_RET_LOG: u32 = 0x7ffc_0000;
+pub const SECCOMP_RET_ALLOW: u32 = 0x7fff_0000;
+pub const SECCOMP_RET_ACTION_FULL: u32 = 0xffff_0000;
+pub const SECCOMP_RET_DATA: u32 = 0x0000_ffff;
+
+// Offsets in struct seccomp_data
+pub const NR_OFFSET: u32 = 0;
+pub const ARCH_OFFSET: u32 = 4;
+pub const IP_OFFSET: u32 = 8;
+pub const ARGS_OFFSET: u32 = 16;
+
+const AUDIT_ARCH_X86_64: u32 = 0xc000_003e;
+const AUDIT_ARCH_AARCH64: u32 = 0xc000_00b7;
+/// x32 syscalls are reported as AUDIT_ARCH_X86_64 with this bit set in the number
+const X32_SYSCALL_BIT: u32 = 0x4000_0000;
+
+/// Action of libseccomp for architectures the filter doesn't know
+const BAD_ARCH_ACTION: u32 = SECCOMP_RET_KILL_THREAD;
+
+/// Syscall table of an architecture which sprofiler can compile filters for.
+/// Only little endian architectures are supported, the argument words are swapped on
+/// big endian ones.
+pub struct ArchTable {
+    pub name: &'static str,
+    pub audit_arch: u32,
+    pub syscalls: &'static HashMap<u32, &'static str>,
+    /// Numbers from this one belong to another ABI sharing the audit arch
+    pub nr_limit: Option<u32>,
+}
+
+pub fn arch_table(arch: &Arch) -> Option<ArchTable> {
+    match arch {
+        Arch::ScmpArchX86_64 => Some(ArchTable {
+            name: "x86_64",
+            audit_arch: AUDIT_ARCH_X86_64,
+            syscalls: &x86_64::SYSCALLS,
+            nr_limit: Some(X32_SYSCALL_BIT),
+        }),
+        Arch::ScmpArchAarch64 => Some(ArchTable {
+            name: "aarch64",
+            audit_arch: AUDIT_ARCH_AARCH64,
+            syscalls: &aarch64::SYSCALLS,
+            nr_limit: None,
+        }),
+        _ => None,
+    }
+}
+
+/// The architecture sprofiler runs on, if it has a syscall table
+pub fn host_arch() -> Option<Arch> {
+    match std::env::consts::ARCH {
+        "x86_64" => Some(Arch::ScmpArchX86_64),
+        "aarch64" => Some(Arch::ScmpArchAarch64),
+        _ => None,
+    }
+}
+
+impl ArchTable {
+    pub fn number(&self, name: &str) -> Option<u32> {
+        self.syscalls
+            .iter()
+            .find(|(_, known)| **known == name)
+            .map(|(number, _)| *number)
+    }
+}
+
+/// struct sock_filter
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SockFilter {
+    pub code: u16,
+    pub jt: u8,
+    pub jf: u8,
+    pub k: u32,
+}
+
+/// struct seccomp_data, as seen by the filter
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SeccompData {
+    pub nr: u32,
+    pub arch: u32,
+    pub instruction_pointer: u64,
+    pub args: [u64; 6],
+}
+
+impl SeccompData {
+    fn load(&self, offset: u32) -> Option<u32> {
+        let word = |value: u64, offset: u32| match offset % 8 {
+            0 => Some(value as u32),
+            4 => Some((value >> 32) as u32),
+            _ => None,
+        };
+
+        match offset {
+            NR_OFFSET => Some(self.nr),
+            ARCH_OFFSET => Some(self.arch),
+            IP_OFFSET..=15 => word(self.instruction_pointer, offset),
+            _ => {
+                let index = (offset.checked_sub(ARGS_OFFSET)? / 8) as usize;
+                self.args.get(index).and_then(|arg| word(*arg, offset))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Program {
+    pub filters: Vec<SockFilter>,
+    /// Architectures of the profile without a syscall table. Their calls hit the
+    /// bad arch action, which runc doesn't install for them.
+    pub skipped: Vec<Arch>,
+}
+
+impl Program {
+    pub fn len(&self) -> usize {
+        self.filters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+
+    /// The filter as an array of struct sock_filter, e.g. for bwrap --seccomp
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.filters
+            .iter()
+            .flat_map(|filter| {
+                let mut bytes = Vec::with_capacity(8);
+                bytes.extend_from_slice(&filter.code.to_ne_bytes());
+                bytes.push(filter.jt);
+                bytes.push(filter.jf);
+                bytes.extend_from_slice(&filter.k.to_ne_bytes());
+                bytes
+            })
+            .collect()
+    }
+
+    /// Runs the filter like the kernel does and returns the seccomp return value
+    pub fn run(&self, data: &SeccompData) -> Result<u32> {
+        let mut acc = 0u32;
+        let mut pc = 0;
+
+        while let Some(filter) = self.filters.get(pc) {
+            pc += 1;
+            let jump = |cond: bool| (if cond { filter.jt } else { filter.jf }) as usize;
+
+            match filter.code {
+                code if code == BPF_LD | BPF_W | BPF_ABS => {
+                    acc = data
+                        .load(filter.k)
+                        .with_context(|| format!("{}: invalid offset {}", pc - 1, filter.k))?;
+                }
+                code if code == BPF_ALU | BPF_AND | BPF_K => acc &= filter.k,
+                code if code == BPF_JMP | BPF_JA => pc += filter.k as usize,
+                code if code == BPF_JMP | BPF_JEQ | BPF_K => pc += jump(acc == filter.k),
+                code if code == BPF_JMP | BPF_JGT | BPF_K => pc += jump(acc > filter.k),
+                code if code == BPF_JMP | BPF_JGE | BPF_K => pc += jump(acc >= filter.k),
+                code if code == BPF_JMP | BPF_JSET | BPF_K => pc += jump(acc & filter.k != 0),
+                code if code == BPF_RET | BPF_K => return Ok(filter.k),
+                code => bail!("{}: unsupported instruction {:#06x}", pc - 1, code),
+            }
+        }
+
+        bail!("the filter ends without a return")
+    }
+}
+
+pub fn action_value(action: LinuxSeccompAction, errno_ret: Option<u32>) -> u32 {
+    let data = errno_ret.unwrap_or(EPERM) & SECCOMP_RET_DATA;
+    match action {
+        LinuxSeccompAction::ScmpActKill => SECCOMP_RET_KILL_THREAD,
+        LinuxSeccompAction::ScmpActKillProcess => SECCOMP_RET_KILL_PROCESS,
+        LinuxSeccompAction::ScmpActTrap => SECCOMP_RET_TRAP,
+        LinuxSeccompAction::ScmpActErrno => SECCOMP_RET_ERRNO | data,
+        LinuxSeccompAction::ScmpActNotify => SECCOMP_RET_USER_NOTIF,
+        LinuxSeccompAction::ScmpActTrace => SECCOMP_RET_TRACE | data,
+        LinuxSeccompAction::ScmpActLog => SECCOMP_RET_LOG,
+        LinuxSeccompAction::ScmpActAllow => SECCOMP_RET_ALLOW,
+    }
+}
+
+#[rustfmt::skip]
+pub fn action_name(value: u32) -> String {
+    let data = value & SECCOMP_RET_DATA;
+    match value & SECCOMP_RET_ACTION_FULL {
+        SECCOMP_RET_KILL_PROCESS => "KILL_PROCESS".to_string(),
+        SECCOMP_RET_KILL_THREAD  => "KILL_THREAD".to_string(),
+        SECCOMP_RET_TRAP         => "TRAP".to_string(),
+        SECCOMP_RET_ERRNO        => format!("ERRNO({})", data),
+        SECCOMP_RET_USER_NOTIF   => "USER_NOTIF".to_string(),
+        SECCOMP_RET_TRACE        => format!("TRACE({})", data),
+        SECCOMP_RET_LOG          => "LOG".to_string(),
+        SECCOMP_RET_ALLOW        => "ALLOW".to_string(),
+        _                        => format!("{:#010x}", value),
+    }
+}
+
+/// Index of an instruction counted from the end of the program.
+/// Filters only jump forward, so the program is emitted backwards and every
+/// jump target is known when the jump is emitted.
+type Label = usize;
+
+#[derive(Default)]
+struct Emitter {
+    filters: Vec<SockFilter>,
+    rets: HashMap<u32, Label>,
+}
+
+impl Emitter {
+    fn offset(&self, target: Label) -> usize {
+        self.filters.len() - target
+    }
+
+    fn stmt(&mut self, code: u16, k: u32) -> Label {
+        self.filters.push(SockFilter {
+            code,
+            jt: 0,
+            jf: 0,
+            k,
+        });
+        self.filters.len()
+    }
+
+    fn load(&mut self, offset: u32) -> Label {
+        self.stmt(BPF_LD | BPF_W | BPF_ABS, offset)
+    }
+
+    fn ret(&mut self, value: u32) -> Label {
+        if let Some(label) = self.rets.get(&value) {
+            return *label;
+        }
+        let label = self.stmt(BPF_RET | BPF_K, value);
+        self.rets.insert(value, label);
+        label
+    }
+
+    /// Conditional jumps reach 255 instructions, farther targets go through a ja,
+    /// or a copy of the target if it is a return
+    fn reach(&mut self, target: Label) -> Label {
+        let filter = self.filters[target - 1];
+        if filter.code == BPF_RET | BPF_K {
+            let label = self.stmt(filter.code, filter.k);
+            self.rets.insert(filter.k, label);
+            label
+        } else {
+            let offset = self.offset(target) as u32;
+            self.stmt(BPF_JMP | BPF_JA, offset)
+        }
+    }
+
+    fn jump(&mut self, code: u16, k: u32, mut jt: Label, mut jf: Label) -> Label {
+        loop {
+            if self.offset(jt) > u8::MAX as usize {
+                jt = self.reach(jt);
+            } else if self.offset(jf) > u8::MAX as usize {
+                jf = self.reach(jf);
+            } else {
+                break;
+            }
+        }
+
+        self.filters.push(SockFilter {
+            code: BPF_JMP | code | BPF_K,
+            jt: self.offset(jt) as u8,
+            jf: self.offset(jf) as u8,
+            k,
+        });
+        self.filters.len()
+    }
+
+    /// Jumps to matched if the 64 bit argument satisfies the comparison
+    fn arg(&mut self, arg: &LinuxSeccompArg, matched: Label, failed: Label) -> Label {
+        let lo_offset = ARGS_OFFSET + 8 * arg.index() as u32;
+        let hi_offset = lo_offset + 4;
+        let (value_hi, value_lo) = ((arg.value() >> 32) as u32, arg.value() as u32);
+
+        match arg.op() {
+            LinuxSeccompOperator::ScmpCmpEq => {
+                self.jump(BPF_JEQ, value_lo, matched, failed);
+                let lo = self.load(lo_offset);
+                self.jump(BPF_JEQ, value_hi, lo, failed);
+            }
+            LinuxSeccompOperator::ScmpCmpNe => {
+                self.jump(BPF_JEQ, value_lo, failed, matched);
+                let lo = self.load(lo_offset);
+                self.jump(BPF_JEQ, value_hi, lo, matched);
+            }
+            LinuxSeccompOperator::ScmpCmpGt | LinuxSeccompOperator::ScmpCmpGe => {
+                let code = match arg.op() {
+                    LinuxSeccompOperator::ScmpCmpGt => BPF_JGT,
+                    _ => BPF_JGE,
+                };
+                self.jump(code, value_lo, matched, failed);
+                let lo = self.load(lo_offset);
+                let equal = self.jump(BPF_JEQ, value_hi, lo, failed);
+                self.jump(BPF_JGT, value_hi, matched, equal);
+            }
+            LinuxSeccompOperator::ScmpCmpLt | LinuxSeccompOperator::ScmpCmpLe => {
+                // The negation of >= and >
+                let code = match arg.op() {
+                    LinuxSeccompOperator::ScmpCmpLt => BPF_JGE,
+                    _ => BPF_JGT,
+                };
+                self.jump(code, value_lo, failed, matched);
+                let lo = self.load(lo_offset);
+                let equal = self.jump(BPF_JEQ, value_hi, lo, matched);
+                self.jump(BPF_JGT, value_hi, failed, equal);
+            }
+            LinuxSeccompOperator::ScmpCmpMaskedEq => {
+                // value is the mask and valueTwo the expected value
+                let datum = arg.value_two().unwrap_or(0);
+                let (datum_hi, datum_lo) = ((datum >> 32) as u32, datum as u32);
+                self.jump(BPF_JEQ, datum_lo, matched, failed);
+                self.stmt(BPF_ALU | BPF_AND | BPF_K, value_lo);
+                let lo = self.load(lo_offset);
+                self.jump(BPF_JEQ, datum_hi, lo, failed);
+                self.stmt(BPF_ALU | BPF_AND | BPF_K, value_hi);
+            }
+        }
+
+        self.load(hi_offset)
+    }
+
+    fn chain(&mut self, chain: &Chain, default: u32) -> Label {
+        let mut next = self.ret(chain.fallback.unwrap_or(default));
+        for (value, args) in chain.conditional.iter().rev() {
+            let mut matched = self.ret(*value);
+            for arg in args.iter().rev() {
+                matched = self.arg(arg, matched, next);
+            }
+            next = matched;
+        }
+        next
+    }
+
+    /// Binary search over intervals of syscall numbers sharing a target
+    fn search(&mut self, intervals: &[(u32, Label)]) -> Label {
+        if intervals.len() == 1 {
+            return intervals[0].1;
+        }
+
+        let mid = intervals.len() / 2;
+        let upper = self.search(&intervals[mid..]);
+        let lower = self.search(&intervals[..mid]);
+        self.jump(BPF_JGE, intervals[mid].0, upper, lower)
+    }
+
+    fn finish(mut self) -> Result<Program> {
+        if self.filters.len() > BPF_MAXINSNS {
+            bail!(
+                "the filter has {} instructions, more than the kernel accepts ({})",
+                self.filters.len(),
+                BPF_MAXINSNS
+            );
+        }
+
+        self.filters.reverse();
+        Ok(Program {
+            filters: self.filters,
+            skipped: vec![],
+        })
+    }
+}
+
+/// Rules of a syscall in the order the filter checks them
+#[derive(Default)]
+struct Chain {
+    conditional: Vec<(u32, Vec<LinuxSeccompArg>)>,
+    /// Action of the first unconditional rule
+    fallback: Option<u32>,
+}
+
+fn has_duplicated_index(args: &[LinuxSeccompArg]) -> bool {
+    args.iter()
+        .enumerate()
+        .any(|(i, arg)| args[..i].iter().any(|other| other.index() == arg.index()))
+}
+
+/// Conditional rules are checked in the order of the profile, then the first
+/// unconditional rule applies.
+/// Like runc, conditions on the same argument are alternatives instead of all
+/// being required.
+fn build_chains(profile: &LinuxSeccomp, table: &ArchTable, default: u32) -> BTreeMap<u32, Chain> {
+    let numbers: HashMap<&str, u32> = table
+        .syscalls
+        .iter()
+        .map(|(number, name)| (*name, *number))
+        .collect();
+
+    let mut chains: BTreeMap<u32, Chain> = BTreeMap::new();
+    for syscall in profile.syscalls().iter().flatten() {
+        let value = action_value(syscall.action(), syscall.errno_ret());
+        let args = syscall.args().clone().unwrap_or_default();
+
+        for name in syscall.names() {
+            // Syscalls the architecture doesn't have are ignored like libseccomp does
+            let number = match numbers.get(name.as_str()) {
+                Some(number) => *number,
+                None => continue,
+            };
+            let chain = chains.entry(number).or_default();
+
+            if args.is_empty() {
+                chain.fallback.get_or_insert(value);
+            } else if has_duplicated_index(&args) {
+                chain
+                    .conditional
+                    .extend(args.iter().map(|arg| (value, vec![*arg])));
+            } else {
+                chain.conditional.push((value, args.clone()));
+            }
+        }
+    }
+
+    // Syscalls taking the default action are left to the default
+    chains.retain(|_, chain| {
+        !chain.conditional.is_empty() || chain.fallback.unwrap_or(default) != default
+    });
+    chains
+}
+
+fn push_interval(intervals: &mut Vec<(u32, Label)>, start: u32, label: Label) {
+    if let Some(last) = intervals.last_mut() {
+        if last.0 == start {
+            last.1 = label;
+            let len = intervals.len();
+            if len >= 2 && intervals[len - 2].1 == label {
+                intervals.pop();
+            }
+            return;
+        }
+        if last.1 == label {
+            return;
+        }
+    }
+    intervals.push((start, label));
+}
+
+fn emit_arch(
+    emitter: &mut Emitter,
+    profile: &LinuxSeccomp,
+    table: &ArchTable,
+    default: u32,
+    bad_arch: Label,
+) -> Label {
+    let default_label = emitter.ret(default);
+
+    let chains = build_chains(profile, table, default);
+    let labels: Vec<(u32, Label)> = chains
+        .iter()
+        .rev()
+        .map(|(number, chain)| (*number, emitter.chain(chain, default)))
+        .collect();
+
+    let mut intervals = vec![(0, default_label)];
+    for (number, label) in labels.into_iter().rev() {
+        push_interval(&mut intervals, number, label);
+        if let Some(next) = number.checked_add(1) {
+            push_interval(&mut intervals, next, default_label);
+        }
+    }
+    if let Some(limit) = table.nr_limit {
+        push_interval(&mut intervals, limit, bad_arch);
+    }
+
+    emitter.search(&intervals);
+    emitter.load(NR_OFFSET)
+}
+
+/// Compiles the profile to the seccomp filter for the architectures of the profile
+/// which sprofiler has syscall tables for (x86_64 if none is listed).
+pub fn compile(profile: &LinuxSeccomp) -> Result<Program> {
+    let mut tables = Vec::new();
+    let mut skipped = Vec::new();
+    match profile.architectures() {
+        Some(architectures) if !architectures.is_empty() => {
+            for arch in architectures {
+                match arch_table(arch) {
+                    Some(table) => tables.push(table),
+                    None => skipped.push(*arch),
+                }
+            }
+        }
+        _ => tables.extend(arch_table(&Arch::ScmpArchX86_64)),
+    }
+    if tables.is_empty() {
+        bail!("no architecture of the profile is supported");
+    }
+
+    let default = action_value(profile.default_action(), profile.default_errno_ret());
+
+    let mut emitter = Emitter::default();
+    let bad_arch = emitter.ret(BAD_ARCH_ACTION);
+
+    let mut next = bad_arch;
+    for table in tables.iter().rev() {
+        let body = emit_arch(&mut emitter, profile, table, default, bad_arch);
+        next = emitter.jump(BPF_JEQ, table.audit_arch, body, next);
+    }
+    emitter.load(ARCH_OFFSET);
+
+    let mut program = emitter.finish()?;
+    program.skipped = skipped;
+    Ok(program)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oci_spec::runtime::{LinuxSeccompArgBuilder, LinuxSeccompBuilder, LinuxSyscallBuilder};
+
+    fn data(name: &str, args: [u64; 6]) -> SeccompData {
+        SeccompData {
+            nr: x86_64::to_syscall_number(name)[0],
+            arch: AUDIT_ARCH_X86_64,
+            instruction_pointer: 0,
+            args,
+        }
+    }
+
+    fn arg(index: usize, value: u64, op: LinuxSeccompOperator) -> Result<LinuxSeccompArg> {
+        Ok(LinuxSeccompArgBuilder::default()
+            .index(index)
+            .value(value)
+            .op(op)
+            .build()?)
+    }
+
+    #[test]
+    fn compile_profile() -> Result<()> {
+        let profile = LinuxSeccompBuilder::default()
+            .default_action(LinuxSeccompAction::ScmpActErrno)
+            .architectures(vec![Arch::ScmpArchX86_64])
+            .syscalls(vec![
+                LinuxSyscallBuilder::default()
+                    .names(vec!["read".to_string(), "write".to_string()])
+                    .action(LinuxSeccompAction::ScmpActAllow)
+                    .build()?,
+                LinuxSyscallBuilder::default()
+                    .names(vec!["personality".to_string()])
+                    .action(LinuxSeccompAction::ScmpActAllow)
+                    .args(vec![arg(0, 0xffff_ffff, LinuxSeccompOperator::ScmpCmpEq)?])
+                    .build()?,
+                LinuxSyscallBuilder::default()
+                    .names(vec!["clone".to_string()])
+                    .action(LinuxSeccompAction::ScmpActAllow)
+                    .args(vec![LinuxSeccompArgBuilder::default()
+                        .index(0usize)
+                        .value(0x7e02_0000u64)
+                        .value_two(0u64)
+                        .op(LinuxSeccompOperator::ScmpCmpMaskedEq)
+                        .build()?])
+                    .build()?,
+                LinuxSyscallBuilder::default()
+                    .names(vec!["mmap".to_string()])
+                    .action(LinuxSeccompAction::ScmpActErrno)
+                    .errno_ret(22u32)
+                    .args(vec![
+                        arg(2, 4, LinuxSeccompOperator::ScmpCmpGe)?,
+                        arg(1, 1 << 32, LinuxSeccompOperator::ScmpCmpLt)?,
+                    ])
+                    .build()?,
+                LinuxSyscallBuilder::default()
+                    .names(vec!["mmap".to_string()])
+                    .action(LinuxSeccompAction::ScmpActAllow)
+                    .build()?,
+            ])
+            .build()?;
+
+        let program = compile(&profile)?;
+        let run = |name, args| program.run(&data(name, args));
+        let errno = |errno| SECCOMP_RET_ERRNO | errno;
+
+        assert_eq!(run("read", [0; 6])?, SECCOMP_RET_ALLOW);
+        assert_eq!(run("write", [0; 6])?, SECCOMP_RET_ALLOW);
+        assert_eq!(run("open", [0; 6])?, errno(EPERM));
+
+        assert_eq!(
+            run("personality", [0xffff_ffff, 0, 0, 0, 0, 0])?,
+            SECCOMP_RET_ALLOW
+        );
+        assert_eq!(
+            run("personality", [0x1_ffff_ffff, 0, 0, 0, 0, 0])?,
+            errno(EPERM)
+        );
+        assert_eq!(run("personality", [8, 0, 0, 0, 0, 0])?, errno(EPERM));
+
+        assert_eq!(run("clone", [0x11, 0, 0, 0, 0, 0])?, SECCOMP_RET_ALLOW);
+        assert_eq!(run("clone", [0x1000_0011, 0, 0, 0, 0, 0])?, errno(EPERM));
+
+        assert_eq!(run("mmap", [0, 0, 4, 0, 0, 0])?, errno(22));
+        assert_eq!(run("mmap", [0, (1 << 32) - 1, 7, 0, 0, 0])?, errno(22));
+        assert_eq!(run("mmap", [0, 1 << 32, 7, 0, 0, 0])?, SECCOMP_RET_ALLOW);
+        assert_eq!(run("mmap", [0, 0, 3, 0, 0, 0])?, SECCOMP_RET_ALLOW);
+
+        let x32 = SeccompData {
+            nr: X32_SYSCALL_BIT,
+            ..data("read", [0; 6])
+        };
+        assert_eq!(program.run(&x32)?, BAD_ARCH_ACTION);
+        let i386 = SeccompData {
+            arch: 0x4000_0003,
+            ..data("read", [0; 6])
+        };
+        assert_eq!(program.run(&i386)?, BAD_ARCH_ACTION);
+
+        Ok(())
+    }
+
+    #[test]
+    fn compile_long_filter() -> Result<()> {
+        // Every syscall with a condition makes jumps longer than 255 instructions
+        let syscalls = x86_64::SYSCALLS
+            .values()
+            .map(|name| {
+                LinuxSyscallBuilder::default()
+                    .names(vec![name.to_string()])
+                    .action(LinuxSeccompAction::ScmpActAllow)
+                    .args(vec![arg(0, 1, LinuxSeccompOperator::ScmpCmpNe)?])
+                    .build()
+                    .map_err(anyhow::Error::from)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let profile = LinuxSeccompBuilder::default()
+            .default_action(LinuxSeccompAction::ScmpActKillProcess)
+            .syscalls(syscalls)
+            .build()?;
+
+        let program = compile(&profile)?;
+        assert!(program.len() > 1024);
+        for name in ["read", "openat", "io_uring_setup"] {
+            assert_eq!(program.run(&data(name, [0; 6]))?, SECCOMP_RET_ALLOW);
+            assert_eq!(
+                program.run(&data(name, [1, 0, 0, 0, 0, 0]))?,
+                SECCOMP_RET_KILL_PROCESS
+            );
+        }
+        assert_eq!(program.to_bytes().len(), program.len() * 8);
+
+        Ok(())
+    }
+}
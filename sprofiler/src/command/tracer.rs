@@ -1,19 +1,21 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::fs::{self, File};
 use std::io;
 use std::os::unix::fs::MetadataExt;
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::{Duration, SystemTime};
 
 use anyhow::{Context, Result};
 use lazy_static::lazy_static;
 use libbpf_rs::Error;
-use libbpf_rs::PerfBufferBuilder;
+use libbpf_rs::{PerfBuffer, PerfBufferBuilder};
 use nix::sys::signal::{kill, Signal};
 use nix::unistd::Pid;
 use plain::Plain;
+use serde::Serialize;
 use signal_hook::consts::*;
 use signal_hook::iterator::exfiltrator::WithOrigin;
 use signal_hook::iterator::SignalsInfo;
@@ -21,15 +23,41 @@ use sprofiler_sys::arch::x86_64::SYSCALLS;
 
 use crate::bpf::*;
 use crate::dynamic::annotation;
+use crate::dynamic::capability::{CapabilityReport, CapabilityTracker};
+use crate::dynamic::file_access::{self, FileAccessReport, FileAccessTracker};
+use crate::dynamic::io_uring::{self, IoUringReport, IoUringTracker};
+use crate::dynamic::network::{NetworkReport, NetworkTracker, SocketAddress};
 use crate::dynamic::process;
+use crate::dynamic::runtime::{self, RuntimeInfo, RuntimeReport};
+use crate::kubernetes;
 use crate::oci::State;
+use crate::profile::form::ProfileForm;
+use crate::profile_util::{self, ProfileDefaults};
+use crate::provenance::{self, Generator, Provenance};
+use crate::systemd;
 
 use oci_spec::runtime::{
-    Arch, LinuxSeccomp, LinuxSeccompAction, LinuxSeccompBuilder, LinuxSyscallBuilder,
+    Arch, LinuxSeccomp, LinuxSeccompAction, LinuxSeccompBuilder, LinuxSyscallBuilder, Spec,
 };
 
+const PATH_LEN: usize = 256;
+const SOCKADDR_LEN: usize = 128;
+
+/// Syscall events of the container and events lost by any perf buffer
+static EVENTS: AtomicU64 = AtomicU64::new(0);
+static LOST_EVENTS: AtomicU64 = AtomicU64::new(0);
+
 lazy_static! {
     static ref SYSCALL_LIST: Mutex<HashSet<&'static str>> = Mutex::new(HashSet::new());
+    /// processes (comm) which called each syscall
+    static ref SYSCALL_PROCESSES: Mutex<BTreeMap<&'static str, BTreeSet<String>>> =
+        Mutex::new(BTreeMap::new());
+    /// syscall numbers missing from the syscall table and their counts
+    static ref UNKNOWN_SYSCALLS: Mutex<BTreeMap<u32, u64>> = Mutex::new(BTreeMap::new());
+    static ref FILE_ACCESS: Mutex<FileAccessTracker> = Mutex::new(FileAccessTracker::default());
+    static ref CAPABILITIES: Mutex<CapabilityTracker> = Mutex::new(CapabilityTracker::default());
+    static ref NETWORK: Mutex<NetworkTracker> = Mutex::new(NetworkTracker::default());
+    static ref IO_URING: Mutex<IoUringTracker> = Mutex::new(IoUringTracker::default());
 }
 
 #[repr(C)]
@@ -43,31 +71,205 @@ struct SysEnterEvent {
 
 unsafe impl Plain for SysEnterEvent {}
 
+#[repr(C)]
+struct FileEvent {
+    pub syscall_nr: i64,
+    pub flags: i64,
+    pub comm: [u8; 32],
+    pub path: [u8; PATH_LEN],
+}
+
+impl Default for FileEvent {
+    fn default() -> Self {
+        FileEvent {
+            syscall_nr: 0,
+            flags: 0,
+            comm: [0; 32],
+            path: [0; PATH_LEN],
+        }
+    }
+}
+
+unsafe impl Plain for FileEvent {}
+
+#[repr(C)]
+struct NetEvent {
+    pub pid: u32,
+    pub addrlen: u32,
+    pub syscall_nr: i64,
+    pub args: [i64; 3],
+    pub comm: [u8; 32],
+    pub addr: [u8; SOCKADDR_LEN],
+}
+
+impl Default for NetEvent {
+    fn default() -> Self {
+        NetEvent {
+            pid: 0,
+            addrlen: 0,
+            syscall_nr: 0,
+            args: [0; 3],
+            comm: [0; 32],
+            addr: [0; SOCKADDR_LEN],
+        }
+    }
+}
+
+unsafe impl Plain for NetEvent {}
+
+#[repr(C)]
+#[derive(Default, Debug)]
+struct CapEvent {
+    pub cap: i32,
+    pub opts: u32,
+    pub ret: i32,
+    pub comm: [u8; 32],
+}
+
+unsafe impl Plain for CapEvent {}
+
+#[repr(C)]
+#[derive(Default, Debug)]
+struct UringEvent {
+    pub opcode: u32,
+    pub comm: [u8; 32],
+}
+
+unsafe impl Plain for UringEvent {}
+
+fn c_str_to_string(buf: &[u8]) -> String {
+    let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    String::from_utf8_lossy(&buf[..len]).into_owned()
+}
+
 fn handle_event(_cpu: i32, data: &[u8]) {
     let mut event = SysEnterEvent::default();
     plain::copy_from_bytes(&mut event, data).expect("Data buffer was too short or invalid");
 
-    let syscall_name = SYSCALLS.get(&(event.syscall_nr as u32));
+    if event.syscall_nr < 0 {
+        return;
+    }
+
+    EVENTS.fetch_add(1, Ordering::Relaxed);
+    let syscall_nr = event.syscall_nr as u32;
 
-    if let Some(syscall_name) = syscall_name {
+    if let Some(syscall_name) = SYSCALLS.get(&syscall_nr) {
         let mut syscall_list = SYSCALL_LIST.lock().unwrap();
         syscall_list.insert(syscall_name);
+        SYSCALL_PROCESSES
+            .lock()
+            .unwrap()
+            .entry(syscall_name)
+            .or_default()
+            .insert(c_str_to_string(&event.comm));
+    } else {
+        let mut unknown_syscalls = UNKNOWN_SYSCALLS.lock().unwrap();
+        *unknown_syscalls.entry(syscall_nr).or_default() += 1;
+    }
+}
+
+fn handle_file_event(_cpu: i32, data: &[u8]) {
+    let mut event = FileEvent::default();
+    plain::copy_from_bytes(&mut event, data).expect("Data buffer was too short or invalid");
+
+    let syscall_name = match SYSCALLS.get(&(event.syscall_nr as u32)) {
+        Some(syscall_name) => syscall_name,
+        None => return,
+    };
+
+    if let Some(mode) = file_access::access_mode(syscall_name, event.flags) {
+        let path = c_str_to_string(&event.path);
+        if !path.is_empty() {
+            FILE_ACCESS.lock().unwrap().record(path, mode);
+        }
     }
 }
 
+fn handle_net_event(_cpu: i32, data: &[u8]) {
+    let mut event = NetEvent::default();
+    plain::copy_from_bytes(&mut event, data).expect("Data buffer was too short or invalid");
+
+    let addr = &event.addr[..(event.addrlen as usize).min(SOCKADDR_LEN)];
+    let mut network = NETWORK.lock().unwrap();
+
+    match SYSCALLS.get(&(event.syscall_nr as u32)) {
+        Some(&"socket") => network.record_socket(event.args[0], event.args[1]),
+        Some(&"bind") => {
+            if let Some(addr) = SocketAddress::from_bytes(addr) {
+                network.record_bind(event.pid, event.args[0], addr);
+            }
+        }
+        Some(&"listen") => network.record_listen(event.pid, event.args[0]),
+        Some(&"connect") => {
+            if let Some(addr) = SocketAddress::from_bytes(addr) {
+                network.record_connect(addr);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn handle_cap_event(_cpu: i32, data: &[u8]) {
+    let mut event = CapEvent::default();
+    plain::copy_from_bytes(&mut event, data).expect("Data buffer was too short or invalid");
+
+    CAPABILITIES.lock().unwrap().record(
+        event.cap as u32,
+        event.opts,
+        event.ret,
+        c_str_to_string(&event.comm),
+    );
+}
+
+fn handle_uring_event(_cpu: i32, data: &[u8]) {
+    let mut event = UringEvent::default();
+    plain::copy_from_bytes(&mut event, data).expect("Data buffer was too short or invalid");
+
+    IO_URING.lock().unwrap().record(event.opcode as u8);
+}
+
 fn handle_lost_event(cpu: i32, count: u64) {
+    LOST_EVENTS.fetch_add(count, Ordering::Relaxed);
     eprintln!("Lost event (CPU: {}, COUNT: {})", cpu, count);
 }
 
-fn gen_seccomp_rule() -> anyhow::Result<LinuxSeccomp> {
-    let syscall_list = SYSCALL_LIST.lock().unwrap();
+fn gen_seccomp_rule(
+    deny_io_uring: bool,
+    runtime_syscalls: &[&'static str],
+    defaults: &ProfileDefaults,
+) -> anyhow::Result<LinuxSeccomp> {
+    let mut syscall_list = SYSCALL_LIST.lock().unwrap().clone();
+    syscall_list.extend(runtime_syscalls);
+
+    let io_uring_syscalls = io_uring::detect(syscall_list.iter().copied());
+    if !io_uring_syscalls.is_empty() {
+        eprintln!(
+            "WARNING: {:?} found. io_uring operations bypass seccomp, the profile is incomplete",
+            io_uring_syscalls
+        );
+    }
+
     let mut syscall_list: Vec<String> = syscall_list
-        .clone()
         .into_iter()
+        .filter(|s| !(deny_io_uring && io_uring::IO_URING_SYSCALLS.contains(s)))
         .map(|s| s.to_string())
         .collect();
     syscall_list.sort();
 
+    // libseccomp ignores names it can't resolve and syscall numbers are
+    // architecture specific, so numbers missing from the syscall table can't
+    // be put in the profile. They are reported to be added by hand.
+    let unknown_syscalls = UNKNOWN_SYSCALLS.lock().unwrap();
+    if !unknown_syscalls.is_empty() {
+        eprintln!(
+            "WARNING: {} unknown syscall numbers are missing from the profile:",
+            unknown_syscalls.len()
+        );
+        for (nr, count) in unknown_syscalls.iter() {
+            eprintln!("  syscall {} (called {} times)", nr, count);
+        }
+    }
+
     let architectures = vec![Arch::ScmpArchX86, Arch::ScmpArchX86_64];
 
     let seccomp_profile = LinuxSeccompBuilder::default()
@@ -79,7 +281,203 @@ fn gen_seccomp_rule() -> anyhow::Result<LinuxSeccomp> {
             .build()?])
         .build()?;
 
-    Ok(seccomp_profile)
+    for warning in defaults.warnings(&seccomp_profile) {
+        eprintln!("{}", warning);
+    }
+    defaults.apply(&seccomp_profile)
+}
+
+fn load_container_spec(state: &State) -> Result<Spec> {
+    let path = state.bundle.join("config.json");
+    let spec = Spec::load(&path).with_context(|| format!("failed to load {}", path.display()))?;
+    Ok(spec)
+}
+
+fn no_new_privileges(state: &State) -> bool {
+    load_container_spec(state)
+        .ok()
+        .and_then(|spec| spec.process().as_ref()?.no_new_privileges())
+        .unwrap_or(false)
+}
+
+fn write_runtime_report(
+    state: &State,
+    runtime: Option<RuntimeInfo>,
+    no_new_privileges: bool,
+    runtime_syscalls: &[&'static str],
+) -> Result<()> {
+    let syscall_list = SYSCALL_LIST.lock().unwrap();
+    let added: Vec<String> = runtime_syscalls
+        .iter()
+        .filter(|syscall| !syscall_list.contains(*syscall))
+        .map(|syscall| syscall.to_string())
+        .collect();
+
+    if !added.is_empty() {
+        eprintln!(
+            "Added syscalls required by the container runtime after seccomp is applied: {:?}",
+            added
+        );
+    }
+
+    let report = RuntimeReport {
+        runtime,
+        no_new_privileges,
+        added,
+    };
+
+    if let Some(path) = annotation::get_report_path(state, "runtime") {
+        let file = File::create(&path)?;
+        serde_json::to_writer_pretty(file, &report)?;
+    }
+
+    Ok(())
+}
+
+fn gen_provenance(
+    state: &State,
+    runtime: Option<RuntimeInfo>,
+    trace_start: SystemTime,
+) -> Provenance {
+    let mut provenance = Provenance::new(Generator::Dynamic).with_kernel();
+    if let Some(annotations) = &state.annotations {
+        provenance = provenance.with_image(annotations);
+    }
+
+    provenance.container_id = Some(state.id.clone());
+    provenance.runtime = runtime.map(|info| match info.version {
+        Some(version) => format!("{} {}", info.runtime, version),
+        None => info.runtime.to_string(),
+    });
+    provenance.trace_start = Some(provenance::format_timestamp(trace_start));
+    provenance.trace_end = Some(provenance::format_timestamp(SystemTime::now()));
+    provenance.events = Some(EVENTS.load(Ordering::Relaxed));
+    provenance.lost_events = Some(LOST_EVENTS.load(Ordering::Relaxed));
+    provenance.syscall_processes = SYSCALL_PROCESSES
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(syscall, processes)| (syscall.to_string(), processes.clone()))
+        .collect();
+
+    provenance
+}
+
+fn write_kubernetes_resources(state: &State, profile: &LinuxSeccomp) -> Result<()> {
+    let name = state
+        .annotations
+        .as_ref()
+        .and_then(kubernetes::ProfileName::from_annotations)
+        .map(Ok)
+        .unwrap_or_else(|| kubernetes::ProfileName::new(&state.id, None))?;
+
+    if let Some(path) = annotation::get_output_path(state, "seccompprofile", "yaml") {
+        let resource = kubernetes::SeccompProfileResource::new(&name, profile)?;
+        fs::write(&path, resource.to_yaml()?)?;
+    }
+
+    if let Some(path) = annotation::get_output_path(state, "security-context", "yaml") {
+        let snippet = kubernetes::SecurityContextSnippet::localhost(&name.operator_profile());
+        fs::write(&path, snippet.to_yaml()?)?;
+    }
+
+    Ok(())
+}
+
+/// x86_64 syscall numbers missing from the syscall table
+#[derive(Serialize)]
+struct UnknownSyscall {
+    number: u32,
+    count: u64,
+}
+
+fn write_unknown_syscalls_report(state: &State) -> Result<()> {
+    let unknown_syscalls = UNKNOWN_SYSCALLS.lock().unwrap();
+    if unknown_syscalls.is_empty() {
+        return Ok(());
+    }
+
+    let report: Vec<UnknownSyscall> = unknown_syscalls
+        .iter()
+        .map(|(number, count)| UnknownSyscall {
+            number: *number,
+            count: *count,
+        })
+        .collect();
+
+    if let Some(path) = annotation::get_report_path(state, "unknown-syscalls") {
+        let file = File::create(&path)?;
+        serde_json::to_writer_pretty(file, &report)?;
+    }
+
+    Ok(())
+}
+
+fn write_file_access_report(state: &State) -> Result<()> {
+    let path = match annotation::get_report_path(state, "file-access") {
+        Some(path) => path,
+        None => return Ok(()),
+    };
+
+    let spec = load_container_spec(state)?;
+    let mounts = spec.mounts().clone().unwrap_or_default();
+
+    let tracker = FILE_ACCESS.lock().unwrap();
+    let report = FileAccessReport::new(&tracker, &mounts);
+
+    let file = File::create(&path)?;
+    serde_json::to_writer_pretty(file, &report)?;
+
+    Ok(())
+}
+
+fn write_capability_report(state: &State) -> Result<()> {
+    let tracker = CAPABILITIES.lock().unwrap();
+    let report = CapabilityReport::new(&tracker);
+
+    if let Some(path) = annotation::get_report_path(state, "capabilities") {
+        let file = File::create(&path)?;
+        serde_json::to_writer_pretty(file, &report)?;
+    }
+
+    if let Some(path) = annotation::get_report_path(state, "oci-capabilities") {
+        let file = File::create(&path)?;
+        serde_json::to_writer_pretty(file, &report.snippet())?;
+    }
+
+    Ok(())
+}
+
+fn write_io_uring_report(state: &State, deny_io_uring: bool) -> Result<()> {
+    let path = match annotation::get_report_path(state, "io-uring") {
+        Some(path) => path,
+        None => return Ok(()),
+    };
+
+    let syscall_list = SYSCALL_LIST.lock().unwrap();
+    let syscalls = io_uring::detect(syscall_list.iter().copied());
+    let allowed: BTreeSet<String> = syscall_list.iter().map(|s| s.to_string()).collect();
+
+    let report = IoUringReport::new(&IO_URING.lock().unwrap(), syscalls, &allowed, deny_io_uring);
+
+    let file = File::create(&path)?;
+    serde_json::to_writer_pretty(file, &report)?;
+
+    Ok(())
+}
+
+fn write_network_report(state: &State) -> Result<()> {
+    let path = match annotation::get_report_path(state, "network") {
+        Some(path) => path,
+        None => return Ok(()),
+    };
+
+    let report = NetworkReport::new(&NETWORK.lock().unwrap());
+
+    let file = File::create(&path)?;
+    serde_json::to_writer_pretty(file, &report)?;
+
+    Ok(())
 }
 
 /// only work when cgroup driver is systemd & v2 on podman
@@ -94,37 +492,172 @@ fn get_contianer_cgroup_id(container_id: &str) -> Result<u64> {
     Ok(meta.ino())
 }
 
-fn start_tracing(spinlock: Arc<AtomicBool>, state: &State) -> Result<()> {
+fn poll(perf: &PerfBuffer, timeout: Duration) -> Result<()> {
+    match perf.poll(timeout) {
+        Ok(()) | Err(Error::System(4)) => Ok(()), // EINTER
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn start_tracing(
+    spinlock: Arc<AtomicBool>,
+    state: &State,
+    runtime: Option<RuntimeInfo>,
+) -> Result<()> {
     let skel_builder = SystraceSkelBuilder::default();
     let mut systrace_skel = skel_builder.open()?;
 
+    let trace_file_access = annotation::is_enabled(state, annotation::TRACE_FILE_ACCESS_ANNOTATION);
+    let trace_capabilities =
+        annotation::is_enabled(state, annotation::TRACE_CAPABILITIES_ANNOTATION);
+    let trace_network = annotation::is_enabled(state, annotation::TRACE_NETWORK_ANNOTATION);
+    let trace_io_uring = annotation::is_enabled(state, annotation::TRACE_IO_URING_ANNOTATION);
+    let deny_io_uring = annotation::is_enabled(state, annotation::DENY_IO_URING_ANNOTATION);
+
     systrace_skel.rodata().target_cgid = get_contianer_cgroup_id(&state.id)?;
+    systrace_skel.rodata().trace_file_access = trace_file_access;
+    systrace_skel.rodata().trace_capabilities = trace_capabilities;
+    systrace_skel.rodata().trace_network = trace_network;
+    systrace_skel.rodata().trace_io_uring = trace_io_uring;
 
     let mut skel = systrace_skel.load()?;
 
-    skel.attach()?;
+    // io_uring tracepoints differ between kernel versions, so programs are attached one by one
+    let mut links = vec![skel
+        .progs_mut()
+        .tracepoint__raw_syscalls__sys_enter()
+        .attach()?];
+
+    if trace_capabilities {
+        links.push(skel.progs_mut().kprobe__cap_capable().attach()?);
+        links.push(skel.progs_mut().kretprobe__cap_capable().attach()?);
+    }
+
+    if trace_io_uring {
+        let link = skel
+            .progs_mut()
+            .tracepoint__io_uring__io_uring_submit_req()
+            .attach()
+            .or_else(|_| {
+                skel.progs_mut()
+                    .tracepoint__io_uring__io_uring_submit_sqe()
+                    .attach()
+            });
+        match link {
+            Ok(link) => links.push(link),
+            Err(e) => eprintln!("failed to attach io_uring tracepoint: {}", e),
+        }
+    }
 
     let perf = PerfBufferBuilder::new(skel.maps().sys_enter_events())
         .sample_cb(handle_event)
         .lost_cb(handle_lost_event)
         .build()?;
 
+    // Buffers of the optional modes are only built when enabled
+    let mut optional_perfs = vec![];
+    if trace_file_access {
+        optional_perfs.push(
+            PerfBufferBuilder::new(skel.maps().file_events())
+                .sample_cb(handle_file_event)
+                .lost_cb(handle_lost_event)
+                .build()?,
+        );
+    }
+    if trace_capabilities {
+        optional_perfs.push(
+            PerfBufferBuilder::new(skel.maps().cap_events())
+                .sample_cb(handle_cap_event)
+                .lost_cb(handle_lost_event)
+                .build()?,
+        );
+    }
+    if trace_network {
+        optional_perfs.push(
+            PerfBufferBuilder::new(skel.maps().net_events())
+                .sample_cb(handle_net_event)
+                .lost_cb(handle_lost_event)
+                .build()?,
+        );
+    }
+    if trace_io_uring {
+        optional_perfs.push(
+            PerfBufferBuilder::new(skel.maps().uring_events())
+                .sample_cb(handle_uring_event)
+                .lost_cb(handle_lost_event)
+                .build()?,
+        );
+    }
+
+    let trace_start = SystemTime::now();
     while spinlock.load(Ordering::Relaxed) {
-        match perf.poll(std::time::Duration::from_millis(100)) {
-            Ok(()) | Err(Error::System(4)) => {} // EINTER
-            Err(e) => return Err(e.into()),
-        };
+        // Only the sys_enter buffer blocks, the others are drained without waiting
+        // so that they don't delay it
+        poll(&perf, Duration::from_millis(100))?;
+        for perf in &optional_perfs {
+            poll(perf, Duration::ZERO)?;
+        }
     }
 
+    let no_new_privileges = no_new_privileges(state);
+    let runtime_syscalls = runtime::post_filter_syscalls(runtime, no_new_privileges);
+
     if let Some(path) = annotation::get_trace_target_path(state) {
-        let file = File::create(path)?;
-        serde_json::to_writer(file, &gen_seccomp_rule()?)?;
+        let defaults = annotation::get_profile_defaults(state);
+        let profile = gen_seccomp_rule(deny_io_uring, &runtime_syscalls, &defaults)?;
+        let mut provenance = gen_provenance(state, runtime, trace_start);
+        provenance.form = Some(ProfileForm::of(&profile));
+        let annotations = if annotation::is_enabled(state, annotation::EMBED_PROVENANCE_ANNOTATION)
+        {
+            provenance.annotations()?
+        } else {
+            BTreeMap::new()
+        };
+        let warnings = profile_util::write_annotated_seccomp_profile(
+            &path,
+            &profile,
+            annotation::get_output_format(state),
+            &annotations,
+        )?;
+        for issue in warnings {
+            eprintln!("{}", issue);
+        }
+        provenance.write(&path).context("write_provenance: ")?;
+        write_runtime_report(state, runtime, no_new_privileges, &runtime_syscalls)
+            .context("write_runtime_report: ")?;
+        write_unknown_syscalls_report(state).context("write_unknown_syscalls_report: ")?;
+
+        if annotation::is_enabled(state, annotation::OUTPUT_KUBERNETES_ANNOTATION) {
+            write_kubernetes_resources(state, &profile).context("write_kubernetes_resources: ")?;
+        }
+
+        if annotation::is_enabled(state, annotation::OUTPUT_SYSTEMD_ANNOTATION) {
+            if let Some(path) = annotation::get_output_path(state, "systemd", "conf") {
+                fs::write(&path, systemd::export(&profile, false).to_string())?;
+            }
+        }
+    }
+
+    if trace_file_access {
+        write_file_access_report(state).context("write_file_access_report: ")?;
+    }
+
+    if trace_capabilities {
+        write_capability_report(state).context("write_capability_report: ")?;
+    }
+
+    if trace_network {
+        write_network_report(state).context("write_network_report: ")?;
+    }
+
+    if trace_io_uring {
+        write_io_uring_report(state, deny_io_uring).context("write_io_uring_report: ")?;
     }
 
     Ok(())
 }
 
-pub fn trace_command() -> Result<()> {
+pub fn trace_command(runtime: Option<RuntimeInfo>) -> Result<()> {
     let state = process::container_state_load_from_reader(io::stdin()).expect("state load error:");
 
     let pid = std::process::id() as i32;
@@ -147,7 +680,7 @@ pub fn trace_command() -> Result<()> {
         }
     });
 
-    start_tracing(spinlock, &state).context("start_tracing: ")?;
+    start_tracing(spinlock, &state, runtime).context("start_tracing: ")?;
 
     th.join().expect("thread join: ");
 
@@ -162,3 +695,40 @@ pub fn stop_tracing() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profile_util::SyscallAccess;
+
+    #[test]
+    fn gen_denylist_seccomp_rule() -> Result<()> {
+        SYSCALL_LIST
+            .lock()
+            .unwrap()
+            .extend(["read", "write", "exit_group"]);
+        let defaults = ProfileDefaults {
+            form: Some(ProfileForm::Denylist),
+            ..Default::default()
+        };
+
+        let profile = gen_seccomp_rule(false, &["execve"], &defaults)?;
+        assert_eq!(ProfileForm::of(&profile), ProfileForm::Denylist);
+        // SCMP_ARCH_X86 has no syscall table
+        assert_eq!(profile.architectures(), &Some(vec![Arch::ScmpArchX86_64]));
+        for (name, access) in [
+            ("read", SyscallAccess::Allowed),
+            ("execve", SyscallAccess::Allowed),
+            ("ptrace", SyscallAccess::Denied),
+        ] {
+            assert_eq!(
+                profile_util::syscall_access(&profile, name),
+                access,
+                "{}",
+                name
+            );
+        }
+
+        Ok(())
+    }
+}
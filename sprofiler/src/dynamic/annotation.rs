@@ -1,8 +1,31 @@
 use std::path::PathBuf;
+use std::str::FromStr;
 
 use crate::oci::State;
+use crate::profile::form::ProfileForm;
+use crate::profile_util::{DefaultAction, FilterFlag, ProfileDefaults, ProfileFormat};
 
 const SPROFILER_OCI_ANNOTATION: &str = "io.sprofiler.output_seccomp_profile_path";
+pub const TRACE_FILE_ACCESS_ANNOTATION: &str = "io.sprofiler.trace_file_access";
+pub const TRACE_CAPABILITIES_ANNOTATION: &str = "io.sprofiler.trace_capabilities";
+pub const TRACE_NETWORK_ANNOTATION: &str = "io.sprofiler.trace_network";
+pub const TRACE_IO_URING_ANNOTATION: &str = "io.sprofiler.trace_io_uring";
+pub const DENY_IO_URING_ANNOTATION: &str = "io.sprofiler.deny_io_uring";
+pub const OUTPUT_KUBERNETES_ANNOTATION: &str = "io.sprofiler.output_kubernetes";
+pub const OUTPUT_SYSTEMD_ANNOTATION: &str = "io.sprofiler.output_systemd";
+pub const EMBED_PROVENANCE_ANNOTATION: &str = "io.sprofiler.embed_provenance";
+const OUTPUT_FORMAT_ANNOTATION: &str = "io.sprofiler.output_format";
+const DEFAULT_ACTION_ANNOTATION: &str = "io.sprofiler.default_action";
+const DEFAULT_ERRNO_ANNOTATION: &str = "io.sprofiler.default_errno";
+const SECCOMP_FLAGS_ANNOTATION: &str = "io.sprofiler.seccomp_flags";
+const PROFILE_FORM_ANNOTATION: &str = "io.sprofiler.profile_form";
+
+fn get_annotation<'a>(state: &'a State, annotation: &str) -> Option<&'a String> {
+    state
+        .annotations
+        .as_ref()
+        .and_then(|annotations| annotations.get(annotation))
+}
 
 pub fn get_trace_target_path(state: &State) -> Option<PathBuf> {
     if let Some(annotations) = &state.annotations {
@@ -11,3 +34,78 @@ pub fn get_trace_target_path(state: &State) -> Option<PathBuf> {
         None
     }
 }
+
+/// "oci" (default) or "moby"
+pub fn get_output_format(state: &State) -> ProfileFormat {
+    let format = get_annotation(state, OUTPUT_FORMAT_ANNOTATION);
+
+    match format.map(|format| ProfileFormat::from_str(format)) {
+        Some(Ok(format)) => format,
+        Some(Err(e)) => {
+            eprintln!("{}, write the profile in the OCI format", e);
+            ProfileFormat::Oci
+        }
+        None => ProfileFormat::Oci,
+    }
+}
+
+/// Invalid values are reported and ignored, e.g.
+/// io.sprofiler.default_action=kill-process, io.sprofiler.seccomp_flags=log,spec-allow,
+/// io.sprofiler.profile_form=auto
+pub fn get_profile_defaults(state: &State) -> ProfileDefaults {
+    let mut defaults = ProfileDefaults::default();
+
+    if let Some(action) = get_annotation(state, DEFAULT_ACTION_ANNOTATION) {
+        match DefaultAction::from_str(action) {
+            Ok(action) => defaults.default_action = Some(action),
+            Err(e) => eprintln!("{}, keep the default action", e),
+        }
+    }
+
+    if let Some(errno) = get_annotation(state, DEFAULT_ERRNO_ANNOTATION) {
+        match errno.parse() {
+            Ok(errno) => defaults.default_errno = Some(errno),
+            Err(_) => eprintln!("invalid default errno: {}, keep the default errno", errno),
+        }
+    }
+
+    if let Some(flags) = get_annotation(state, SECCOMP_FLAGS_ANNOTATION) {
+        for flag in flags
+            .split(',')
+            .map(str::trim)
+            .filter(|flag| !flag.is_empty())
+        {
+            match FilterFlag::from_str(flag) {
+                Ok(flag) => defaults.flags.push(flag),
+                Err(e) => eprintln!("{}, skip it", e),
+            }
+        }
+    }
+
+    if let Some(form) = get_annotation(state, PROFILE_FORM_ANNOTATION) {
+        match ProfileForm::from_str(form) {
+            Ok(form) => defaults.form = Some(form),
+            Err(e) => eprintln!("{}, keep the allowlist form", e),
+        }
+    }
+
+    defaults
+}
+
+/// Optional tracing modes are enabled by setting the annotation to "true"
+pub fn is_enabled(state: &State, annotation: &str) -> bool {
+    get_annotation(state, annotation)
+        .map(|value| value == "true")
+        .unwrap_or(false)
+}
+
+/// Reports are written next to the seccomp profile.
+/// e.g. seccomp-profile.json -> seccomp-profile.file-access.json
+pub fn get_report_path(state: &State, kind: &str) -> Option<PathBuf> {
+    get_output_path(state, kind, "json")
+}
+
+/// e.g. seccomp-profile.json -> seccomp-profile.seccompprofile.yaml
+pub fn get_output_path(state: &State, kind: &str, extension: &str) -> Option<PathBuf> {
+    get_trace_target_path(state).map(|path| path.with_extension(format!("{}.{}", kind, extension)))
+}
@@ -0,0 +1,204 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+
+/// cap_capable() option: the check is speculative and doesn't need to succeed
+pub const CAP_OPT_NOAUDIT: u32 = 0b10;
+
+/// Index is the capability number (include/uapi/linux/capability.h)
+const CAPABILITIES: [&str; 41] = [
+    "CAP_CHOWN",
+    "CAP_DAC_OVERRIDE",
+    "CAP_DAC_READ_SEARCH",
+    "CAP_FOWNER",
+    "CAP_FSETID",
+    "CAP_KILL",
+    "CAP_SETGID",
+    "CAP_SETUID",
+    "CAP_SETPCAP",
+    "CAP_LINUX_IMMUTABLE",
+    "CAP_NET_BIND_SERVICE",
+    "CAP_NET_BROADCAST",
+    "CAP_NET_ADMIN",
+    "CAP_NET_RAW",
+    "CAP_IPC_LOCK",
+    "CAP_IPC_OWNER",
+    "CAP_SYS_MODULE",
+    "CAP_SYS_RAWIO",
+    "CAP_SYS_CHROOT",
+    "CAP_SYS_PTRACE",
+    "CAP_SYS_PACCT",
+    "CAP_SYS_ADMIN",
+    "CAP_SYS_BOOT",
+    "CAP_SYS_NICE",
+    "CAP_SYS_RESOURCE",
+    "CAP_SYS_TIME",
+    "CAP_SYS_TTY_CONFIG",
+    "CAP_MKNOD",
+    "CAP_LEASE",
+    "CAP_AUDIT_WRITE",
+    "CAP_AUDIT_CONTROL",
+    "CAP_SETFCAP",
+    "CAP_MAC_OVERRIDE",
+    "CAP_MAC_ADMIN",
+    "CAP_SYSLOG",
+    "CAP_WAKE_ALARM",
+    "CAP_BLOCK_SUSPEND",
+    "CAP_AUDIT_READ",
+    "CAP_PERFMON",
+    "CAP_BPF",
+    "CAP_CHECKPOINT_RESTORE",
+];
+
+pub fn capability_name(cap: u32) -> String {
+    CAPABILITIES
+        .get(cap as usize)
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| format!("CAP_{}", cap))
+}
+
+#[derive(Default, Debug)]
+struct Usage {
+    count: u64,
+    comms: BTreeSet<String>,
+}
+
+#[derive(Default, Debug)]
+pub struct CapabilityTracker {
+    usages: BTreeMap<u32, Usage>,
+}
+
+impl CapabilityTracker {
+    /// Only checks which succeeded (ret 0) count, denied checks don't make the
+    /// capability necessary
+    pub fn record(&mut self, cap: u32, opts: u32, ret: i32, comm: String) {
+        if ret != 0 || opts & CAP_OPT_NOAUDIT != 0 {
+            return;
+        }
+
+        let usage = self.usages.entry(cap).or_default();
+        usage.count += 1;
+        usage.comms.insert(comm);
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CapabilityUsage {
+    pub name: String,
+    pub count: u64,
+    pub comms: Vec<String>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CapabilityReport {
+    pub capabilities: Vec<CapabilityUsage>,
+    /// e.g. ["--cap-drop=all", "--cap-add=CAP_CHOWN"]
+    pub podman_args: Vec<String>,
+}
+
+/// Can be used as `process.capabilities` of the OCI runtime config
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CapabilitiesSnippet {
+    pub bounding: Vec<String>,
+    pub effective: Vec<String>,
+    /// Empty, capabilities in it are kept across execve(2) of non-root processes
+    /// (CVE-2022-24769, CVE-2022-27651)
+    pub inheritable: Vec<String>,
+    pub permitted: Vec<String>,
+    /// Empty for the same reason as inheritable
+    pub ambient: Vec<String>,
+}
+
+impl CapabilityReport {
+    pub fn new(tracker: &CapabilityTracker) -> Self {
+        let capabilities: Vec<CapabilityUsage> = tracker
+            .usages
+            .iter()
+            .map(|(cap, usage)| CapabilityUsage {
+                name: capability_name(*cap),
+                count: usage.count,
+                comms: usage.comms.iter().cloned().collect(),
+            })
+            .collect();
+
+        let mut podman_args = vec!["--cap-drop=all".to_string()];
+        podman_args.extend(
+            capabilities
+                .iter()
+                .map(|usage| format!("--cap-add={}", usage.name)),
+        );
+
+        CapabilityReport {
+            capabilities,
+            podman_args,
+        }
+    }
+
+    pub fn snippet(&self) -> CapabilitiesSnippet {
+        let names: Vec<String> = self
+            .capabilities
+            .iter()
+            .map(|usage| usage.name.clone())
+            .collect();
+
+        CapabilitiesSnippet {
+            bounding: names.clone(),
+            effective: names.clone(),
+            inheritable: vec![],
+            permitted: names,
+            ambient: vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capability_name_from_number() {
+        assert_eq!(capability_name(0), "CAP_CHOWN");
+        assert_eq!(capability_name(21), "CAP_SYS_ADMIN");
+        assert_eq!(capability_name(40), "CAP_CHECKPOINT_RESTORE");
+        assert_eq!(capability_name(63), "CAP_63");
+    }
+
+    #[test]
+    fn report_ignore_noaudit_checks() {
+        let mut tracker = CapabilityTracker::default();
+        tracker.record(10, 0, 0, "nginx".to_string());
+        tracker.record(10, 0, 0, "nginx".to_string());
+        tracker.record(21, CAP_OPT_NOAUDIT, 0, "nginx".to_string());
+        tracker.record(0, 0, 0, "chown".to_string());
+        // EPERM, the process works without it
+        tracker.record(12, 0, -1, "nginx".to_string());
+
+        let report = CapabilityReport::new(&tracker);
+
+        assert_eq!(report.capabilities.len(), 2);
+        assert_eq!(report.capabilities[0].name, "CAP_CHOWN");
+        assert_eq!(report.capabilities[1].name, "CAP_NET_BIND_SERVICE");
+        assert_eq!(report.capabilities[1].count, 2);
+        assert_eq!(
+            report.podman_args,
+            vec![
+                "--cap-drop=all".to_string(),
+                "--cap-add=CAP_CHOWN".to_string(),
+                "--cap-add=CAP_NET_BIND_SERVICE".to_string(),
+            ]
+        );
+
+        let snippet = report.snippet();
+        assert_eq!(
+            snippet.bounding,
+            vec!["CAP_CHOWN".to_string(), "CAP_NET_BIND_SERVICE".to_string()]
+        );
+        assert_eq!(snippet.bounding, snippet.effective);
+        assert_eq!(snippet.bounding, snippet.permitted);
+        assert!(snippet.inheritable.is_empty());
+        assert!(snippet.ambient.is_empty());
+    }
+}
@@ -0,0 +1,242 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use oci_spec::runtime::Mount;
+use serde::Serialize;
+
+const O_ACCMODE: i64 = 0o3;
+const O_RDONLY: i64 = 0o0;
+const O_CREAT: i64 = 0o100;
+const O_TRUNC: i64 = 0o1000;
+
+/// Pseudo filesystems which are never candidates for read-only suggestions.
+const PSEUDO_FS_TYPES: [&str; 6] = ["proc", "sysfs", "cgroup", "cgroup2", "devpts", "mqueue"];
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum AccessMode {
+    Read,
+    Write,
+    Exec,
+}
+
+/// Classify the access intent of a file related syscall.
+/// `flags` is only used by the open family.
+pub fn access_mode(syscall_name: &str, flags: i64) -> Option<AccessMode> {
+    match syscall_name {
+        "open" | "openat" | "openat2" => {
+            if flags & O_ACCMODE != O_RDONLY || flags & (O_CREAT | O_TRUNC) != 0 {
+                Some(AccessMode::Write)
+            } else {
+                Some(AccessMode::Read)
+            }
+        }
+        "execve" | "execveat" => Some(AccessMode::Exec),
+        "creat" | "truncate" | "mkdir" | "mkdirat" | "rmdir" | "unlink" | "unlinkat" | "rename"
+        | "renameat" | "renameat2" | "link" | "linkat" | "symlink" | "symlinkat" | "chmod"
+        | "fchmodat" | "chown" | "lchown" | "fchownat" | "mknod" | "mknodat" => {
+            Some(AccessMode::Write)
+        }
+        "stat" | "lstat" | "newfstatat" | "statx" | "access" | "faccessat" | "faccessat2"
+        | "readlink" | "readlinkat" | "chdir" => Some(AccessMode::Read),
+        _ => None,
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct FileAccessTracker {
+    accesses: BTreeMap<String, BTreeSet<AccessMode>>,
+}
+
+impl FileAccessTracker {
+    pub fn record(&mut self, path: String, mode: AccessMode) {
+        self.accesses.entry(path).or_default().insert(mode);
+    }
+
+    fn written_paths(&self) -> impl Iterator<Item = &String> {
+        self.accesses
+            .iter()
+            .filter(|(_, modes)| modes.contains(&AccessMode::Write))
+            .map(|(path, _)| path)
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PathAccess {
+    pub path: String,
+    pub modes: Vec<AccessMode>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MountSuggestion {
+    pub destination: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub typ: Option<String>,
+    pub read_only: bool,
+    pub written: bool,
+    /// The mount is writable but nothing was written under it
+    pub read_only_candidate: bool,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FileAccessReport {
+    pub accesses: Vec<PathAccess>,
+    pub write_required: Vec<String>,
+    pub mounts: Vec<MountSuggestion>,
+    /// Written paths which belong to the container rootfs
+    pub rootfs_writes: Vec<String>,
+    /// Relative paths (resolved by the kernel against a dirfd) which can't be assigned to a mount
+    pub unresolved_writes: Vec<String>,
+    pub read_only_rootfs: bool,
+}
+
+fn is_read_only(mount: &Mount) -> bool {
+    mount
+        .options()
+        .as_ref()
+        .map(|options| options.iter().any(|o| o == "ro"))
+        .unwrap_or(false)
+}
+
+fn is_pseudo_fs(mount: &Mount) -> bool {
+    mount
+        .typ()
+        .as_ref()
+        .map(|typ| PSEUDO_FS_TYPES.contains(&typ.as_str()))
+        .unwrap_or(false)
+}
+
+/// Find the mount which contains `path` (longest destination prefix).
+fn find_mount<'a>(mounts: &'a [Mount], path: &Path) -> Option<&'a Mount> {
+    mounts
+        .iter()
+        .filter(|mount| mount.destination() != Path::new("/"))
+        .filter(|mount| path.starts_with(mount.destination()))
+        .max_by_key(|mount| mount.destination().components().count())
+}
+
+impl FileAccessReport {
+    pub fn new(tracker: &FileAccessTracker, mounts: &[Mount]) -> Self {
+        let accesses = tracker
+            .accesses
+            .iter()
+            .map(|(path, modes)| PathAccess {
+                path: path.clone(),
+                modes: modes.iter().copied().collect(),
+            })
+            .collect();
+
+        let write_required: Vec<String> = tracker.written_paths().cloned().collect();
+
+        let mut written_mounts = BTreeSet::new();
+        let mut rootfs_writes = vec![];
+        let mut unresolved_writes = vec![];
+
+        for path in &write_required {
+            if !path.starts_with('/') {
+                unresolved_writes.push(path.clone());
+                continue;
+            }
+            match find_mount(mounts, Path::new(path)) {
+                Some(mount) => {
+                    written_mounts.insert(mount.destination().clone());
+                }
+                None => rootfs_writes.push(path.clone()),
+            }
+        }
+
+        let mounts = mounts
+            .iter()
+            .filter(|mount| !is_pseudo_fs(mount))
+            .map(|mount| {
+                let read_only = is_read_only(mount);
+                let written = written_mounts.contains(mount.destination());
+                MountSuggestion {
+                    destination: mount.destination().clone(),
+                    typ: mount.typ().clone(),
+                    read_only,
+                    written,
+                    read_only_candidate: !read_only && !written,
+                }
+            })
+            .collect();
+
+        let read_only_rootfs = rootfs_writes.is_empty() && unresolved_writes.is_empty();
+
+        FileAccessReport {
+            accesses,
+            write_required,
+            mounts,
+            rootfs_writes,
+            unresolved_writes,
+            read_only_rootfs,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oci_spec::runtime::MountBuilder;
+
+    fn gen_mount(destination: &str, typ: &str, options: Vec<&str>) -> Mount {
+        MountBuilder::default()
+            .destination(destination)
+            .typ(typ)
+            .options(options.into_iter().map(String::from).collect::<Vec<_>>())
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn access_mode_open_flags() {
+        assert_eq!(access_mode("openat", 0), Some(AccessMode::Read));
+        assert_eq!(access_mode("openat", 0o1), Some(AccessMode::Write));
+        assert_eq!(access_mode("open", 0o2), Some(AccessMode::Write));
+        assert_eq!(access_mode("openat", O_CREAT), Some(AccessMode::Write));
+        assert_eq!(access_mode("execve", 0), Some(AccessMode::Exec));
+        assert_eq!(access_mode("mkdir", 0), Some(AccessMode::Write));
+        assert_eq!(access_mode("getpid", 0), None);
+    }
+
+    #[test]
+    fn report_read_only_rootfs() {
+        let mut tracker = FileAccessTracker::default();
+        tracker.record("/etc/passwd".to_string(), AccessMode::Read);
+        tracker.record("/tmp/cache".to_string(), AccessMode::Write);
+        tracker.record("/usr/bin/app".to_string(), AccessMode::Exec);
+
+        let mounts = vec![
+            gen_mount("/proc", "proc", vec![]),
+            gen_mount("/tmp", "tmpfs", vec!["rw"]),
+            gen_mount("/etc/hosts", "bind", vec!["rbind", "rw"]),
+        ];
+        let report = FileAccessReport::new(&tracker, &mounts);
+
+        assert!(report.read_only_rootfs);
+        assert_eq!(report.write_required, vec!["/tmp/cache".to_string()]);
+        assert_eq!(report.mounts.len(), 2);
+        assert!(report.mounts[0].written);
+        assert!(!report.mounts[0].read_only_candidate);
+        assert!(report.mounts[1].read_only_candidate);
+    }
+
+    #[test]
+    fn report_rootfs_writes() {
+        let mut tracker = FileAccessTracker::default();
+        tracker.record("/var/lib/app/db".to_string(), AccessMode::Write);
+        tracker.record("data.tmp".to_string(), AccessMode::Write);
+
+        let mounts = vec![gen_mount("/var/lib/app/cache", "bind", vec!["ro"])];
+        let report = FileAccessReport::new(&tracker, &mounts);
+
+        assert!(!report.read_only_rootfs);
+        assert_eq!(report.rootfs_writes, vec!["/var/lib/app/db".to_string()]);
+        assert_eq!(report.unresolved_writes, vec!["data.tmp".to_string()]);
+        assert!(report.mounts[0].read_only);
+        assert!(!report.mounts[0].read_only_candidate);
+    }
+}
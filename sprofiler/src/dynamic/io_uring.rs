@@ -0,0 +1,195 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+
+pub const IO_URING_SYSCALLS: [&str; 3] = ["io_uring_setup", "io_uring_enter", "io_uring_register"];
+
+/// Index is the io_uring opcode (include/uapi/linux/io_uring.h),
+/// paired with the syscall which performs the same operation.
+const IO_URING_OPS: [(&str, Option<&str>); 58] = [
+    ("IORING_OP_NOP", None),
+    ("IORING_OP_READV", Some("readv")),
+    ("IORING_OP_WRITEV", Some("writev")),
+    ("IORING_OP_FSYNC", Some("fsync")),
+    ("IORING_OP_READ_FIXED", Some("pread64")),
+    ("IORING_OP_WRITE_FIXED", Some("pwrite64")),
+    ("IORING_OP_POLL_ADD", Some("poll")),
+    ("IORING_OP_POLL_REMOVE", None),
+    ("IORING_OP_SYNC_FILE_RANGE", Some("sync_file_range")),
+    ("IORING_OP_SENDMSG", Some("sendmsg")),
+    ("IORING_OP_RECVMSG", Some("recvmsg")),
+    ("IORING_OP_TIMEOUT", None),
+    ("IORING_OP_TIMEOUT_REMOVE", None),
+    ("IORING_OP_ACCEPT", Some("accept4")),
+    ("IORING_OP_ASYNC_CANCEL", None),
+    ("IORING_OP_LINK_TIMEOUT", None),
+    ("IORING_OP_CONNECT", Some("connect")),
+    ("IORING_OP_FALLOCATE", Some("fallocate")),
+    ("IORING_OP_OPENAT", Some("openat")),
+    ("IORING_OP_CLOSE", Some("close")),
+    ("IORING_OP_FILES_UPDATE", None),
+    ("IORING_OP_STATX", Some("statx")),
+    ("IORING_OP_READ", Some("read")),
+    ("IORING_OP_WRITE", Some("write")),
+    ("IORING_OP_FADVISE", Some("fadvise64")),
+    ("IORING_OP_MADVISE", Some("madvise")),
+    ("IORING_OP_SEND", Some("sendto")),
+    ("IORING_OP_RECV", Some("recvfrom")),
+    ("IORING_OP_OPENAT2", Some("openat2")),
+    ("IORING_OP_EPOLL_CTL", Some("epoll_ctl")),
+    ("IORING_OP_SPLICE", Some("splice")),
+    ("IORING_OP_PROVIDE_BUFFERS", None),
+    ("IORING_OP_REMOVE_BUFFERS", None),
+    ("IORING_OP_TEE", Some("tee")),
+    ("IORING_OP_SHUTDOWN", Some("shutdown")),
+    ("IORING_OP_RENAMEAT", Some("renameat2")),
+    ("IORING_OP_UNLINKAT", Some("unlinkat")),
+    ("IORING_OP_MKDIRAT", Some("mkdirat")),
+    ("IORING_OP_SYMLINKAT", Some("symlinkat")),
+    ("IORING_OP_LINKAT", Some("linkat")),
+    ("IORING_OP_MSG_RING", None),
+    ("IORING_OP_FSETXATTR", Some("fsetxattr")),
+    ("IORING_OP_SETXATTR", Some("setxattr")),
+    ("IORING_OP_FGETXATTR", Some("fgetxattr")),
+    ("IORING_OP_GETXATTR", Some("getxattr")),
+    ("IORING_OP_SOCKET", Some("socket")),
+    ("IORING_OP_URING_CMD", Some("ioctl")),
+    ("IORING_OP_SEND_ZC", Some("sendto")),
+    ("IORING_OP_SENDMSG_ZC", Some("sendmsg")),
+    ("IORING_OP_READ_MULTISHOT", Some("read")),
+    ("IORING_OP_WAITID", Some("waitid")),
+    ("IORING_OP_FUTEX_WAIT", Some("futex")),
+    ("IORING_OP_FUTEX_WAKE", Some("futex")),
+    ("IORING_OP_FUTEX_WAITV", Some("futex_waitv")),
+    ("IORING_OP_FIXED_FD_INSTALL", None),
+    ("IORING_OP_FTRUNCATE", Some("ftruncate")),
+    ("IORING_OP_BIND", Some("bind")),
+    ("IORING_OP_LISTEN", Some("listen")),
+];
+
+pub fn opcode_name(opcode: u8) -> String {
+    IO_URING_OPS
+        .get(opcode as usize)
+        .map(|(name, _)| name.to_string())
+        .unwrap_or_else(|| format!("IORING_OP_{}", opcode))
+}
+
+fn equivalent_syscall(opcode: u8) -> Option<&'static str> {
+    IO_URING_OPS
+        .get(opcode as usize)
+        .and_then(|(_, syscall)| *syscall)
+}
+
+/// io_uring syscalls found in the traced syscall list
+pub fn detect<'a, I: IntoIterator<Item = &'a str>>(syscalls: I) -> Vec<String> {
+    let mut detected: Vec<String> = syscalls
+        .into_iter()
+        .filter(|name| IO_URING_SYSCALLS.contains(name))
+        .map(String::from)
+        .collect();
+    detected.sort();
+    detected.dedup();
+    detected
+}
+
+#[derive(Default, Debug)]
+pub struct IoUringTracker {
+    operations: BTreeMap<u8, u64>,
+}
+
+impl IoUringTracker {
+    pub fn record(&mut self, opcode: u8) {
+        *self.operations.entry(opcode).or_default() += 1;
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct IoUringOperation {
+    pub name: String,
+    pub count: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub syscall: Option<String>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct IoUringReport {
+    pub syscalls: Vec<String>,
+    pub operations: Vec<IoUringOperation>,
+    /// Syscalls performed through io_uring which the seccomp profile doesn't allow
+    pub bypassed_syscalls: Vec<String>,
+    /// io_uring syscalls were removed from the generated profile
+    pub denied: bool,
+}
+
+impl IoUringReport {
+    pub fn new(
+        tracker: &IoUringTracker,
+        syscalls: Vec<String>,
+        allowed: &BTreeSet<String>,
+        denied: bool,
+    ) -> Self {
+        let operations: Vec<IoUringOperation> = tracker
+            .operations
+            .iter()
+            .map(|(opcode, count)| IoUringOperation {
+                name: opcode_name(*opcode),
+                count: *count,
+                syscall: equivalent_syscall(*opcode).map(String::from),
+            })
+            .collect();
+
+        let bypassed_syscalls: BTreeSet<String> = operations
+            .iter()
+            .filter_map(|op| op.syscall.clone())
+            .filter(|syscall| !allowed.contains(syscall))
+            .collect();
+
+        IoUringReport {
+            syscalls,
+            operations,
+            bypassed_syscalls: bypassed_syscalls.into_iter().collect(),
+            denied,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_io_uring_syscalls() {
+        assert!(detect(vec!["read", "write"]).is_empty());
+        assert_eq!(
+            detect(vec!["io_uring_setup", "read", "io_uring_enter"]),
+            vec!["io_uring_enter".to_string(), "io_uring_setup".to_string()]
+        );
+    }
+
+    #[test]
+    fn report_bypassed_syscalls() {
+        let mut tracker = IoUringTracker::default();
+        tracker.record(18);
+        tracker.record(22);
+        tracker.record(22);
+        tracker.record(0);
+        tracker.record(200);
+
+        let allowed: BTreeSet<String> = vec!["read".to_string()].into_iter().collect();
+        let report = IoUringReport::new(
+            &tracker,
+            vec!["io_uring_setup".to_string()],
+            &allowed,
+            false,
+        );
+
+        assert_eq!(report.operations.len(), 4);
+        assert_eq!(report.operations[0].name, "IORING_OP_NOP");
+        assert_eq!(report.operations[2].name, "IORING_OP_READ");
+        assert_eq!(report.operations[2].count, 2);
+        assert_eq!(report.operations[3].name, "IORING_OP_200");
+        assert_eq!(report.bypassed_syscalls, vec!["openat".to_string()]);
+    }
+}
@@ -1,35 +1,77 @@
 pub mod annotation;
+pub mod capability;
+pub mod file_access;
+pub mod io_uring;
+pub mod network;
 pub mod process;
+pub mod runtime;
 
 use std::process::{Command, Stdio};
 use std::str;
+use std::str::FromStr;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use structopt::StructOpt;
 
 use crate::command::tracer::{stop_tracing, trace_command};
+use runtime::{Runtime, RuntimeInfo};
 
 #[derive(Debug, StructOpt)]
 #[structopt(name = "dynamic", about = "Dynamic Analyzer")]
 pub enum DynamicSubCommand {
     Start {},
     Stop {},
-    Tracer {},
+    Tracer {
+        /// Container runtime which executes the hook (e.g. crun, runc, youki)
+        #[structopt(long)]
+        runtime: Option<String>,
+        /// Container runtime version (e.g. 1.8.4)
+        #[structopt(long)]
+        runtime_version: Option<String>,
+    },
 }
 
 pub fn handle_dynamic_analyzer(dynamic: DynamicSubCommand) -> Result<()> {
     match dynamic {
         DynamicSubCommand::Start {} => run_trace_command()?,
         DynamicSubCommand::Stop {} => stop_tracing()?,
-        DynamicSubCommand::Tracer {} => trace_command()?,
+        DynamicSubCommand::Tracer {
+            runtime,
+            runtime_version,
+        } => trace_command(parse_runtime_info(runtime, runtime_version)?)?,
     }
     Ok(())
 }
 
+fn parse_runtime_info(
+    runtime: Option<String>,
+    version: Option<String>,
+) -> Result<Option<RuntimeInfo>> {
+    let runtime = match runtime {
+        Some(runtime) => Runtime::from_str(&runtime)
+            .map_err(|_| anyhow!("unsupported container runtime: {}", runtime))?,
+        None => return Ok(None),
+    };
+
+    Ok(Some(RuntimeInfo {
+        runtime,
+        version: version.as_deref().and_then(runtime::parse_version),
+    }))
+}
+
 fn run_trace_command() -> anyhow::Result<()> {
-    Command::new("/proc/self/exe")
-        .arg("dynamic")
-        .arg("tracer")
+    let mut command = Command::new("/proc/self/exe");
+    command.arg("dynamic").arg("tracer");
+
+    // the tracer is detached from the runtime, so the runtime is detected by the hook process
+    if let Some(info) = runtime::detect_parent_runtime() {
+        command.arg("--runtime").arg(info.runtime.to_string());
+        if let Some(version) = info.version {
+            command.arg("--runtime-version").arg(version.to_string());
+        }
+    }
+
+    command
         .stdin(Stdio::inherit())
         .stdout(Stdio::inherit())
         .stderr(Stdio::inherit())
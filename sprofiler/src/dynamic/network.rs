@@ -0,0 +1,256 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use serde::Serialize;
+
+const AF_UNIX: u16 = 1;
+const AF_INET: u16 = 2;
+const AF_INET6: u16 = 10;
+
+pub fn family_name(family: u16) -> String {
+    let name = match family {
+        0 => "AF_UNSPEC",
+        1 => "AF_UNIX",
+        2 => "AF_INET",
+        10 => "AF_INET6",
+        15 => "AF_KEY",
+        16 => "AF_NETLINK",
+        17 => "AF_PACKET",
+        31 => "AF_BLUETOOTH",
+        38 => "AF_ALG",
+        40 => "AF_VSOCK",
+        44 => "AF_XDP",
+        _ => return format!("AF_{}", family),
+    };
+    name.to_string()
+}
+
+pub fn socket_type_name(typ: i64) -> String {
+    // SOCK_NONBLOCK and SOCK_CLOEXEC are or'ed into the type
+    let typ = typ & 0xf;
+    let name = match typ {
+        1 => "SOCK_STREAM",
+        2 => "SOCK_DGRAM",
+        3 => "SOCK_RAW",
+        4 => "SOCK_RDM",
+        5 => "SOCK_SEQPACKET",
+        6 => "SOCK_DCCP",
+        10 => "SOCK_PACKET",
+        _ => return format!("SOCK_{}", typ),
+    };
+    name.to_string()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SocketAddress {
+    V4(Ipv4Addr, u16),
+    V6(Ipv6Addr, u16),
+    Unix(String),
+    Other(u16),
+}
+
+impl SocketAddress {
+    /// Parse a raw `struct sockaddr`
+    pub fn from_bytes(addr: &[u8]) -> Option<Self> {
+        if addr.len() < 2 {
+            return None;
+        }
+        let family = u16::from_ne_bytes([addr[0], addr[1]]);
+
+        let address = match family {
+            AF_INET if addr.len() >= 8 => SocketAddress::V4(
+                Ipv4Addr::new(addr[4], addr[5], addr[6], addr[7]),
+                u16::from_be_bytes([addr[2], addr[3]]),
+            ),
+            AF_INET6 if addr.len() >= 24 => {
+                let mut octets = [0u8; 16];
+                octets.copy_from_slice(&addr[8..24]);
+                SocketAddress::V6(
+                    Ipv6Addr::from(octets),
+                    u16::from_be_bytes([addr[2], addr[3]]),
+                )
+            }
+            AF_UNIX => {
+                let path = &addr[2..];
+                // abstract socket address starts with a null byte
+                let (prefix, path) = match path.first() {
+                    Some(0) => ("@", &path[1..]),
+                    _ => ("", path),
+                };
+                let len = path.iter().position(|&b| b == 0).unwrap_or(path.len());
+                SocketAddress::Unix(format!(
+                    "{}{}",
+                    prefix,
+                    String::from_utf8_lossy(&path[..len])
+                ))
+            }
+            _ => SocketAddress::Other(family),
+        };
+
+        Some(address)
+    }
+}
+
+impl fmt::Display for SocketAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SocketAddress::V4(addr, port) => write!(f, "{}:{}", addr, port),
+            SocketAddress::V6(addr, port) => write!(f, "[{}]:{}", addr, port),
+            SocketAddress::Unix(path) => write!(f, "unix:{}", path),
+            SocketAddress::Other(family) => write!(f, "{}", family_name(*family)),
+        }
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct NetworkTracker {
+    families: BTreeMap<String, BTreeSet<String>>,
+    /// bound addresses by (pid, fd) to resolve the port of listen(2)
+    bound_sockets: HashMap<(u32, i64), SocketAddress>,
+    bound: BTreeSet<String>,
+    listening: BTreeSet<String>,
+    outbound: BTreeSet<String>,
+}
+
+impl NetworkTracker {
+    pub fn record_socket(&mut self, family: i64, typ: i64) {
+        self.families
+            .entry(family_name(family as u16))
+            .or_default()
+            .insert(socket_type_name(typ));
+    }
+
+    pub fn record_bind(&mut self, pid: u32, fd: i64, addr: SocketAddress) {
+        self.bound.insert(addr.to_string());
+        self.bound_sockets.insert((pid, fd), addr);
+    }
+
+    pub fn record_listen(&mut self, pid: u32, fd: i64) {
+        if let Some(addr) = self.bound_sockets.get(&(pid, fd)) {
+            self.listening.insert(addr.to_string());
+        }
+    }
+
+    pub fn record_connect(&mut self, addr: SocketAddress) {
+        self.outbound.insert(addr.to_string());
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SocketFamily {
+    pub family: String,
+    pub types: Vec<String>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkReport {
+    pub families: Vec<SocketFamily>,
+    pub listening: Vec<String>,
+    pub bound: Vec<String>,
+    pub outbound: Vec<String>,
+}
+
+impl NetworkReport {
+    pub fn new(tracker: &NetworkTracker) -> Self {
+        NetworkReport {
+            families: tracker
+                .families
+                .iter()
+                .map(|(family, types)| SocketFamily {
+                    family: family.clone(),
+                    types: types.iter().cloned().collect(),
+                })
+                .collect(),
+            listening: tracker.listening.iter().cloned().collect(),
+            bound: tracker.bound.iter().cloned().collect(),
+            outbound: tracker.outbound.iter().cloned().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sockaddr_in(addr: [u8; 4], port: u16) -> Vec<u8> {
+        let mut buf = AF_INET.to_ne_bytes().to_vec();
+        buf.extend(port.to_be_bytes());
+        buf.extend(addr);
+        buf.extend([0u8; 8]);
+        buf
+    }
+
+    #[test]
+    fn parse_socket_address() {
+        assert_eq!(
+            SocketAddress::from_bytes(&sockaddr_in([127, 0, 0, 1], 8080)),
+            Some(SocketAddress::V4(Ipv4Addr::new(127, 0, 0, 1), 8080))
+        );
+
+        let mut unix = AF_UNIX.to_ne_bytes().to_vec();
+        unix.extend(b"/run/app.sock\0\0\0");
+        assert_eq!(
+            SocketAddress::from_bytes(&unix).unwrap().to_string(),
+            "unix:/run/app.sock"
+        );
+
+        let mut abstract_unix = AF_UNIX.to_ne_bytes().to_vec();
+        abstract_unix.extend(b"\0app");
+        assert_eq!(
+            SocketAddress::from_bytes(&abstract_unix)
+                .unwrap()
+                .to_string(),
+            "unix:@app"
+        );
+
+        let netlink = 16u16.to_ne_bytes().to_vec();
+        assert_eq!(
+            SocketAddress::from_bytes(&netlink).unwrap().to_string(),
+            "AF_NETLINK"
+        );
+        assert_eq!(SocketAddress::from_bytes(&[]), None);
+    }
+
+    #[test]
+    fn report_listening_and_outbound() {
+        let mut tracker = NetworkTracker::default();
+        tracker.record_socket(2, 1 | 0o2000000);
+        tracker.record_socket(2, 2);
+        tracker.record_socket(16, 3);
+
+        let http = SocketAddress::from_bytes(&sockaddr_in([0, 0, 0, 0], 80)).unwrap();
+        let dns = SocketAddress::from_bytes(&sockaddr_in([0, 0, 0, 0], 53)).unwrap();
+        let upstream = SocketAddress::from_bytes(&sockaddr_in([10, 0, 0, 2], 5432)).unwrap();
+
+        tracker.record_bind(100, 3, http);
+        tracker.record_bind(100, 4, dns);
+        tracker.record_listen(100, 3);
+        tracker.record_listen(100, 5);
+        tracker.record_connect(upstream);
+
+        let report = NetworkReport::new(&tracker);
+
+        assert_eq!(
+            report.families,
+            vec![
+                SocketFamily {
+                    family: "AF_INET".to_string(),
+                    types: vec!["SOCK_DGRAM".to_string(), "SOCK_STREAM".to_string()],
+                },
+                SocketFamily {
+                    family: "AF_NETLINK".to_string(),
+                    types: vec!["SOCK_RAW".to_string()],
+                },
+            ]
+        );
+        assert_eq!(report.listening, vec!["0.0.0.0:80".to_string()]);
+        assert_eq!(
+            report.bound,
+            vec!["0.0.0.0:53".to_string(), "0.0.0.0:80".to_string()]
+        );
+        assert_eq!(report.outbound, vec!["10.0.0.2:5432".to_string()]);
+    }
+}
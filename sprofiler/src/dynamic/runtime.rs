@@ -0,0 +1,276 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::str::FromStr;
+
+use nix::unistd::getppid;
+use serde::Serialize;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Runtime {
+    Crun,
+    Runc,
+    Youki,
+}
+
+impl FromStr for Runtime {
+    type Err = ();
+
+    #[rustfmt::skip]
+    fn from_str(runtime: &str) -> Result<Runtime, Self::Err> {
+        match runtime.to_lowercase().as_str() {
+            "crun"  => Ok(Runtime::Crun),
+            "runc"  => Ok(Runtime::Runc),
+            "youki" => Ok(Runtime::Youki),
+            _       => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for Runtime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Runtime::Crun => "crun",
+            Runtime::Runc => "runc",
+            Runtime::Youki => "youki",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Version(pub u32, pub u32, pub u32);
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.0, self.1, self.2)
+    }
+}
+
+/// Parse "1.8.4", "v1.1.0-rc.1" or the first line of `<runtime> --version`
+/// (e.g. "crun version 1.8.4")
+pub fn parse_version(s: &str) -> Option<Version> {
+    let line = s.lines().next()?;
+    let version = line.split_whitespace().last()?.trim_start_matches('v');
+    let version = version.split(['-', '+']).next()?;
+
+    let mut numbers = version.split('.').map(|n| n.parse::<u32>());
+    let major = numbers.next()?.ok()?;
+    let minor = numbers.next().unwrap_or(Ok(0)).ok()?;
+    let patch = numbers.next().unwrap_or(Ok(0)).ok()?;
+
+    Some(Version(major, minor, patch))
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+pub struct RuntimeInfo {
+    pub runtime: Runtime,
+    pub version: Option<Version>,
+}
+
+/// OCI hooks are executed by the runtime, so the parent of the hook process is the runtime.
+pub fn detect_parent_runtime() -> Option<RuntimeInfo> {
+    let exe = fs::read_link(format!("/proc/{}/exe", getppid())).ok()?;
+    let runtime = Runtime::from_str(exe.file_name()?.to_str()?).ok()?;
+
+    Some(RuntimeInfo {
+        runtime,
+        version: runtime_version(&exe),
+    })
+}
+
+fn runtime_version(exe: &Path) -> Option<Version> {
+    let output = Command::new(exe).arg("--version").output().ok()?;
+    parse_version(&String::from_utf8_lossy(&output.stdout))
+}
+
+struct PostFilterSyscalls {
+    runtime: Runtime,
+    since: Version,
+    /// Executed after the seccomp filter is installed in any case
+    always: &'static [&'static str],
+    /// Without no_new_privs the filter is installed while the runtime still has
+    /// CAP_SYS_ADMIN, before switching credentials and dropping capabilities.
+    without_no_new_privs: &'static [&'static str],
+}
+
+const CREDENTIAL_SYSCALLS: &[&str] = &[
+    "capget",
+    "capset",
+    "prctl",
+    "setgid",
+    "setgroups",
+    "setresgid",
+    "setresuid",
+    "setuid",
+];
+
+const POST_FILTER_SYSCALLS: &[PostFilterSyscalls] = &[
+    PostFilterSyscalls {
+        runtime: Runtime::Crun,
+        since: Version(0, 0, 0),
+        always: &["close", "execve", "exit_group", "fstat", "prctl"],
+        without_no_new_privs: CREDENTIAL_SYSCALLS,
+    },
+    PostFilterSyscalls {
+        runtime: Runtime::Crun,
+        since: Version(1, 0, 0),
+        always: &["close_range"],
+        without_no_new_privs: &["chdir", "fchdir"],
+    },
+    // runc is a Go program, the Go runtime keeps running until execve
+    PostFilterSyscalls {
+        runtime: Runtime::Runc,
+        since: Version(0, 0, 0),
+        always: &[
+            "close",
+            "epoll_pwait",
+            "execve",
+            "exit_group",
+            "fcntl",
+            "fstat",
+            "futex",
+            "getpid",
+            "madvise",
+            "mmap",
+            "munmap",
+            "nanosleep",
+            "newfstatat",
+            "openat",
+            "read",
+            "rt_sigaction",
+            "rt_sigprocmask",
+            "rt_sigreturn",
+            "sched_yield",
+            "sigaltstack",
+            "tgkill",
+            "write",
+        ],
+        without_no_new_privs: CREDENTIAL_SYSCALLS,
+    },
+    PostFilterSyscalls {
+        runtime: Runtime::Runc,
+        since: Version(1, 1, 0),
+        always: &["close_range"],
+        without_no_new_privs: &["chdir", "fchdir", "getppid"],
+    },
+    PostFilterSyscalls {
+        runtime: Runtime::Youki,
+        since: Version(0, 0, 0),
+        always: &[
+            "close",
+            "close_range",
+            "execve",
+            "exit_group",
+            "futex",
+            "sigaltstack",
+        ],
+        without_no_new_privs: CREDENTIAL_SYSCALLS,
+    },
+];
+
+/// Syscalls the runtime executes between installing the seccomp filter and exec'ing the entrypoint.
+/// If the runtime or its version is unknown, every matching entry is included.
+pub fn post_filter_syscalls(runtime: Option<RuntimeInfo>, no_new_privs: bool) -> Vec<&'static str> {
+    let mut syscalls = BTreeSet::new();
+
+    let entries = POST_FILTER_SYSCALLS.iter().filter(|entry| match runtime {
+        Some(RuntimeInfo {
+            runtime,
+            version: Some(version),
+        }) => entry.runtime == runtime && entry.since <= version,
+        Some(RuntimeInfo {
+            runtime,
+            version: None,
+        }) => entry.runtime == runtime,
+        None => true,
+    });
+
+    for entry in entries {
+        syscalls.extend(entry.always);
+        if !no_new_privs {
+            syscalls.extend(entry.without_no_new_privs);
+        }
+    }
+
+    syscalls.into_iter().collect()
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeReport {
+    pub runtime: Option<RuntimeInfo>,
+    pub no_new_privileges: bool,
+    /// Post filter syscalls which were not traced and added to the profile
+    pub added: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_runtime_version() {
+        assert_eq!(
+            parse_version("crun version 1.8.4\ncommit: 5a8fa99"),
+            Some(Version(1, 8, 4))
+        );
+        assert_eq!(
+            parse_version("runc version 1.1.0-rc.1"),
+            Some(Version(1, 1, 0))
+        );
+        assert_eq!(parse_version("v0.1"), Some(Version(0, 1, 0)));
+        assert_eq!(parse_version("youki version unknown"), None);
+        assert_eq!(parse_version(""), None);
+    }
+
+    #[test]
+    fn post_filter_syscalls_by_version() {
+        let old_runc = RuntimeInfo {
+            runtime: Runtime::Runc,
+            version: Some(Version(1, 0, 3)),
+        };
+        let runc = RuntimeInfo {
+            runtime: Runtime::Runc,
+            version: Some(Version(1, 1, 4)),
+        };
+
+        let syscalls = post_filter_syscalls(Some(old_runc), true);
+        assert!(syscalls.contains(&"execve"));
+        assert!(!syscalls.contains(&"close_range"));
+        assert!(!syscalls.contains(&"capset"));
+
+        let syscalls = post_filter_syscalls(Some(runc), true);
+        assert!(syscalls.contains(&"close_range"));
+
+        let syscalls = post_filter_syscalls(Some(runc), false);
+        assert!(syscalls.contains(&"capset"));
+        assert!(syscalls.contains(&"fchdir"));
+    }
+
+    #[test]
+    fn post_filter_syscalls_unknown_runtime() {
+        let crun = RuntimeInfo {
+            runtime: Runtime::Crun,
+            version: None,
+        };
+
+        let syscalls = post_filter_syscalls(Some(crun), true);
+        assert!(syscalls.contains(&"close_range"));
+        assert!(!syscalls.contains(&"futex"));
+
+        // all runtimes
+        let syscalls = post_filter_syscalls(None, true);
+        assert!(syscalls.contains(&"close_range"));
+        assert!(syscalls.contains(&"futex"));
+
+        // sorted and deduplicated
+        let mut sorted = syscalls.clone();
+        sorted.sort_unstable();
+        sorted.dedup();
+        assert_eq!(syscalls, sorted);
+    }
+}
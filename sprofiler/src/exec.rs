@@ -0,0 +1,449 @@
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::ffi::CString;
+use std::fs::File;
+use std::os::unix::io::RawFd;
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use nix::errno::Errno;
+use nix::libc;
+use nix::poll::{poll, PollFd, PollFlags};
+use nix::sys::socket::{
+    recvmsg, sendmsg, socketpair, AddressFamily, ControlMessage, ControlMessageOwned, MsgFlags,
+    SockFlag, SockType,
+};
+use nix::sys::uio::IoVec;
+use nix::sys::wait::{waitpid, WaitStatus};
+use nix::unistd::{close, execvp, fork, ForkResult};
+use oci_spec::runtime::LinuxSeccomp;
+use serde::Serialize;
+use structopt::StructOpt;
+
+use crate::cbpf::{
+    self, ArchTable, Program, SeccompData, SockFilter, ARCH_OFFSET, ARGS_OFFSET, BPF_ABS, BPF_JEQ,
+    BPF_JMP, BPF_K, BPF_LD, BPF_RET, BPF_W, NR_OFFSET, SECCOMP_RET_ACTION_FULL, SECCOMP_RET_ALLOW,
+    SECCOMP_RET_DATA, SECCOMP_RET_ERRNO, SECCOMP_RET_USER_NOTIF,
+};
+use crate::profile_util;
+
+const SECCOMP_SET_MODE_FILTER: libc::c_uint = 1;
+const SECCOMP_FILTER_FLAG_LOG: libc::c_ulong = 1 << 1;
+const SECCOMP_FILTER_FLAG_SPEC_ALLOW: libc::c_ulong = 1 << 2;
+const SECCOMP_FILTER_FLAG_NEW_LISTENER: libc::c_ulong = 1 << 3;
+const SECCOMP_FILTER_FLAG_WAIT_KILLABLE_RECV: libc::c_ulong = 1 << 5;
+const SECCOMP_USER_NOTIF_FLAG_CONTINUE: u32 = 1;
+// _IOWR('!', 0, struct seccomp_notif) and _IOWR('!', 1, struct seccomp_notif_resp)
+const SECCOMP_IOCTL_NOTIF_RECV: libc::c_ulong = 0xc050_2100;
+const SECCOMP_IOCTL_NOTIF_SEND: libc::c_ulong = 0xc018_2101;
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "exec",
+    about = "Execute a command with a seccomp profile and report denied syscalls"
+)]
+pub struct ExecCommand {
+    #[structopt(long, parse(from_os_str))]
+    profile: PathBuf,
+    /// Install the profile as is and don't report denied syscalls
+    #[structopt(long)]
+    no_report: bool,
+    /// Write the denied syscalls as JSON
+    #[structopt(long, parse(from_os_str))]
+    report: Option<PathBuf>,
+    #[structopt(required = true)]
+    command: Vec<String>,
+}
+
+/// struct sock_fprog
+#[repr(C)]
+struct SockFprog {
+    len: libc::c_ushort,
+    filter: *const SockFilter,
+}
+
+/// struct seccomp_data
+#[repr(C)]
+#[derive(Default)]
+struct RawSeccompData {
+    nr: i32,
+    arch: u32,
+    instruction_pointer: u64,
+    args: [u64; 6],
+}
+
+/// struct seccomp_notif
+#[repr(C)]
+#[derive(Default)]
+struct SeccompNotif {
+    id: u64,
+    pid: u32,
+    flags: u32,
+    data: RawSeccompData,
+}
+
+/// struct seccomp_notif_resp
+#[repr(C)]
+#[derive(Default)]
+struct SeccompNotifResp {
+    id: u64,
+    val: i64,
+    error: i32,
+    flags: u32,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Denial {
+    pub syscall: String,
+    pub errno: String,
+    pub count: usize,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ExecResult {
+    /// Exit code of the command, 128 + signal number if it was killed
+    pub status: i32,
+    pub denials: Vec<Denial>,
+}
+
+fn install(program: &Program, flags: libc::c_ulong) -> Result<RawFd> {
+    if unsafe { libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) } < 0 {
+        return Err(Errno::last()).context("prctl(PR_SET_NO_NEW_PRIVS)");
+    }
+
+    let prog = SockFprog {
+        len: program.len() as libc::c_ushort,
+        filter: program.filters.as_ptr(),
+    };
+    let fd = unsafe {
+        libc::syscall(
+            libc::SYS_seccomp,
+            SECCOMP_SET_MODE_FILTER,
+            flags,
+            &prog as *const SockFprog,
+        )
+    };
+    if fd < 0 {
+        return Err(Errno::last()).context("seccomp(SECCOMP_SET_MODE_FILTER)");
+    }
+
+    Ok(fd as RawFd)
+}
+
+/// Flags of the profile passed to seccomp(2)
+fn filter_flags(profile: &LinuxSeccomp) -> Result<libc::c_ulong> {
+    let mut flags = 0;
+    for flag in profile.flags().iter().flatten() {
+        flags |= match flag.as_str() {
+            "SECCOMP_FILTER_FLAG_LOG" => SECCOMP_FILTER_FLAG_LOG,
+            "SECCOMP_FILTER_FLAG_SPEC_ALLOW" => SECCOMP_FILTER_FLAG_SPEC_ALLOW,
+            "SECCOMP_FILTER_FLAG_WAIT_KILLABLE_RECV" => SECCOMP_FILTER_FLAG_WAIT_KILLABLE_RECV,
+            // The command is executed by a single thread
+            "SECCOMP_FILTER_FLAG_TSYNC" => 0,
+            _ => bail!("unsupported seccomp filter flag: {}", flag),
+        };
+    }
+    Ok(flags)
+}
+
+fn to_cstrings(command: &[String]) -> Result<Vec<CString>> {
+    command
+        .iter()
+        .map(|arg| CString::new(arg.as_str()).context("the command contains a NUL byte"))
+        .collect()
+}
+
+/// Installs the profile on the current process and executes the command
+pub fn exec(profile: &LinuxSeccomp, command: &[String]) -> Result<Infallible> {
+    let args = to_cstrings(command)?;
+    let program = cbpf::compile(profile)?;
+
+    // The kernel accepts WAIT_KILLABLE_RECV only together with a listener
+    let flags = filter_flags(profile)? & !SECCOMP_FILTER_FLAG_WAIT_KILLABLE_RECV;
+    install(&program, flags)?;
+    Ok(execvp(&args[0], &args)?)
+}
+
+/// Denied calls are sent to sprofiler as user notifications, which answers them with
+/// the errno of the profile. The command itself sees the same results as with the profile.
+fn listener_program(program: &Program, table: &ArchTable, sock: RawFd) -> Result<Program> {
+    let sendmsg_nr = table
+        .number("sendmsg")
+        .with_context(|| format!("{} has no sendmsg", table.name))?;
+    let stmt = |code, k| SockFilter {
+        code,
+        jt: 0,
+        jf: 0,
+        k,
+    };
+    let jeq = |k, jf| SockFilter {
+        code: BPF_JMP | BPF_JEQ | BPF_K,
+        jt: 0,
+        jf,
+        k,
+    };
+
+    // Lets the child pass the listener to sprofiler before executing the command
+    let mut filters = vec![
+        stmt(BPF_LD | BPF_W | BPF_ABS, ARCH_OFFSET),
+        jeq(table.audit_arch, 5),
+        stmt(BPF_LD | BPF_W | BPF_ABS, NR_OFFSET),
+        jeq(sendmsg_nr, 3),
+        stmt(BPF_LD | BPF_W | BPF_ABS, ARGS_OFFSET),
+        jeq(sock as u32, 1),
+        stmt(BPF_RET | BPF_K, SECCOMP_RET_ALLOW),
+    ];
+
+    filters.extend(program.filters.iter().map(|filter| {
+        let mut filter = *filter;
+        if filter.code == BPF_RET | BPF_K && filter.k & SECCOMP_RET_ACTION_FULL == SECCOMP_RET_ERRNO
+        {
+            filter.k = SECCOMP_RET_USER_NOTIF;
+        }
+        filter
+    }));
+
+    Ok(Program {
+        filters,
+        skipped: program.skipped.clone(),
+    })
+}
+
+fn child(
+    program: &Program,
+    flags: libc::c_ulong,
+    sock: RawFd,
+    args: &[CString],
+) -> Result<Infallible> {
+    let listener = install(program, flags | SECCOMP_FILTER_FLAG_NEW_LISTENER)?;
+    sendmsg(
+        sock,
+        &[IoVec::from_slice(b"\0")],
+        &[ControlMessage::ScmRights(&[listener])],
+        MsgFlags::empty(),
+        None,
+    )?;
+
+    Ok(execvp(&args[0], args)?)
+}
+
+/// None if the child failed before sending the listener
+fn receive_listener(sock: RawFd) -> Result<Option<RawFd>> {
+    let mut buf = [0u8; 1];
+    let mut cmsg_buffer = nix::cmsg_space!([RawFd; 1]);
+    let msg = recvmsg(
+        sock,
+        &[IoVec::from_mut_slice(&mut buf)],
+        Some(&mut cmsg_buffer),
+        MsgFlags::empty(),
+    )?;
+
+    for cmsg in msg.cmsgs() {
+        if let ControlMessageOwned::ScmRights(fds) = cmsg {
+            return Ok(fds.first().copied());
+        }
+    }
+
+    Ok(None)
+}
+
+fn handle_notification(
+    listener: RawFd,
+    program: &Program,
+    denials: &mut BTreeMap<(u32, u32, i32), usize>,
+) -> Result<()> {
+    let mut notif = SeccompNotif::default();
+    if unsafe { libc::ioctl(listener, SECCOMP_IOCTL_NOTIF_RECV as _, &mut notif) } < 0 {
+        return match Errno::last() {
+            // The calling thread was killed while waiting
+            Errno::ENOENT | Errno::EINTR => Ok(()),
+            errno => Err(errno).context("SECCOMP_IOCTL_NOTIF_RECV"),
+        };
+    }
+
+    let data = SeccompData {
+        nr: notif.data.nr as u32,
+        arch: notif.data.arch,
+        instruction_pointer: notif.data.instruction_pointer,
+        args: notif.data.args,
+    };
+    let value = program.run(&data)?;
+
+    let mut resp = SeccompNotifResp {
+        id: notif.id,
+        ..Default::default()
+    };
+    if value & SECCOMP_RET_ACTION_FULL == SECCOMP_RET_ERRNO {
+        let errno = (value & SECCOMP_RET_DATA) as i32;
+        resp.error = -errno;
+        *denials.entry((data.arch, data.nr, errno)).or_insert(0) += 1;
+    } else {
+        // Notifications of the profile itself have no supervisor other than sprofiler
+        resp.flags = SECCOMP_USER_NOTIF_FLAG_CONTINUE;
+    }
+
+    if unsafe { libc::ioctl(listener, SECCOMP_IOCTL_NOTIF_SEND as _, &mut resp) } < 0 {
+        return match Errno::last() {
+            Errno::ENOENT => Ok(()),
+            errno => Err(errno).context("SECCOMP_IOCTL_NOTIF_SEND"),
+        };
+    }
+
+    Ok(())
+}
+
+/// Answers notifications until no process uses the filter
+fn supervise(listener: RawFd, program: &Program, table: &ArchTable) -> Result<Vec<Denial>> {
+    let mut denials = BTreeMap::new();
+
+    loop {
+        let mut fds = [PollFd::new(listener, PollFlags::POLLIN)];
+        match poll(&mut fds, -1) {
+            Err(Errno::EINTR) => continue,
+            result => result?,
+        };
+
+        let revents = fds[0].revents().unwrap_or_else(PollFlags::empty);
+        if revents.contains(PollFlags::POLLIN) {
+            handle_notification(listener, program, &mut denials)?;
+        } else if revents.intersects(PollFlags::POLLHUP | PollFlags::POLLERR) {
+            break;
+        }
+    }
+
+    Ok(denials
+        .into_iter()
+        .map(|((arch, nr, errno), count)| Denial {
+            // Calls of another ABI (e.g. 32-bit) are named by number
+            syscall: Some(table)
+                .filter(|table| table.audit_arch == arch)
+                .and_then(|table| table.syscalls.get(&nr))
+                .map(|name| name.to_string())
+                .unwrap_or_else(|| nr.to_string()),
+            errno: format!("{:?}", Errno::from_i32(errno)),
+            count,
+        })
+        .collect())
+}
+
+/// Executes the command with the profile in a child process and collects the
+/// syscalls the profile denied with an errno
+pub fn run(profile: &LinuxSeccomp, command: &[String]) -> Result<ExecResult> {
+    let args = to_cstrings(command)?;
+    let program = cbpf::compile(profile)?;
+    let table = cbpf::host_arch()
+        .as_ref()
+        .and_then(cbpf::arch_table)
+        .with_context(|| format!("{} is not supported", std::env::consts::ARCH))?;
+
+    let (sock, child_sock) = socketpair(
+        AddressFamily::Unix,
+        SockType::Stream,
+        None,
+        SockFlag::SOCK_CLOEXEC,
+    )?;
+    let listener_program = listener_program(&program, &table, child_sock)?;
+    let flags = filter_flags(profile)?;
+
+    let pid = match unsafe { fork()? } {
+        ForkResult::Child => {
+            let _ = close(sock);
+            let code = match child(&listener_program, flags, child_sock, &args) {
+                Ok(never) => match never {},
+                Err(e) => {
+                    eprintln!("sprofiler exec: {:#}", e);
+                    127
+                }
+            };
+            unsafe { libc::_exit(code) }
+        }
+        ForkResult::Parent { child } => child,
+    };
+
+    close(child_sock)?;
+    let listener = receive_listener(sock);
+    close(sock)?;
+
+    let denials = match listener? {
+        Some(listener) => {
+            let denials = supervise(listener, &program, &table);
+            close(listener)?;
+            denials?
+        }
+        None => Vec::new(),
+    };
+
+    let status = match waitpid(pid, None)? {
+        WaitStatus::Exited(_, code) => code,
+        WaitStatus::Signaled(_, signal, _) => 128 + signal as i32,
+        status => bail!("unexpected wait status {:?}", status),
+    };
+
+    Ok(ExecResult { status, denials })
+}
+
+pub fn handle_exec_command(exec_cmd: ExecCommand) -> Result<()> {
+    let profile = profile_util::read_seccomp_profile(&exec_cmd.profile)?;
+    for arch in cbpf::compile(&profile)?.skipped {
+        eprintln!(
+            "WARNING: {:?} has no syscall table, its calls are killed unlike with runc",
+            arch
+        );
+    }
+
+    if exec_cmd.no_report {
+        exec(&profile, &exec_cmd.command)?;
+        return Ok(());
+    }
+
+    let result = run(&profile, &exec_cmd.command)?;
+    for denial in &result.denials {
+        eprintln!(
+            "denied: {} ({}) x{}",
+            denial.syscall, denial.errno, denial.count
+        );
+    }
+    if let Some(path) = exec_cmd.report {
+        let file = File::create(&path)?;
+        serde_json::to_writer_pretty(file, &result)?;
+    }
+
+    std::process::exit(result.status);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oci_spec::runtime::{LinuxSeccompAction, LinuxSeccompBuilder, LinuxSyscallBuilder};
+
+    #[test]
+    fn run_reports_denied_syscalls() -> Result<()> {
+        let profile = LinuxSeccompBuilder::default()
+            .default_action(LinuxSeccompAction::ScmpActAllow)
+            .architectures(vec![cbpf::host_arch().unwrap()])
+            .syscalls(vec![LinuxSyscallBuilder::default()
+                .names(vec!["mkdir".to_string(), "mkdirat".to_string()])
+                .action(LinuxSeccompAction::ScmpActErrno)
+                .errno_ret(13u32)
+                .build()?])
+            .build()?;
+
+        let dir = std::env::temp_dir().join(format!("sprofiler-exec-{}", std::process::id()));
+        let result = run(
+            &profile,
+            &["mkdir".to_string(), dir.to_string_lossy().into_owned()],
+        )?;
+
+        assert_ne!(result.status, 0);
+        assert!(!dir.exists());
+        assert_eq!(result.denials.len(), 1);
+        assert!(result.denials[0].syscall.starts_with("mkdir"));
+        assert_eq!(result.denials[0].errno, "EACCES");
+
+        let result = run(&profile, &["true".to_string()])?;
+        assert_eq!(result.status, 0);
+        assert!(result.denials.is_empty());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,271 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use oci_spec::runtime::LinuxSeccomp;
+use serde::Serialize;
+
+use crate::profile::validate::Issue;
+use crate::profile_util::{self, ProfileFormat};
+
+/// Pod annotations set by CRI-O and containerd respectively
+const POD_NAME_ANNOTATIONS: [&str; 2] =
+    ["io.kubernetes.pod.name", "io.kubernetes.cri.sandbox-name"];
+const POD_NAMESPACE_ANNOTATIONS: [&str; 2] = [
+    "io.kubernetes.pod.namespace",
+    "io.kubernetes.cri.sandbox-namespace",
+];
+const CONTAINER_NAME_ANNOTATIONS: [&str; 2] = [
+    "io.kubernetes.container.name",
+    "io.kubernetes.cri.container-name",
+];
+
+const API_VERSION: &str = "security-profiles-operator.x-k8s.io/v1beta1";
+const KIND: &str = "SeccompProfile";
+const DEFAULT_NAMESPACE: &str = "default";
+/// Directory under the kubelet seccomp root (/var/lib/kubelet/seccomp) for node-local profiles
+const LOCALHOST_DIR: &str = "sprofiler";
+/// Directory under the kubelet seccomp root where security-profiles-operator installs profiles
+const OPERATOR_DIR: &str = "operator";
+/// Kubernetes object names are DNS subdomains
+const MAX_NAME_LEN: usize = 253;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfileName {
+    pub name: String,
+    pub namespace: String,
+}
+
+impl ProfileName {
+    pub fn new(name: &str, namespace: Option<&str>) -> Result<Self> {
+        let name = sanitize(name);
+        if name.is_empty() {
+            bail!("profile name must contain an alphanumeric character");
+        }
+        let namespace = match namespace.map(sanitize) {
+            Some(namespace) if !namespace.is_empty() => namespace,
+            _ => DEFAULT_NAMESPACE.to_string(),
+        };
+
+        Ok(ProfileName { name, namespace })
+    }
+
+    /// The profile is named <pod>-<container> in the namespace of the pod
+    pub fn from_annotations(annotations: &HashMap<String, String>) -> Option<Self> {
+        let get = |keys: &[&str]| {
+            keys.iter()
+                .find_map(|key| annotations.get(*key))
+                .map(String::as_str)
+        };
+
+        let pod = get(&POD_NAME_ANNOTATIONS)?;
+        let name = match get(&CONTAINER_NAME_ANNOTATIONS) {
+            Some(container) => format!("{}-{}", pod, container),
+            None => pod.to_string(),
+        };
+
+        ProfileName::new(&name, get(&POD_NAMESPACE_ANNOTATIONS)).ok()
+    }
+
+    /// Path relative to the kubelet seccomp root of the node-local profile file
+    pub fn localhost_profile(&self) -> PathBuf {
+        Path::new(LOCALHOST_DIR)
+            .join(&self.namespace)
+            .join(format!("{}.json", self.name))
+    }
+
+    /// Path relative to the kubelet seccomp root where security-profiles-operator
+    /// installs the SeccompProfile resource
+    pub fn operator_profile(&self) -> PathBuf {
+        Path::new(OPERATOR_DIR)
+            .join(&self.namespace)
+            .join(format!("{}.json", self.name))
+    }
+}
+
+/// Lowercase alphanumerics, '-' and '.', starting and ending with an alphanumeric
+fn sanitize(name: &str) -> String {
+    let name: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | '0'..='9' | '-' | '.' => c,
+            _ => '-',
+        })
+        .collect();
+
+    let name: String = name
+        .trim_matches(|c: char| !c.is_ascii_alphanumeric())
+        .chars()
+        .take(MAX_NAME_LEN)
+        .collect();
+    name.trim_end_matches(|c: char| !c.is_ascii_alphanumeric())
+        .to_string()
+}
+
+#[derive(Serialize, Debug)]
+pub struct ObjectMeta {
+    pub name: String,
+    pub namespace: String,
+}
+
+/// SeccompProfile custom resource of security-profiles-operator
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SeccompProfileResource {
+    pub api_version: String,
+    pub kind: String,
+    pub metadata: ObjectMeta,
+    pub spec: serde_json::Value,
+}
+
+impl SeccompProfileResource {
+    pub fn new(name: &ProfileName, profile: &LinuxSeccomp) -> Result<Self> {
+        let mut spec = serde_json::to_value(profile)?;
+        // The spec has no defaultErrnoRet, the operator always returns EPERM
+        if let Some(errno_ret) = spec
+            .as_object_mut()
+            .and_then(|spec| spec.remove("defaultErrnoRet"))
+        {
+            eprintln!(
+                "SeccompProfile has no defaultErrnoRet, drop defaultErrnoRet {}",
+                errno_ret
+            );
+        }
+
+        Ok(SeccompProfileResource {
+            api_version: API_VERSION.to_string(),
+            kind: KIND.to_string(),
+            metadata: ObjectMeta {
+                name: name.name.clone(),
+                namespace: name.namespace.clone(),
+            },
+            spec,
+        })
+    }
+
+    pub fn to_yaml(&self) -> Result<String> {
+        Ok(serde_yaml::to_string(self)?)
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SeccompProfileRef {
+    #[serde(rename = "type")]
+    pub profile_type: String,
+    pub localhost_profile: String,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SecurityContext {
+    pub seccomp_profile: SeccompProfileRef,
+}
+
+/// securityContext of the pod or the container which uses a Localhost profile
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SecurityContextSnippet {
+    pub security_context: SecurityContext,
+}
+
+impl SecurityContextSnippet {
+    pub fn localhost(localhost_profile: &Path) -> Self {
+        SecurityContextSnippet {
+            security_context: SecurityContext {
+                seccomp_profile: SeccompProfileRef {
+                    profile_type: "Localhost".to_string(),
+                    localhost_profile: localhost_profile.to_string_lossy().into_owned(),
+                },
+            },
+        }
+    }
+
+    pub fn to_yaml(&self) -> Result<String> {
+        Ok(serde_yaml::to_string(self)?)
+    }
+}
+
+/// Writes the profile to <seccomp_root>/sprofiler/<namespace>/<name>.json and
+/// returns its warnings.
+/// The kubelet only reads OCI profiles, so moby profiles are not written here.
+pub fn write_localhost_profile(
+    seccomp_root: &Path,
+    name: &ProfileName,
+    profile: &LinuxSeccomp,
+) -> Result<Vec<Issue>> {
+    let path = seccomp_root.join(name.localhost_profile());
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    profile_util::write_seccomp_profile(&path, profile, ProfileFormat::Oci)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::gen_seccomp_profile;
+
+    #[test]
+    fn profile_name_from_annotations() -> Result<()> {
+        let annotations: HashMap<String, String> = [
+            ("io.kubernetes.pod.name", "Web_0"),
+            ("io.kubernetes.pod.namespace", "shop"),
+            ("io.kubernetes.container.name", "nginx"),
+        ]
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+        let name = ProfileName::from_annotations(&annotations).unwrap();
+        assert_eq!(name, ProfileName::new("web-0-nginx", Some("shop"))?);
+        assert_eq!(
+            name.localhost_profile(),
+            PathBuf::from("sprofiler/shop/web-0-nginx.json")
+        );
+        assert_eq!(
+            name.operator_profile(),
+            PathBuf::from("operator/shop/web-0-nginx.json")
+        );
+
+        assert!(ProfileName::from_annotations(&HashMap::new()).is_none());
+        assert_eq!(ProfileName::new("-App-", None)?.name, "app");
+        assert_eq!(ProfileName::new("app", None)?.namespace, "default");
+        assert!(ProfileName::new("__", None).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn seccomp_profile_resource() -> Result<()> {
+        let name = ProfileName::new("app", Some("shop"))?;
+        let mut profile = gen_seccomp_profile(vec!["read", "write"])?;
+        profile.set_default_errno_ret(Some(38));
+        let resource = SeccompProfileResource::new(&name, &profile)?;
+        let value = serde_json::to_value(&resource)?;
+
+        assert_eq!(value["apiVersion"], API_VERSION);
+        assert_eq!(value["kind"], "SeccompProfile");
+        assert_eq!(value["metadata"]["name"], "app");
+        assert_eq!(value["metadata"]["namespace"], "shop");
+        assert_eq!(value["spec"]["defaultAction"], "SCMP_ACT_ERRNO");
+        assert_eq!(value["spec"]["syscalls"][0]["names"][1], "write");
+        assert!(value["spec"].get("defaultErrnoRet").is_none());
+
+        let snippet = SecurityContextSnippet::localhost(&name.operator_profile());
+        let value = serde_json::to_value(&snippet)?;
+        assert_eq!(
+            value["securityContext"]["seccompProfile"]["type"],
+            "Localhost"
+        );
+        assert_eq!(
+            value["securityContext"]["seccompProfile"]["localhostProfile"],
+            "operator/shop/app.json"
+        );
+
+        Ok(())
+    }
+}
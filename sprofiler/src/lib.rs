@@ -1,6 +1,16 @@
 pub mod bpf;
+pub mod cbpf;
 pub mod command;
 pub mod dynamic;
+pub mod exec;
+pub mod kubernetes;
+pub mod moby;
 pub mod oci;
+pub mod profile;
 pub mod profile_util;
+pub mod provenance;
 pub mod r#static;
+pub mod systemd;
+
+#[cfg(test)]
+mod test_util;
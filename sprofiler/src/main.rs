@@ -2,6 +2,8 @@ use anyhow::Result;
 use structopt::StructOpt;
 
 use sprofiler::dynamic::{handle_dynamic_analyzer, DynamicSubCommand};
+use sprofiler::exec::{handle_exec_command, ExecCommand};
+use sprofiler::profile::{handle_profile_command, ProfileSubCommand};
 use sprofiler::r#static::{handle_static_analyzer, StaticSubCommand};
 
 #[derive(Debug, StructOpt)]
@@ -9,6 +11,8 @@ use sprofiler::r#static::{handle_static_analyzer, StaticSubCommand};
 enum SprofilerCommand {
     Static(StaticSubCommand),
     Dynamic(DynamicSubCommand),
+    Profile(ProfileSubCommand),
+    Exec(ExecCommand),
 }
 
 fn main() -> Result<()> {
@@ -17,6 +21,8 @@ fn main() -> Result<()> {
     match sprofiler_cmd {
         SprofilerCommand::Static(static_) => handle_static_analyzer(static_)?,
         SprofilerCommand::Dynamic(dynamic) => handle_dynamic_analyzer(dynamic)?,
+        SprofilerCommand::Profile(profile) => handle_profile_command(profile)?,
+        SprofilerCommand::Exec(exec) => handle_exec_command(exec)?,
     }
 
     Ok(())
@@ -0,0 +1,555 @@
+use std::fmt;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use nix::sys::utsname::uname;
+use oci_spec::runtime::{
+    Arch, LinuxSeccomp, LinuxSeccompAction, LinuxSeccompArg, LinuxSeccompBuilder,
+    LinuxSyscallBuilder,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::dynamic::runtime::{parse_version, Version};
+
+/// Capabilities of a container started by docker or podman without --cap-add/--cap-drop
+pub const DEFAULT_CAPABILITIES: [&str; 14] = [
+    "CAP_AUDIT_WRITE",
+    "CAP_CHOWN",
+    "CAP_DAC_OVERRIDE",
+    "CAP_FOWNER",
+    "CAP_FSETID",
+    "CAP_KILL",
+    "CAP_MKNOD",
+    "CAP_NET_BIND_SERVICE",
+    "CAP_NET_RAW",
+    "CAP_SETFCAP",
+    "CAP_SETGID",
+    "CAP_SETPCAP",
+    "CAP_SETUID",
+    "CAP_SYS_CHROOT",
+];
+
+/// Architectures whose syscalls can be executed on the host architecture
+const ARCH_MAP: &[(Arch, &[Arch])] = &[
+    (
+        Arch::ScmpArchX86_64,
+        &[Arch::ScmpArchX86, Arch::ScmpArchX32],
+    ),
+    (Arch::ScmpArchAarch64, &[Arch::ScmpArchArm]),
+    (
+        Arch::ScmpArchMips64,
+        &[Arch::ScmpArchMips, Arch::ScmpArchMips64n32],
+    ),
+    (
+        Arch::ScmpArchMips64n32,
+        &[Arch::ScmpArchMips, Arch::ScmpArchMips64],
+    ),
+    (
+        Arch::ScmpArchMipsel64,
+        &[Arch::ScmpArchMipsel, Arch::ScmpArchMipsel64n32],
+    ),
+    (
+        Arch::ScmpArchMipsel64n32,
+        &[Arch::ScmpArchMipsel, Arch::ScmpArchMipsel64],
+    ),
+    (Arch::ScmpArchS390x, &[Arch::ScmpArchS390]),
+];
+
+/// Architecture names used by moby are GOARCH values
+fn go_arch_to_seccomp(arch: &str) -> Option<Arch> {
+    let arch = match arch {
+        "386" => Arch::ScmpArchX86,
+        "amd64" => Arch::ScmpArchX86_64,
+        "arm" => Arch::ScmpArchArm,
+        "arm64" => Arch::ScmpArchAarch64,
+        "mips64" => Arch::ScmpArchMips64,
+        "mips64le" => Arch::ScmpArchMipsel64,
+        "ppc64le" => Arch::ScmpArchPpc64le,
+        "s390x" => Arch::ScmpArchS390x,
+        _ => return None,
+    };
+    Some(arch)
+}
+
+fn host_arch() -> String {
+    let arch = match std::env::consts::ARCH {
+        "x86" => "386",
+        "x86_64" => "amd64",
+        "aarch64" => "arm64",
+        "powerpc64" => "ppc64le",
+        arch => arch,
+    };
+    arch.to_string()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Filter {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub caps: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub arches: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_kernel: Option<String>,
+}
+
+impl Filter {
+    fn is_empty(&self) -> bool {
+        self.caps.is_empty() && self.arches.is_empty() && self.min_kernel.is_none()
+    }
+
+    fn min_kernel(&self) -> Option<Version> {
+        self.min_kernel.as_deref().and_then(parse_version)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchMap {
+    pub architecture: Arch,
+    #[serde(default)]
+    pub sub_architectures: Vec<Arch>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MobySyscall {
+    /// Deprecated single name form
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub names: Vec<String>,
+    pub action: LinuxSeccompAction,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub errno_ret: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub args: Option<Vec<LinuxSeccompArg>>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub comment: String,
+    #[serde(default, skip_serializing_if = "Filter::is_empty")]
+    pub includes: Filter,
+    #[serde(default, skip_serializing_if = "Filter::is_empty")]
+    pub excludes: Filter,
+}
+
+impl MobySyscall {
+    /// The rule applies to the container
+    fn matches(&self, target: &Target) -> bool {
+        let (includes, excludes) = (&self.includes, &self.excludes);
+
+        if excludes.arches.contains(&target.arch)
+            || excludes.caps.iter().any(|cap| target.caps.contains(cap))
+        {
+            return false;
+        }
+        match (excludes.min_kernel(), target.kernel) {
+            (Some(min_kernel), Some(kernel)) if kernel < min_kernel => {}
+            (Some(_), _) => return false,
+            _ => {}
+        }
+
+        if !includes.arches.is_empty() && !includes.arches.contains(&target.arch) {
+            return false;
+        }
+        if !includes.caps.iter().all(|cap| target.caps.contains(cap)) {
+            return false;
+        }
+        match (includes.min_kernel(), target.kernel) {
+            (Some(min_kernel), Some(kernel)) if kernel < min_kernel => return false,
+            _ => {}
+        }
+
+        true
+    }
+}
+
+/// Seccomp profile format of moby (docker) and containers/common (podman)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MobyProfile {
+    pub default_action: LinuxSeccompAction,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_errno_ret: Option<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub arch_map: Vec<ArchMap>,
+    /// Used if archMap is empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub architectures: Vec<Arch>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub flags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub listener_path: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub listener_metadata: Option<String>,
+    #[serde(default)]
+    pub syscalls: Vec<MobySyscall>,
+}
+
+/// The container a moby profile is resolved for
+#[derive(Clone, Debug, PartialEq)]
+pub struct Target {
+    /// GOARCH, e.g. amd64
+    pub arch: String,
+    pub caps: Vec<String>,
+    /// minKernel conditions are considered satisfied if the kernel is unknown
+    pub kernel: Option<Version>,
+}
+
+impl Default for Target {
+    fn default() -> Self {
+        Target {
+            arch: host_arch(),
+            caps: DEFAULT_CAPABILITIES
+                .iter()
+                .map(|cap| cap.to_string())
+                .collect(),
+            kernel: parse_version(uname().release()),
+        }
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.arch)?;
+        if self.caps.iter().eq(DEFAULT_CAPABILITIES.iter()) {
+            write!(f, " with the default capabilities")?;
+        } else {
+            write!(f, " with the capabilities {}", self.caps.join(","))?;
+        }
+        match self.kernel {
+            Some(kernel) => write!(f, " on kernel {}", kernel),
+            None => write!(f, " on any kernel"),
+        }
+    }
+}
+
+/// How many rules of a moby profile don't apply to the target
+#[derive(Debug, PartialEq)]
+pub struct Resolution {
+    pub target: Target,
+    /// Rules whose includes or excludes don't match the target
+    pub excluded: usize,
+    pub rules: usize,
+}
+
+impl fmt::Display for Resolution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "moby profile resolved for {}: {} of {} rules excluded by includes/excludes",
+            self.target, self.excluded, self.rules
+        )
+    }
+}
+
+/// moby profiles have fields which OCI profiles don't.
+/// OCI profiles parse moby profiles silently, dropping the conditions of the rules.
+pub fn is_moby_profile(value: &serde_json::Value) -> bool {
+    if value.get("archMap").is_some() {
+        return true;
+    }
+
+    let syscalls = match value
+        .get("syscalls")
+        .and_then(|syscalls| syscalls.as_array())
+    {
+        Some(syscalls) => syscalls,
+        None => return false,
+    };
+
+    syscalls.iter().any(|syscall| {
+        ["name", "includes", "excludes", "comment"]
+            .iter()
+            .any(|field| syscall.get(field).is_some())
+    })
+}
+
+impl MobyProfile {
+    /// Sort the names of every rule, the order of the rules is kept as conditions may overlap
+    pub fn canonicalize(&mut self) {
+        for syscall in &mut self.syscalls {
+            syscall.names.sort();
+            syscall.names.dedup();
+        }
+    }
+
+    pub fn from_oci(profile: &LinuxSeccomp) -> Self {
+        let architectures = profile.architectures().clone().unwrap_or_default();
+
+        let mut arch_map = vec![];
+        let mut mapped = vec![];
+        for (arch, sub_archs) in ARCH_MAP {
+            if !architectures.contains(arch) || mapped.contains(arch) {
+                continue;
+            }
+            let sub_architectures: Vec<Arch> = sub_archs
+                .iter()
+                .filter(|sub| architectures.contains(sub))
+                .copied()
+                .collect();
+            mapped.push(*arch);
+            mapped.extend(sub_architectures.iter().copied());
+            arch_map.push(ArchMap {
+                architecture: *arch,
+                sub_architectures,
+            });
+        }
+        for arch in &architectures {
+            if !mapped.contains(arch) {
+                arch_map.push(ArchMap {
+                    architecture: *arch,
+                    sub_architectures: vec![],
+                });
+            }
+        }
+
+        let flags = profile
+            .flags()
+            .iter()
+            .flatten()
+            .filter_map(|flag| match serde_json::to_value(flag) {
+                Ok(serde_json::Value::String(flag)) => Some(flag),
+                _ => None,
+            })
+            .collect();
+
+        let syscalls = profile
+            .syscalls()
+            .iter()
+            .flatten()
+            .map(|syscall| MobySyscall {
+                name: String::new(),
+                names: syscall.names().clone(),
+                action: syscall.action(),
+                errno_ret: syscall.errno_ret(),
+                args: syscall.args().clone(),
+                comment: String::new(),
+                includes: Filter::default(),
+                excludes: Filter::default(),
+            })
+            .collect();
+
+        MobyProfile {
+            default_action: profile.default_action(),
+            default_errno_ret: profile.default_errno_ret(),
+            arch_map,
+            architectures: vec![],
+            flags,
+            listener_path: profile.listener_path().clone(),
+            listener_metadata: profile.listener_metadata().clone(),
+            syscalls,
+        }
+    }
+
+    pub fn resolution(&self, target: &Target) -> Resolution {
+        Resolution {
+            target: target.clone(),
+            excluded: self
+                .syscalls
+                .iter()
+                .filter(|syscall| !syscall.matches(target))
+                .count(),
+            rules: self.syscalls.len(),
+        }
+    }
+
+    /// Resolve conditional rules and archMap like the container engine does
+    pub fn resolve(&self, target: &Target) -> Result<LinuxSeccomp> {
+        let architectures = if self.arch_map.is_empty() {
+            self.architectures.clone()
+        } else {
+            let native = go_arch_to_seccomp(&target.arch);
+            let mut architectures = vec![];
+            for arch_map in &self.arch_map {
+                if Some(arch_map.architecture) == native {
+                    architectures.push(arch_map.architecture);
+                    architectures.extend(arch_map.sub_architectures.iter().copied());
+                }
+            }
+            architectures
+        };
+
+        let mut syscalls = vec![];
+        for syscall in self
+            .syscalls
+            .iter()
+            .filter(|syscall| syscall.matches(target))
+        {
+            let mut names = syscall.names.clone();
+            if !syscall.name.is_empty() {
+                names.insert(0, syscall.name.clone());
+            }
+            if names.is_empty() {
+                continue;
+            }
+
+            let mut builder = LinuxSyscallBuilder::default()
+                .names(names)
+                .action(syscall.action);
+            if let Some(errno_ret) = syscall.errno_ret {
+                builder = builder.errno_ret(errno_ret);
+            }
+            match &syscall.args {
+                Some(args) if !args.is_empty() => builder = builder.args(args.clone()),
+                _ => {}
+            }
+            syscalls.push(builder.build()?);
+        }
+
+        let mut builder = LinuxSeccompBuilder::default().default_action(self.default_action);
+        if let Some(errno_ret) = self.default_errno_ret {
+            builder = builder.default_errno_ret(errno_ret);
+        }
+        if !architectures.is_empty() {
+            builder = builder.architectures(architectures);
+        }
+        if let Some(path) = &self.listener_path {
+            builder = builder.listener_path(path.clone());
+        }
+        if let Some(metadata) = &self.listener_metadata {
+            builder = builder.listener_metadata(metadata.clone());
+        }
+        if !self.flags.is_empty() {
+            builder = builder.flags(self.flags.clone());
+        }
+        if !syscalls.is_empty() {
+            builder = builder.syscalls(syscalls);
+        }
+
+        Ok(builder.build()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOBY_PROFILE: &str = r#"{
+        "defaultAction": "SCMP_ACT_ERRNO",
+        "defaultErrnoRet": 1,
+        "archMap": [
+            {
+                "architecture": "SCMP_ARCH_X86_64",
+                "subArchitectures": ["SCMP_ARCH_X86", "SCMP_ARCH_X32"]
+            },
+            {
+                "architecture": "SCMP_ARCH_AARCH64",
+                "subArchitectures": ["SCMP_ARCH_ARM"]
+            }
+        ],
+        "syscalls": [
+            { "names": ["read", "write"], "action": "SCMP_ACT_ALLOW" },
+            {
+                "names": ["ptrace"],
+                "action": "SCMP_ACT_ALLOW",
+                "includes": { "minKernel": "4.8" }
+            },
+            {
+                "names": ["arch_prctl"],
+                "action": "SCMP_ACT_ALLOW",
+                "includes": { "arches": ["amd64", "x32"] }
+            },
+            {
+                "names": ["mount"],
+                "action": "SCMP_ACT_ALLOW",
+                "includes": { "caps": ["CAP_SYS_ADMIN"] }
+            },
+            {
+                "names": ["clone"],
+                "action": "SCMP_ACT_ALLOW",
+                "args": [{ "index": 0, "value": 2114060288, "valueTwo": 0, "op": "SCMP_CMP_MASKED_EQ" }],
+                "excludes": { "caps": ["CAP_SYS_ADMIN"] }
+            }
+        ]
+    }"#;
+
+    fn names(profile: &LinuxSeccomp) -> Vec<String> {
+        let mut names: Vec<String> = profile
+            .syscalls()
+            .iter()
+            .flatten()
+            .flat_map(|syscall| syscall.names().clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn resolve_moby_profile() -> Result<()> {
+        let value: serde_json::Value = serde_json::from_str(MOBY_PROFILE)?;
+        assert!(is_moby_profile(&value));
+        let moby: MobyProfile = serde_json::from_value(value)?;
+
+        let amd64 = Target {
+            arch: "amd64".to_string(),
+            caps: DEFAULT_CAPABILITIES
+                .iter()
+                .map(|cap| cap.to_string())
+                .collect(),
+            kernel: Some(Version(5, 15, 0)),
+        };
+        let profile = moby.resolve(&amd64)?;
+        assert_eq!(
+            profile.architectures(),
+            &Some(vec![
+                Arch::ScmpArchX86_64,
+                Arch::ScmpArchX86,
+                Arch::ScmpArchX32
+            ])
+        );
+        assert_eq!(
+            names(&profile),
+            vec!["arch_prctl", "clone", "ptrace", "read", "write"]
+        );
+
+        let privileged_arm64 = Target {
+            arch: "arm64".to_string(),
+            caps: vec!["CAP_SYS_ADMIN".to_string()],
+            kernel: Some(Version(4, 4, 0)),
+        };
+        let profile = moby.resolve(&privileged_arm64)?;
+        assert_eq!(
+            profile.architectures(),
+            &Some(vec![Arch::ScmpArchAarch64, Arch::ScmpArchArm])
+        );
+        assert_eq!(names(&profile), vec!["mount", "read", "write"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn export_oci_profile() -> Result<()> {
+        let profile = LinuxSeccompBuilder::default()
+            .default_action(LinuxSeccompAction::ScmpActErrno)
+            .architectures(vec![Arch::ScmpArchX86, Arch::ScmpArchX86_64])
+            .syscalls(vec![LinuxSyscallBuilder::default()
+                .names(vec!["read".to_string()])
+                .action(LinuxSeccompAction::ScmpActAllow)
+                .build()?])
+            .build()?;
+
+        let moby = MobyProfile::from_oci(&profile);
+        assert_eq!(
+            moby.arch_map,
+            vec![ArchMap {
+                architecture: Arch::ScmpArchX86_64,
+                sub_architectures: vec![Arch::ScmpArchX86],
+            }]
+        );
+        assert!(is_moby_profile(&serde_json::to_value(&moby)?));
+
+        let target = Target {
+            arch: "amd64".to_string(),
+            caps: vec![],
+            kernel: None,
+        };
+        let resolved = moby.resolve(&target)?;
+        assert_eq!(
+            resolved.architectures(),
+            &Some(vec![Arch::ScmpArchX86_64, Arch::ScmpArchX86])
+        );
+        assert_eq!(resolved.syscalls(), profile.syscalls());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use anyhow::{bail, Context, Result};
+use oci_spec::runtime::{Arch, LinuxSeccomp};
+use serde::{Deserialize, Serialize};
+
+use crate::cbpf::{self, SeccompData, SECCOMP_RET_ACTION_FULL, SECCOMP_RET_ALLOW, SECCOMP_RET_LOG};
+
+/// A syscall of a recorded trace
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Call {
+    pub syscall: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<u64>,
+    /// x86_64 if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arch: Option<Arch>,
+}
+
+impl fmt::Display for Call {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let args: Vec<String> = self.args.iter().map(|arg| format!("{:#x}", arg)).collect();
+        write!(f, "{}({})", self.syscall, args.join(", "))
+    }
+}
+
+fn parse_arg(arg: &str) -> Result<u64> {
+    let value = match arg.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        // Negative values like AT_FDCWD are passed sign extended
+        None if arg.starts_with('-') => arg.parse::<i64>().map(|value| value as u64),
+        None => arg.parse::<u64>(),
+    };
+    value.with_context(|| format!("invalid argument {}", arg))
+}
+
+/// Traces are a JSON array of calls, JSON lines, or lines of a syscall name and
+/// its arguments separated by whitespace, e.g. "openat -100 0x7ffc1000 0".
+pub fn parse_trace(trace: &str) -> Result<Vec<Call>> {
+    let trimmed = trace.trim_start();
+    if trimmed.starts_with('[') {
+        return Ok(serde_json::from_str(trimmed)?);
+    }
+
+    let mut calls = Vec::new();
+    for (number, line) in trace.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let call = if line.starts_with('{') {
+            serde_json::from_str(line)?
+        } else {
+            let mut fields = line.split_whitespace();
+            Call {
+                syscall: fields.next().unwrap_or_default().to_string(),
+                args: fields.map(parse_arg).collect::<Result<_>>()?,
+                arch: None,
+            }
+        };
+        if call.args.len() > 6 {
+            bail!("line {}: syscalls take at most 6 arguments", number + 1);
+        }
+        calls.push(call);
+    }
+
+    Ok(calls)
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Verdict {
+    #[serde(flatten)]
+    pub call: Call,
+    /// Return value of the filter, e.g. ERRNO(1)
+    pub action: String,
+    /// Number of identical calls in the trace
+    pub count: usize,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct CheckReport {
+    pub calls: usize,
+    pub denied: Vec<Verdict>,
+    /// Architectures of the profile the filter doesn't check like runc does
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped_architectures: Vec<Arch>,
+}
+
+/// Evaluates the calls with the seccomp filter compiled from the profile
+pub fn check(profile: &LinuxSeccomp, calls: &[Call]) -> Result<CheckReport> {
+    let program = cbpf::compile(profile)?;
+
+    let mut unique: Vec<(&Call, usize)> = Vec::new();
+    let mut index: HashMap<(&str, &[u64], String), usize> = HashMap::new();
+    for call in calls {
+        let key = (
+            call.syscall.as_str(),
+            call.args.as_slice(),
+            format!("{:?}", call.arch),
+        );
+        match index.get(&key) {
+            Some(i) => unique[*i].1 += 1,
+            None => {
+                index.insert(key, unique.len());
+                unique.push((call, 1));
+            }
+        }
+    }
+
+    let mut denied = Vec::new();
+    for (call, count) in unique {
+        let arch = call.arch.as_ref().unwrap_or(&Arch::ScmpArchX86_64);
+        let table =
+            cbpf::arch_table(arch).with_context(|| format!("{:?} is not supported", arch))?;
+        let nr = match table
+            .syscalls
+            .iter()
+            .find(|(_, name)| **name == call.syscall)
+        {
+            Some((nr, _)) => *nr,
+            None => {
+                eprintln!(
+                    "{} is not a syscall of {}, skip it",
+                    call.syscall, table.name
+                );
+                continue;
+            }
+        };
+
+        let mut args = [0; 6];
+        args[..call.args.len()].copy_from_slice(&call.args);
+        let value = program.run(&SeccompData {
+            nr,
+            arch: table.audit_arch,
+            instruction_pointer: 0,
+            args,
+        })?;
+
+        if !matches!(
+            value & SECCOMP_RET_ACTION_FULL,
+            SECCOMP_RET_ALLOW | SECCOMP_RET_LOG
+        ) {
+            denied.push(Verdict {
+                call: call.clone(),
+                action: cbpf::action_name(value),
+                count,
+            });
+        }
+    }
+
+    Ok(CheckReport {
+        calls: calls.len(),
+        denied,
+        skipped_architectures: program.skipped,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oci_spec::runtime::{
+        LinuxSeccompAction, LinuxSeccompArgBuilder, LinuxSeccompBuilder, LinuxSeccompOperator,
+        LinuxSyscallBuilder,
+    };
+
+    #[test]
+    fn parse_trace_formats() -> Result<()> {
+        let expected = vec![
+            Call {
+                syscall: "openat".to_string(),
+                args: vec![(-100i64) as u64, 0x7ffc_1000, 0],
+                arch: None,
+            },
+            Call {
+                syscall: "getpid".to_string(),
+                args: vec![],
+                arch: None,
+            },
+        ];
+
+        let text = "# recorded trace\nopenat -100 0x7ffc1000 0\n\ngetpid\n";
+        assert_eq!(parse_trace(text)?, expected);
+
+        let json_lines = format!(
+            "{}\n{}\n",
+            serde_json::to_string(&expected[0])?,
+            serde_json::to_string(&expected[1])?
+        );
+        assert_eq!(parse_trace(&json_lines)?, expected);
+        assert_eq!(parse_trace(&serde_json::to_string(&expected)?)?, expected);
+
+        assert!(parse_trace("read 1 2 3 4 5 6 7").is_err());
+        assert!(parse_trace("read fd").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_trace() -> Result<()> {
+        let profile = LinuxSeccompBuilder::default()
+            .default_action(LinuxSeccompAction::ScmpActErrno)
+            .default_errno_ret(38u32)
+            .architectures(vec![Arch::ScmpArchX86_64])
+            .syscalls(vec![
+                LinuxSyscallBuilder::default()
+                    .names(vec!["read".to_string(), "openat".to_string()])
+                    .action(LinuxSeccompAction::ScmpActAllow)
+                    .build()?,
+                LinuxSyscallBuilder::default()
+                    .names(vec!["personality".to_string()])
+                    .action(LinuxSeccompAction::ScmpActAllow)
+                    .args(vec![LinuxSeccompArgBuilder::default()
+                        .index(0usize)
+                        .value(8u64)
+                        .op(LinuxSeccompOperator::ScmpCmpEq)
+                        .build()?])
+                    .build()?,
+            ])
+            .build()?;
+
+        let calls = parse_trace(
+            "read 0 0x1000 16\nread 3 0x1000 16\nopenat -100 0x1000 0\n\
+             personality 8\npersonality 0xffffffff\nmount\nmount\nnot_a_syscall\n",
+        )?;
+        let report = check(&profile, &calls)?;
+
+        assert_eq!(report.calls, 8);
+        assert_eq!(
+            report
+                .denied
+                .iter()
+                .map(|verdict| (
+                    verdict.call.to_string(),
+                    verdict.action.as_str(),
+                    verdict.count
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("personality(0xffffffff)".to_string(), "ERRNO(38)", 1),
+                ("mount()".to_string(), "ERRNO(38)", 2),
+            ]
+        );
+
+        Ok(())
+    }
+}
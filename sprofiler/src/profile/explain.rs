@@ -0,0 +1,573 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+use oci_spec::runtime::LinuxSeccomp;
+use serde::Serialize;
+
+use super::lint::{self, Severity};
+use crate::profile_util::{self, SyscallAccess};
+use crate::provenance::Provenance;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Category {
+    File,
+    Network,
+    Process,
+    Memory,
+    Ipc,
+    Time,
+    Security,
+    /// Syscalls sprofiler has no description of
+    Unknown,
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Category::File => "file",
+            Category::Network => "network",
+            Category::Process => "process",
+            Category::Memory => "memory",
+            Category::Ipc => "ipc",
+            Category::Time => "time",
+            Category::Security => "security",
+            Category::Unknown => "unknown",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExplainFormat {
+    Text,
+    Json,
+    Markdown,
+}
+
+impl FromStr for ExplainFormat {
+    type Err = String;
+
+    #[rustfmt::skip]
+    fn from_str(format: &str) -> Result<ExplainFormat, Self::Err> {
+        match format.to_lowercase().as_str() {
+            "text"              => Ok(ExplainFormat::Text),
+            "json"              => Ok(ExplainFormat::Json),
+            "markdown" | "md"   => Ok(ExplainFormat::Markdown),
+            _                   => Err(format!("unsupported explain format: {}", format)),
+        }
+    }
+}
+
+use Category::*;
+
+#[rustfmt::skip]
+const SYSCALL_DESCRIPTIONS: &[(&str, Category, &str)] = &[
+    ("_sysctl",                 Process,  "obsolete interface to read and write kernel parameters"),
+    ("accept",                  Network,  "accepts a connection on a listening socket"),
+    ("accept4",                 Network,  "accepts a connection on a listening socket with flags"),
+    ("access",                  File,     "checks whether the process may access a file"),
+    ("acct",                    Security, "switches process accounting on or off"),
+    ("add_key",                 Security, "adds a key to the kernel keyring"),
+    ("adjtimex",                Time,     "reads or tunes the kernel clock"),
+    ("afs_syscall",             Unknown,  "unimplemented"),
+    ("alarm",                   Time,     "delivers SIGALRM after a number of seconds"),
+    ("arch_prctl",              Process,  "sets architecture specific thread state, e.g. the TLS base"),
+    ("bind",                    Network,  "binds a socket to an address"),
+    ("bpf",                     Security, "loads eBPF programs and manages eBPF maps"),
+    ("brk",                     Memory,   "grows or shrinks the heap"),
+    ("cachestat",               File,     "reports page cache statistics of a file"),
+    ("capget",                  Security, "reads the capabilities of a thread"),
+    ("capset",                  Security, "sets the capabilities of a thread"),
+    ("chdir",                   File,     "changes the working directory"),
+    ("chmod",                   File,     "changes the permissions of a file"),
+    ("chown",                   File,     "changes the owner of a file"),
+    ("chroot",                  File,     "changes the root directory"),
+    ("clock_adjtime",           Time,     "tunes a clock"),
+    ("clock_getres",            Time,     "reads the resolution of a clock"),
+    ("clock_gettime",           Time,     "reads a clock"),
+    ("clock_nanosleep",         Time,     "sleeps on a clock"),
+    ("clock_settime",           Time,     "sets a clock"),
+    ("clone",                   Process,  "creates a process or a thread"),
+    ("clone3",                  Process,  "creates a process or a thread with extended arguments"),
+    ("close",                   File,     "closes a file descriptor"),
+    ("close_range",             File,     "closes a range of file descriptors"),
+    ("connect",                 Network,  "connects a socket to an address"),
+    ("copy_file_range",         File,     "copies data between files in the kernel"),
+    ("creat",                   File,     "creates a file"),
+    ("create_module",           Security, "obsolete, allocated kernel module memory"),
+    ("delete_module",           Security, "unloads a kernel module"),
+    ("dup",                     File,     "duplicates a file descriptor"),
+    ("dup2",                    File,     "duplicates a file descriptor to a given number"),
+    ("dup3",                    File,     "duplicates a file descriptor to a given number with flags"),
+    ("epoll_create",            File,     "creates an epoll instance"),
+    ("epoll_create1",           File,     "creates an epoll instance with flags"),
+    ("epoll_ctl",               File,     "adds, modifies or removes file descriptors of an epoll instance"),
+    ("epoll_ctl_old",           Unknown,  "unimplemented"),
+    ("epoll_pwait",             File,     "waits for events of an epoll instance with a signal mask"),
+    ("epoll_pwait2",            File,     "waits for events of an epoll instance with a nanosecond timeout"),
+    ("epoll_wait",              File,     "waits for events of an epoll instance"),
+    ("epoll_wait_old",          Unknown,  "unimplemented"),
+    ("eventfd",                 Ipc,      "creates a file descriptor for event notification"),
+    ("eventfd2",                Ipc,      "creates a file descriptor for event notification with flags"),
+    ("execve",                  Process,  "executes a program"),
+    ("execveat",                Process,  "executes a program relative to a directory file descriptor"),
+    ("exit",                    Process,  "terminates the calling thread"),
+    ("exit_group",              Process,  "terminates all threads of the process"),
+    ("faccessat",               File,     "checks whether the process may access a file"),
+    ("faccessat2",              File,     "checks whether the process may access a file with flags"),
+    ("fadvise64",               File,     "declares the access pattern of file data"),
+    ("fallocate",               File,     "allocates or deallocates space of a file"),
+    ("fanotify_init",           File,     "creates a filesystem-wide event notification group"),
+    ("fanotify_mark",           File,     "adds or removes marks of a fanotify group"),
+    ("fchdir",                  File,     "changes the working directory to an open directory"),
+    ("fchmod",                  File,     "changes the permissions of an open file"),
+    ("fchmodat",                File,     "changes the permissions of a file"),
+    ("fchmodat2",               File,     "changes the permissions of a file with flags"),
+    ("fchown",                  File,     "changes the owner of an open file"),
+    ("fchownat",                File,     "changes the owner of a file"),
+    ("fcntl",                   File,     "manipulates a file descriptor, e.g. flags and locks"),
+    ("fdatasync",               File,     "flushes the data of a file to disk"),
+    ("fgetxattr",               File,     "reads an extended attribute of an open file"),
+    ("file_getattr",            File,     "reads the inode attributes of a file"),
+    ("file_setattr",            File,     "sets the inode attributes of a file"),
+    ("finit_module",            Security, "loads a kernel module from a file"),
+    ("flistxattr",              File,     "lists the extended attributes of an open file"),
+    ("flock",                   File,     "applies or removes an advisory lock on a file"),
+    ("fork",                    Process,  "creates a child process"),
+    ("fremovexattr",            File,     "removes an extended attribute of an open file"),
+    ("fsconfig",                File,     "configures a filesystem context of the new mount API"),
+    ("fsetxattr",               File,     "sets an extended attribute of an open file"),
+    ("fsmount",                 File,     "creates a mount from a filesystem context"),
+    ("fsopen",                  File,     "opens a filesystem context of the new mount API"),
+    ("fspick",                  File,     "opens the filesystem context of an existing mount"),
+    ("fstat",                   File,     "reads the status of an open file"),
+    ("fstatfs",                 File,     "reads filesystem statistics of an open file"),
+    ("fsync",                   File,     "flushes a file to disk"),
+    ("ftruncate",               File,     "truncates an open file"),
+    ("futex",                   Ipc,      "waits on or wakes threads of a fast userspace mutex"),
+    ("futex_requeue",           Ipc,      "moves waiters between futexes"),
+    ("futex_wait",              Ipc,      "waits on a futex"),
+    ("futex_waitv",             Ipc,      "waits on multiple futexes"),
+    ("futex_wake",              Ipc,      "wakes waiters of a futex"),
+    ("futimesat",               File,     "obsolete, changes the timestamps of a file"),
+    ("get_kernel_syms",         Security, "obsolete, read the symbols of the kernel"),
+    ("get_mempolicy",           Memory,   "reads the NUMA memory policy"),
+    ("get_robust_list",         Ipc,      "reads the robust futex list of a thread"),
+    ("get_thread_area",         Process,  "reads a TLS descriptor"),
+    ("getcpu",                  Process,  "reads the CPU and NUMA node the thread runs on"),
+    ("getcwd",                  File,     "reads the working directory"),
+    ("getdents",                File,     "reads directory entries"),
+    ("getdents64",              File,     "reads directory entries"),
+    ("getegid",                 Security, "reads the effective group id"),
+    ("geteuid",                 Security, "reads the effective user id"),
+    ("getgid",                  Security, "reads the real group id"),
+    ("getgroups",               Security, "reads the supplementary group ids"),
+    ("getitimer",               Time,     "reads an interval timer"),
+    ("getpeername",             Network,  "reads the address of the peer of a socket"),
+    ("getpgid",                 Process,  "reads the process group of a process"),
+    ("getpgrp",                 Process,  "reads the process group of the process"),
+    ("getpid",                  Process,  "reads the process id"),
+    ("getpmsg",                 Unknown,  "unimplemented"),
+    ("getppid",                 Process,  "reads the parent process id"),
+    ("getpriority",             Process,  "reads the scheduling priority"),
+    ("getrandom",               Security, "reads random bytes"),
+    ("getresgid",               Security, "reads the real, effective and saved group ids"),
+    ("getresuid",               Security, "reads the real, effective and saved user ids"),
+    ("getrlimit",               Process,  "reads a resource limit"),
+    ("getrusage",               Process,  "reads resource usage"),
+    ("getsid",                  Process,  "reads the session id"),
+    ("getsockname",             Network,  "reads the address of a socket"),
+    ("getsockopt",              Network,  "reads an option of a socket"),
+    ("gettid",                  Process,  "reads the thread id"),
+    ("gettimeofday",            Time,     "reads the time of day"),
+    ("getuid",                  Security, "reads the real user id"),
+    ("getxattr",                File,     "reads an extended attribute of a file"),
+    ("getxattrat",              File,     "reads an extended attribute of a file relative to a directory"),
+    ("init_module",             Security, "loads a kernel module from memory"),
+    ("inotify_add_watch",       File,     "watches a file for changes"),
+    ("inotify_init",            File,     "creates an inotify instance"),
+    ("inotify_init1",           File,     "creates an inotify instance with flags"),
+    ("inotify_rm_watch",        File,     "removes a watch of an inotify instance"),
+    ("io_cancel",               File,     "cancels an asynchronous I/O operation"),
+    ("io_destroy",              File,     "destroys an asynchronous I/O context"),
+    ("io_getevents",            File,     "reads completed asynchronous I/O events"),
+    ("io_pgetevents",           File,     "reads completed asynchronous I/O events with a signal mask"),
+    ("io_setup",                File,     "creates an asynchronous I/O context"),
+    ("io_submit",               File,     "submits asynchronous I/O operations"),
+    ("io_uring_enter",          File,     "submits and waits for io_uring operations"),
+    ("io_uring_register",       File,     "registers files and buffers of an io_uring instance"),
+    ("io_uring_setup",          File,     "creates an io_uring instance"),
+    ("ioctl",                   File,     "device specific operation on a file descriptor"),
+    ("ioperm",                  Security, "sets permissions of I/O ports"),
+    ("iopl",                    Security, "changes the I/O privilege level"),
+    ("ioprio_get",              Process,  "reads the I/O scheduling priority"),
+    ("ioprio_set",              Process,  "sets the I/O scheduling priority"),
+    ("kcmp",                    Process,  "compares kernel resources of two processes"),
+    ("kexec_file_load",         Security, "loads a new kernel from a file"),
+    ("kexec_load",              Security, "loads a new kernel"),
+    ("keyctl",                  Security, "manipulates the kernel keyring"),
+    ("kill",                    Process,  "sends a signal to a process"),
+    ("landlock_add_rule",       Security, "adds a rule to a Landlock ruleset"),
+    ("landlock_create_ruleset", Security, "creates a Landlock ruleset"),
+    ("landlock_restrict_self",  Security, "enforces a Landlock ruleset on the thread"),
+    ("lchown",                  File,     "changes the owner of a symbolic link"),
+    ("lgetxattr",               File,     "reads an extended attribute of a symbolic link"),
+    ("link",                    File,     "creates a hard link"),
+    ("linkat",                  File,     "creates a hard link relative to directory file descriptors"),
+    ("listen",                  Network,  "listens for connections on a socket"),
+    ("listmount",               File,     "lists the mounts under a mount"),
+    ("listxattr",               File,     "lists the extended attributes of a file"),
+    ("listxattrat",             File,     "lists the extended attributes of a file relative to a directory"),
+    ("llistxattr",              File,     "lists the extended attributes of a symbolic link"),
+    ("lookup_dcookie",          Security, "reads the path of a directory entry cookie"),
+    ("lremovexattr",            File,     "removes an extended attribute of a symbolic link"),
+    ("lseek",                   File,     "moves the offset of a file descriptor"),
+    ("lsetxattr",               File,     "sets an extended attribute of a symbolic link"),
+    ("lsm_get_self_attr",       Security, "reads LSM attributes of the process"),
+    ("lsm_list_modules",        Security, "lists the active LSMs"),
+    ("lsm_set_self_attr",       Security, "sets LSM attributes of the process"),
+    ("lstat",                   File,     "reads the status of a file without following symbolic links"),
+    ("madvise",                 Memory,   "declares the usage pattern of memory"),
+    ("map_shadow_stack",        Memory,   "allocates a shadow stack"),
+    ("mbind",                   Memory,   "sets the NUMA memory policy of a memory range"),
+    ("membarrier",              Memory,   "issues memory barriers on the threads of the process"),
+    ("memfd_create",            Memory,   "creates an anonymous file in memory"),
+    ("memfd_secret",            Memory,   "creates memory hidden from the kernel"),
+    ("migrate_pages",           Memory,   "moves the pages of a process to other NUMA nodes"),
+    ("mincore",                 Memory,   "reports whether pages are resident in memory"),
+    ("mkdir",                   File,     "creates a directory"),
+    ("mkdirat",                 File,     "creates a directory relative to a directory file descriptor"),
+    ("mknod",                   File,     "creates a device or special file"),
+    ("mknodat",                 File,     "creates a device or special file relative to a directory"),
+    ("mlock",                   Memory,   "locks memory in RAM"),
+    ("mlock2",                  Memory,   "locks memory in RAM with flags"),
+    ("mlockall",                Memory,   "locks all memory of the process in RAM"),
+    ("mmap",                    Memory,   "maps files or anonymous memory"),
+    ("modify_ldt",              Process,  "reads or writes the local descriptor table"),
+    ("mount",                   File,     "mounts a filesystem"),
+    ("mount_setattr",           File,     "changes the properties of a mount tree"),
+    ("move_mount",              File,     "moves or attaches a mount"),
+    ("move_pages",              Memory,   "moves pages to other NUMA nodes"),
+    ("mprotect",                Memory,   "changes the protection of memory"),
+    ("mq_getsetattr",           Ipc,      "reads or sets the attributes of a message queue"),
+    ("mq_notify",               Ipc,      "registers for notification of messages of a message queue"),
+    ("mq_open",                 Ipc,      "opens a POSIX message queue"),
+    ("mq_timedreceive",         Ipc,      "receives a message from a message queue"),
+    ("mq_timedsend",            Ipc,      "sends a message to a message queue"),
+    ("mq_unlink",               Ipc,      "removes a message queue"),
+    ("mremap",                  Memory,   "resizes or moves a memory mapping"),
+    ("mseal",                   Memory,   "seals a memory mapping against changes"),
+    ("msgctl",                  Ipc,      "controls a System V message queue"),
+    ("msgget",                  Ipc,      "opens a System V message queue"),
+    ("msgrcv",                  Ipc,      "receives a message from a System V message queue"),
+    ("msgsnd",                  Ipc,      "sends a message to a System V message queue"),
+    ("msync",                   Memory,   "flushes a file mapping to disk"),
+    ("munlock",                 Memory,   "unlocks memory"),
+    ("munlockall",              Memory,   "unlocks all memory of the process"),
+    ("munmap",                  Memory,   "unmaps memory"),
+    ("name_to_handle_at",       File,     "reads the handle of a file"),
+    ("nanosleep",               Time,     "sleeps"),
+    ("newfstatat",              File,     "reads the status of a file relative to a directory"),
+    ("nfsservctl",              Unknown,  "removed NFS daemon interface"),
+    ("open",                    File,     "opens a file"),
+    ("open_by_handle_at",       File,     "opens a file by its handle"),
+    ("open_tree",               File,     "opens or clones a mount tree"),
+    ("open_tree_attr",          File,     "clones a mount tree with attributes"),
+    ("openat",                  File,     "opens a file relative to a directory file descriptor"),
+    ("openat2",                 File,     "opens a file with path resolution restrictions"),
+    ("pause",                   Process,  "waits for a signal"),
+    ("perf_event_open",         Security, "opens a performance monitoring counter"),
+    ("personality",             Process,  "sets the execution domain, e.g. to disable ASLR"),
+    ("pidfd_getfd",             Process,  "duplicates a file descriptor of another process"),
+    ("pidfd_open",              Process,  "opens a file descriptor referring to a process"),
+    ("pidfd_send_signal",       Process,  "sends a signal to a process by its file descriptor"),
+    ("pipe",                    Ipc,      "creates a pipe"),
+    ("pipe2",                   Ipc,      "creates a pipe with flags"),
+    ("pivot_root",              File,     "changes the root mount"),
+    ("pkey_alloc",              Memory,   "allocates a memory protection key"),
+    ("pkey_free",               Memory,   "frees a memory protection key"),
+    ("pkey_mprotect",           Memory,   "changes the protection of memory with a protection key"),
+    ("poll",                    File,     "waits for events on file descriptors"),
+    ("ppoll",                   File,     "waits for events on file descriptors with a signal mask"),
+    ("prctl",                   Process,  "reads or sets process attributes, e.g. the name or no_new_privs"),
+    ("pread64",                 File,     "reads from a file at an offset"),
+    ("preadv",                  File,     "reads from a file at an offset into multiple buffers"),
+    ("preadv2",                 File,     "reads from a file at an offset into multiple buffers with flags"),
+    ("prlimit64",               Process,  "reads or sets a resource limit of a process"),
+    ("process_madvise",         Memory,   "declares the usage pattern of memory of another process"),
+    ("process_mrelease",        Memory,   "releases the memory of a dying process"),
+    ("process_vm_readv",        Memory,   "reads memory of another process"),
+    ("process_vm_writev",       Memory,   "writes memory of another process"),
+    ("pselect6",                File,     "waits for file descriptors to become ready with a signal mask"),
+    ("ptrace",                  Process,  "traces another process"),
+    ("putpmsg",                 Unknown,  "unimplemented"),
+    ("pwrite64",                File,     "writes to a file at an offset"),
+    ("pwritev",                 File,     "writes to a file at an offset from multiple buffers"),
+    ("pwritev2",                File,     "writes to a file at an offset from multiple buffers with flags"),
+    ("query_module",            Security, "obsolete, queried kernel modules"),
+    ("quotactl",                File,     "manipulates disk quotas"),
+    ("quotactl_fd",             File,     "manipulates disk quotas of the filesystem of an open file"),
+    ("read",                    File,     "reads from a file descriptor"),
+    ("readahead",               File,     "reads a file into the page cache"),
+    ("readlink",                File,     "reads the target of a symbolic link"),
+    ("readlinkat",              File,     "reads the target of a symbolic link relative to a directory"),
+    ("readv",                   File,     "reads from a file descriptor into multiple buffers"),
+    ("reboot",                  Security, "reboots or halts the system"),
+    ("recvfrom",                Network,  "receives a message from a socket"),
+    ("recvmmsg",                Network,  "receives multiple messages from a socket"),
+    ("recvmsg",                 Network,  "receives a message and ancillary data from a socket"),
+    ("remap_file_pages",        Memory,   "deprecated, creates a nonlinear file mapping"),
+    ("removexattr",             File,     "removes an extended attribute of a file"),
+    ("removexattrat",           File,     "removes an extended attribute of a file relative to a directory"),
+    ("rename",                  File,     "renames a file"),
+    ("renameat",                File,     "renames a file relative to directory file descriptors"),
+    ("renameat2",               File,     "renames or exchanges files with flags"),
+    ("request_key",             Security, "requests a key from the kernel keyring"),
+    ("restart_syscall",         Process,  "restarts a syscall interrupted by a signal"),
+    ("rmdir",                   File,     "removes a directory"),
+    ("rseq",                    Process,  "registers restartable sequences"),
+    ("rt_sigaction",            Process,  "sets the handler of a signal"),
+    ("rt_sigpending",           Process,  "reads the pending signals"),
+    ("rt_sigprocmask",          Process,  "blocks or unblocks signals"),
+    ("rt_sigqueueinfo",         Process,  "sends a signal with data to a process"),
+    ("rt_sigreturn",            Process,  "returns from a signal handler"),
+    ("rt_sigsuspend",           Process,  "waits for a signal with a temporary signal mask"),
+    ("rt_sigtimedwait",         Process,  "waits for a blocked signal with a timeout"),
+    ("rt_tgsigqueueinfo",       Process,  "sends a signal with data to a thread"),
+    ("sched_get_priority_max",  Process,  "reads the maximum priority of a scheduling policy"),
+    ("sched_get_priority_min",  Process,  "reads the minimum priority of a scheduling policy"),
+    ("sched_getaffinity",       Process,  "reads the CPU affinity of a thread"),
+    ("sched_getattr",           Process,  "reads the scheduling policy and attributes of a thread"),
+    ("sched_getparam",          Process,  "reads the scheduling parameters of a thread"),
+    ("sched_getscheduler",      Process,  "reads the scheduling policy of a thread"),
+    ("sched_rr_get_interval",   Process,  "reads the round-robin time slice of a thread"),
+    ("sched_setaffinity",       Process,  "sets the CPU affinity of a thread"),
+    ("sched_setattr",           Process,  "sets the scheduling policy and attributes of a thread"),
+    ("sched_setparam",          Process,  "sets the scheduling parameters of a thread"),
+    ("sched_setscheduler",      Process,  "sets the scheduling policy of a thread"),
+    ("sched_yield",             Process,  "yields the CPU"),
+    ("seccomp",                 Security, "installs seccomp filters"),
+    ("security",                Unknown,  "unimplemented"),
+    ("select",                  File,     "waits for file descriptors to become ready"),
+    ("semctl",                  Ipc,      "controls a System V semaphore set"),
+    ("semget",                  Ipc,      "opens a System V semaphore set"),
+    ("semop",                   Ipc,      "operates on a System V semaphore set"),
+    ("semtimedop",              Ipc,      "operates on a System V semaphore set with a timeout"),
+    ("sendfile",                File,     "copies data between file descriptors in the kernel"),
+    ("sendmmsg",                Network,  "sends multiple messages on a socket"),
+    ("sendmsg",                 Network,  "sends a message and ancillary data on a socket"),
+    ("sendto",                  Network,  "sends a message on a socket"),
+    ("set_mempolicy",           Memory,   "sets the NUMA memory policy"),
+    ("set_mempolicy_home_node", Memory,   "sets the home NUMA node of a memory range"),
+    ("set_robust_list",         Ipc,      "registers the robust futex list of a thread, used by glibc at thread start"),
+    ("set_thread_area",         Process,  "sets a TLS descriptor"),
+    ("set_tid_address",         Process,  "sets the address cleared when the thread exits, used by glibc at start"),
+    ("setdomainname",           Security, "sets the NIS domain name"),
+    ("setfsgid",                Security, "sets the group id for filesystem checks"),
+    ("setfsuid",                Security, "sets the user id for filesystem checks"),
+    ("setgid",                  Security, "sets the group id"),
+    ("setgroups",               Security, "sets the supplementary group ids"),
+    ("sethostname",             Security, "sets the hostname"),
+    ("setitimer",               Time,     "sets an interval timer"),
+    ("setns",                   Security, "joins a namespace"),
+    ("setpgid",                 Process,  "sets the process group of a process"),
+    ("setpriority",             Process,  "sets the scheduling priority"),
+    ("setregid",                Security, "sets the real and effective group ids"),
+    ("setresgid",               Security, "sets the real, effective and saved group ids"),
+    ("setresuid",               Security, "sets the real, effective and saved user ids"),
+    ("setreuid",                Security, "sets the real and effective user ids"),
+    ("setrlimit",               Process,  "sets a resource limit"),
+    ("setsid",                  Process,  "creates a session"),
+    ("setsockopt",              Network,  "sets an option of a socket"),
+    ("settimeofday",            Time,     "sets the time of day"),
+    ("setuid",                  Security, "sets the user id"),
+    ("setxattr",                File,     "sets an extended attribute of a file"),
+    ("setxattrat",              File,     "sets an extended attribute of a file relative to a directory"),
+    ("shmat",                   Ipc,      "attaches a System V shared memory segment"),
+    ("shmctl",                  Ipc,      "controls a System V shared memory segment"),
+    ("shmdt",                   Ipc,      "detaches a System V shared memory segment"),
+    ("shmget",                  Ipc,      "opens a System V shared memory segment"),
+    ("shutdown",                Network,  "shuts down a socket connection"),
+    ("sigaltstack",             Process,  "sets the alternate stack of signal handlers"),
+    ("signalfd",                Process,  "creates a file descriptor for receiving signals"),
+    ("signalfd4",               Process,  "creates a file descriptor for receiving signals with flags"),
+    ("socket",                  Network,  "creates a socket"),
+    ("socketpair",              Network,  "creates a pair of connected sockets"),
+    ("splice",                  File,     "moves data between a pipe and a file descriptor"),
+    ("stat",                    File,     "reads the status of a file"),
+    ("statfs",                  File,     "reads filesystem statistics"),
+    ("statmount",               File,     "reads information of a mount"),
+    ("statx",                   File,     "reads the extended status of a file"),
+    ("swapoff",                 Memory,   "stops swapping to a device or file"),
+    ("swapon",                  Memory,   "starts swapping to a device or file"),
+    ("symlink",                 File,     "creates a symbolic link"),
+    ("symlinkat",               File,     "creates a symbolic link relative to a directory"),
+    ("sync",                    File,     "flushes all filesystems to disk"),
+    ("sync_file_range",         File,     "flushes a range of a file to disk"),
+    ("syncfs",                  File,     "flushes the filesystem of an open file to disk"),
+    ("sysfs",                   File,     "obsolete, reads filesystem type information"),
+    ("sysinfo",                 Process,  "reads system statistics, e.g. memory and uptime"),
+    ("syslog",                  Security, "reads or controls the kernel log"),
+    ("tee",                     Ipc,      "duplicates data between pipes"),
+    ("tgkill",                  Process,  "sends a signal to a thread"),
+    ("time",                    Time,     "reads the time in seconds"),
+    ("timer_create",            Time,     "creates a POSIX timer"),
+    ("timer_delete",            Time,     "deletes a POSIX timer"),
+    ("timer_getoverrun",        Time,     "reads the overrun count of a POSIX timer"),
+    ("timer_gettime",           Time,     "reads a POSIX timer"),
+    ("timer_settime",           Time,     "arms or disarms a POSIX timer"),
+    ("timerfd_create",          Time,     "creates a timer file descriptor"),
+    ("timerfd_gettime",         Time,     "reads a timer file descriptor"),
+    ("timerfd_settime",         Time,     "arms or disarms a timer file descriptor"),
+    ("times",                   Time,     "reads the process times"),
+    ("tkill",                   Process,  "obsolete, sends a signal to a thread"),
+    ("truncate",                File,     "truncates a file"),
+    ("tuxcall",                 Unknown,  "unimplemented"),
+    ("umask",                   File,     "sets the file mode creation mask"),
+    ("umount2",                 File,     "unmounts a filesystem"),
+    ("uname",                   Process,  "reads the kernel name and version"),
+    ("unlink",                  File,     "removes a file"),
+    ("unlinkat",                File,     "removes a file relative to a directory file descriptor"),
+    ("unshare",                 Security, "moves the process into new namespaces"),
+    ("uselib",                  Security, "obsolete, loads a shared library"),
+    ("userfaultfd",             Memory,   "handles page faults in userspace"),
+    ("ustat",                   File,     "obsolete, reads filesystem statistics"),
+    ("utime",                   File,     "changes the timestamps of a file"),
+    ("utimensat",               File,     "changes the timestamps of a file with nanosecond precision"),
+    ("utimes",                  File,     "changes the timestamps of a file"),
+    ("vfork",                   Process,  "creates a child process sharing memory until it executes"),
+    ("vhangup",                 Process,  "hangs up the terminal"),
+    ("vmsplice",                Ipc,      "moves user memory into a pipe"),
+    ("vserver",                 Unknown,  "unimplemented"),
+    ("wait4",                   Process,  "waits for a child process to change state"),
+    ("waitid",                  Process,  "waits for a child process to change state"),
+    ("write",                   File,     "writes to a file descriptor"),
+    ("writev",                  File,     "writes to a file descriptor from multiple buffers"),
+];
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Explanation {
+    pub syscall: String,
+    pub access: SyscallAccess,
+    pub category: Category,
+    pub description: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub risk: Option<Severity>,
+    /// Processes which called the syscall while the profile was traced
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub processes: Vec<String>,
+}
+
+pub fn describe(syscall: &str) -> (Category, &'static str) {
+    SYSCALL_DESCRIPTIONS
+        .iter()
+        .find(|(name, _, _)| *name == syscall)
+        .map(|(_, category, description)| (*category, *description))
+        .unwrap_or((Category::Unknown, ""))
+}
+
+/// Explains every syscall the profile allows, including those allowed by the
+/// default action of a deny list profile
+pub fn explain(profile: &LinuxSeccomp, provenance: Option<&Provenance>) -> Vec<Explanation> {
+    let mut names: BTreeSet<&str> = profile
+        .syscalls()
+        .iter()
+        .flatten()
+        .flat_map(|syscall| syscall.names())
+        .map(String::as_str)
+        .collect();
+    names.extend(SYSCALL_DESCRIPTIONS.iter().map(|(name, _, _)| *name));
+
+    let mut explanations = vec![];
+    for name in names {
+        let access = profile_util::syscall_access(profile, name);
+        if access == SyscallAccess::Denied {
+            continue;
+        }
+
+        let (category, description) = describe(name);
+        let processes = provenance
+            .and_then(|provenance| provenance.syscall_processes.get(name))
+            .map(|processes| processes.iter().cloned().collect())
+            .unwrap_or_default();
+
+        explanations.push(Explanation {
+            syscall: name.to_string(),
+            access,
+            category,
+            description,
+            risk: lint::syscall_risk(name, access),
+            processes,
+        });
+    }
+
+    explanations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provenance::Generator;
+    use anyhow::Result;
+    use oci_spec::runtime::{LinuxSeccompAction, LinuxSeccompBuilder, LinuxSyscallBuilder};
+
+    #[test]
+    fn descriptions_are_sorted() {
+        let names: Vec<&str> = SYSCALL_DESCRIPTIONS
+            .iter()
+            .map(|(name, _, _)| *name)
+            .collect();
+        let mut sorted = names.clone();
+        sorted.sort_unstable();
+        sorted.dedup();
+        assert_eq!(names, sorted);
+    }
+
+    #[test]
+    fn explain_profile() -> Result<()> {
+        let profile = LinuxSeccompBuilder::default()
+            .default_action(LinuxSeccompAction::ScmpActErrno)
+            .syscalls(vec![LinuxSyscallBuilder::default()
+                .names(vec![
+                    "set_robust_list".to_string(),
+                    "ptrace".to_string(),
+                    "not_a_syscall".to_string(),
+                ])
+                .action(LinuxSeccompAction::ScmpActAllow)
+                .build()?])
+            .build()?;
+
+        let mut provenance = Provenance::new(Generator::Dynamic);
+        provenance.syscall_processes.insert(
+            "ptrace".to_string(),
+            ["gdb".to_string()].into_iter().collect(),
+        );
+
+        let explanations = explain(&profile, Some(&provenance));
+        assert_eq!(
+            explanations
+                .iter()
+                .map(|e| (
+                    e.syscall.as_str(),
+                    e.category,
+                    e.risk,
+                    e.processes.join(",")
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("not_a_syscall", Category::Unknown, None, "".to_string()),
+                (
+                    "ptrace",
+                    Category::Process,
+                    Some(Severity::High),
+                    "gdb".to_string()
+                ),
+                ("set_robust_list", Category::Ipc, None, "".to_string()),
+            ]
+        );
+
+        Ok(())
+    }
+}
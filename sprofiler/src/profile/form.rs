@@ -0,0 +1,344 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{bail, Result};
+use oci_spec::runtime::{
+    LinuxSeccomp, LinuxSeccompAction, LinuxSeccompArg, LinuxSeccompArgBuilder, LinuxSeccompBuilder,
+    LinuxSeccompOperator, LinuxSyscall, LinuxSyscallBuilder,
+};
+use serde::{Deserialize, Serialize};
+
+use super::validate::ARCHITECTURES;
+use crate::cbpf;
+use crate::profile_util;
+
+/// How a profile lists syscalls
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ProfileForm {
+    /// Denies by default and allows the used syscalls
+    Allowlist,
+    /// Allows by default and denies the unused syscalls
+    Denylist,
+    /// The form with fewer syscall entries
+    Auto,
+}
+
+impl FromStr for ProfileForm {
+    type Err = String;
+
+    #[rustfmt::skip]
+    fn from_str(form: &str) -> Result<ProfileForm, Self::Err> {
+        match form.to_lowercase().as_str() {
+            "allowlist" | "allow" => Ok(ProfileForm::Allowlist),
+            "denylist" | "deny"   => Ok(ProfileForm::Denylist),
+            "auto"                => Ok(ProfileForm::Auto),
+            _                     => Err(format!("unsupported profile form: {}", form)),
+        }
+    }
+}
+
+impl fmt::Display for ProfileForm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ProfileForm::Allowlist => "allowlist",
+            ProfileForm::Denylist => "denylist",
+            ProfileForm::Auto => "auto",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl ProfileForm {
+    pub fn of(profile: &LinuxSeccomp) -> ProfileForm {
+        if profile_util::is_allowing(profile.default_action()) {
+            ProfileForm::Denylist
+        } else {
+            ProfileForm::Allowlist
+        }
+    }
+}
+
+/// Number of syscall names over all rules, what a reader has to go through
+pub fn syscall_entries(profile: &LinuxSeccomp) -> usize {
+    profile
+        .syscalls()
+        .iter()
+        .flatten()
+        .map(|syscall| syscall.names().len())
+        .sum()
+}
+
+#[rustfmt::skip]
+fn negate(op: LinuxSeccompOperator) -> Option<LinuxSeccompOperator> {
+    match op {
+        LinuxSeccompOperator::ScmpCmpEq => Some(LinuxSeccompOperator::ScmpCmpNe),
+        LinuxSeccompOperator::ScmpCmpNe => Some(LinuxSeccompOperator::ScmpCmpEq),
+        LinuxSeccompOperator::ScmpCmpLt => Some(LinuxSeccompOperator::ScmpCmpGe),
+        LinuxSeccompOperator::ScmpCmpGe => Some(LinuxSeccompOperator::ScmpCmpLt),
+        LinuxSeccompOperator::ScmpCmpLe => Some(LinuxSeccompOperator::ScmpCmpGt),
+        LinuxSeccompOperator::ScmpCmpGt => Some(LinuxSeccompOperator::ScmpCmpLe),
+        LinuxSeccompOperator::ScmpCmpMaskedEq => None,
+    }
+}
+
+fn deny_rule(
+    profile: &LinuxSeccomp,
+    names: Vec<String>,
+    args: Option<Vec<LinuxSeccompArg>>,
+) -> Result<LinuxSyscall> {
+    let mut builder = LinuxSyscallBuilder::default()
+        .names(names)
+        .action(profile.default_action());
+    if let Some(errno_ret) = profile.default_errno_ret() {
+        builder = builder.errno_ret(errno_ret);
+    }
+    if let Some(args) = args {
+        builder = builder.args(args);
+    }
+    Ok(builder.build()?)
+}
+
+/// Allowing a syscall only if all argument conditions hold is denying it if any
+/// of them fails, a deny rule per negated condition
+fn negated_rules(
+    profile: &LinuxSeccomp,
+    name: &str,
+    rule: &LinuxSyscall,
+) -> Result<Vec<LinuxSyscall>> {
+    let mut rules = vec![];
+    for arg in rule.args().iter().flatten() {
+        let op = match negate(arg.op()) {
+            Some(op) => op,
+            None => bail!(
+                "{}: {:?} can't be negated for the denylist form",
+                name,
+                arg.op()
+            ),
+        };
+        let arg = LinuxSeccompArgBuilder::default()
+            .index(arg.index())
+            .value(arg.value())
+            .op(op)
+            .build()?;
+        rules.push(deny_rule(profile, vec![name.to_string()], Some(vec![arg]))?);
+    }
+    Ok(rules)
+}
+
+/// The denylist equivalent of an allowlist profile on the architectures of the
+/// profile: every known syscall without a rule is denied with the default action.
+/// Architectures without a syscall table (e.g. SCMP_ARCH_X86 of generated profiles)
+/// are dropped, syscalls only they have couldn't be denied.
+pub fn to_denylist(profile: &LinuxSeccomp) -> Result<LinuxSeccomp> {
+    if ProfileForm::of(profile) == ProfileForm::Denylist {
+        return Ok(profile.clone());
+    }
+
+    let candidates = match profile.architectures() {
+        Some(architectures) if !architectures.is_empty() => architectures.clone(),
+        _ => ARCHITECTURES.to_vec(),
+    };
+    let mut architectures = vec![];
+    let mut known = BTreeSet::new();
+    for arch in candidates {
+        match cbpf::arch_table(&arch) {
+            Some(table) => {
+                known.extend(table.syscalls.values().copied());
+                architectures.push(arch);
+            }
+            None => eprintln!(
+                "WARNING: sprofiler has no syscall table of {:?}, it is dropped from the denylist form",
+                arch
+            ),
+        }
+    }
+    if architectures.is_empty() {
+        bail!("sprofiler has no syscall table of the architectures of the profile");
+    }
+
+    let mut rules_by_name: BTreeMap<&str, Vec<&LinuxSyscall>> = BTreeMap::new();
+    for syscall in profile.syscalls().iter().flatten() {
+        for name in syscall.names() {
+            rules_by_name.entry(name).or_default().push(syscall);
+        }
+    }
+
+    let mut syscalls = vec![];
+    let denied: Vec<String> = known
+        .iter()
+        .filter(|name| !rules_by_name.contains_key(*name))
+        .map(|name| name.to_string())
+        .collect();
+    if !denied.is_empty() {
+        syscalls.push(deny_rule(profile, denied, None)?);
+    }
+
+    for (name, rules) in rules_by_name {
+        let allows = rules
+            .iter()
+            .all(|rule| rule.action() == LinuxSeccompAction::ScmpActAllow);
+        let unconditional =
+            |rule: &&LinuxSyscall| !matches!(rule.args(), Some(args) if !args.is_empty());
+
+        match (allows, rules.as_slice()) {
+            (true, rules) if rules.iter().any(unconditional) => {}
+            (true, [rule]) => syscalls.extend(negated_rules(profile, name, rule)?),
+            (true, _) => bail!(
+                "{}: several argument rules can't be negated for the denylist form",
+                name
+            ),
+            // e.g. SCMP_ACT_LOG or an errno of its own, kept as it is
+            (false, [rule]) if unconditional(rule) => {
+                let mut builder = LinuxSyscallBuilder::default()
+                    .names(vec![name.to_string()])
+                    .action(rule.action());
+                if let Some(errno_ret) = rule.errno_ret() {
+                    builder = builder.errno_ret(errno_ret);
+                }
+                syscalls.push(builder.build()?);
+            }
+            (false, _) => bail!(
+                "{}: argument rules of actions other than SCMP_ACT_ALLOW can't be kept in the denylist form",
+                name
+            ),
+        }
+    }
+
+    let mut builder = LinuxSeccompBuilder::default()
+        .default_action(LinuxSeccompAction::ScmpActAllow)
+        .syscalls(syscalls);
+    if matches!(profile.architectures(), Some(archs) if !archs.is_empty()) {
+        builder = builder.architectures(architectures);
+    }
+    if let Some(flags) = profile.flags() {
+        builder = builder.flags(flags.clone());
+    }
+    if let Some(path) = profile.listener_path() {
+        builder = builder.listener_path(path.clone());
+    }
+    if let Some(metadata) = profile.listener_metadata() {
+        builder = builder.listener_metadata(metadata.clone());
+    }
+
+    profile_util::canonicalize(&builder.build()?)
+}
+
+/// Rewrites a generated allowlist profile in the requested form, auto keeps the
+/// allowlist if the denylist isn't shorter or can't express the profile
+pub fn select(profile: &LinuxSeccomp, form: ProfileForm) -> Result<LinuxSeccomp> {
+    let selected = match form {
+        ProfileForm::Allowlist => {
+            if ProfileForm::of(profile) != ProfileForm::Allowlist {
+                bail!("denylist profiles can't be converted to the allowlist form");
+            }
+            profile.clone()
+        }
+        ProfileForm::Denylist => to_denylist(profile)?,
+        ProfileForm::Auto => match to_denylist(profile) {
+            Ok(denylist) if syscall_entries(&denylist) < syscall_entries(profile) => denylist,
+            Ok(_) => profile.clone(),
+            Err(e) => {
+                eprintln!("{}, keep the allowlist form", e);
+                profile.clone()
+            }
+        },
+    };
+
+    eprintln!(
+        "{} form with {} syscall entries",
+        ProfileForm::of(&selected),
+        syscall_entries(&selected)
+    );
+    Ok(selected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profile_util::SyscallAccess;
+    use crate::test_util::gen_seccomp_profile;
+    use oci_spec::runtime::Arch;
+
+    fn allowlist(names: &[&str]) -> Result<LinuxSeccomp> {
+        let mut profile = gen_seccomp_profile(names.to_vec())?;
+        profile.set_default_errno_ret(Some(1));
+
+        let mut syscalls = profile.syscalls().clone().unwrap_or_default();
+        syscalls.push(
+            LinuxSyscallBuilder::default()
+                .names(vec!["personality".to_string()])
+                .action(LinuxSeccompAction::ScmpActAllow)
+                .args(vec![LinuxSeccompArgBuilder::default()
+                    .index(0usize)
+                    .value(8u64)
+                    .op(LinuxSeccompOperator::ScmpCmpEq)
+                    .build()?])
+                .build()?,
+        );
+        profile.set_syscalls(Some(syscalls));
+
+        Ok(profile)
+    }
+
+    #[test]
+    fn convert_to_denylist() -> Result<()> {
+        let profile = allowlist(&["read", "write", "exit_group"])?;
+        let denylist = to_denylist(&profile)?;
+
+        assert_eq!(ProfileForm::of(&denylist), ProfileForm::Denylist);
+        // SCMP_ARCH_X86 has no syscall table
+        assert_eq!(denylist.architectures(), &Some(vec![Arch::ScmpArchX86_64]));
+        for name in [
+            "read",
+            "write",
+            "exit_group",
+            "mount",
+            "ptrace",
+            "personality",
+        ] {
+            assert_eq!(
+                profile_util::syscall_access(&denylist, name),
+                profile_util::syscall_access(&profile, name),
+                "{}",
+                name
+            );
+        }
+        assert_eq!(
+            profile_util::syscall_access(&denylist, "personality"),
+            SyscallAccess::Conditional
+        );
+
+        let mount = profile_util::rules_for(&denylist, "mount");
+        assert_eq!(mount[0].action(), LinuxSeccompAction::ScmpActErrno);
+        assert_eq!(mount[0].errno_ret(), Some(1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn select_form() -> Result<()> {
+        let small = allowlist(&["read", "write"])?;
+        assert_eq!(select(&small, ProfileForm::Auto)?, small);
+
+        let table = cbpf::arch_table(&Arch::ScmpArchX86_64).unwrap();
+        let names: Vec<&str> = table
+            .syscalls
+            .values()
+            .copied()
+            .filter(|name| *name != "kexec_load")
+            .collect();
+        let large = allowlist(&names)?;
+        let selected = select(&large, ProfileForm::Auto)?;
+        assert_eq!(ProfileForm::of(&selected), ProfileForm::Denylist);
+        assert_eq!(
+            profile_util::syscall_access(&selected, "kexec_load"),
+            SyscallAccess::Denied
+        );
+
+        assert!(select(&selected, ProfileForm::Allowlist).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,299 @@
+use std::fmt;
+
+use oci_spec::runtime::{LinuxSeccomp, LinuxSeccompAction, LinuxSeccompOperator};
+use serde::Serialize;
+
+use crate::profile_util::{self, SyscallAccess};
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    fn weight(&self) -> u32 {
+        match self {
+            Severity::Low => 2,
+            Severity::Medium => 5,
+            Severity::High => 10,
+            Severity::Critical => 25,
+        }
+    }
+
+    fn lower(&self) -> Severity {
+        match self {
+            Severity::Critical => Severity::High,
+            Severity::High => Severity::Medium,
+            _ => Severity::Low,
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+            Severity::Critical => "critical",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+struct DangerousSyscall {
+    name: &'static str,
+    severity: Severity,
+    reason: &'static str,
+}
+
+#[rustfmt::skip]
+const DANGEROUS_SYSCALLS: &[DangerousSyscall] = &[
+    DangerousSyscall { name: "acct",              severity: Severity::Medium,   reason: "switches process accounting for the whole host" },
+    DangerousSyscall { name: "add_key",           severity: Severity::Medium,   reason: "the kernel keyring is not namespaced" },
+    DangerousSyscall { name: "bpf",               severity: Severity::Critical, reason: "loads eBPF programs into the kernel and reads kernel memory" },
+    DangerousSyscall { name: "clock_adjtime",     severity: Severity::Medium,   reason: "the system clock is not namespaced" },
+    DangerousSyscall { name: "clock_settime",     severity: Severity::Medium,   reason: "the system clock is not namespaced" },
+    DangerousSyscall { name: "delete_module",     severity: Severity::Critical, reason: "unloads kernel modules" },
+    DangerousSyscall { name: "finit_module",      severity: Severity::Critical, reason: "loads kernel modules" },
+    DangerousSyscall { name: "fsconfig",          severity: Severity::High,     reason: "configures filesystems with the new mount API" },
+    DangerousSyscall { name: "fsmount",           severity: Severity::High,     reason: "creates mounts with the new mount API" },
+    DangerousSyscall { name: "fsopen",            severity: Severity::High,     reason: "opens filesystems with the new mount API" },
+    DangerousSyscall { name: "init_module",       severity: Severity::Critical, reason: "loads kernel modules" },
+    DangerousSyscall { name: "io_uring_setup",    severity: Severity::Medium,   reason: "io_uring operations are not filtered by seccomp" },
+    DangerousSyscall { name: "ioperm",            severity: Severity::Critical, reason: "gives access to I/O ports of the host" },
+    DangerousSyscall { name: "iopl",              severity: Severity::Critical, reason: "gives access to I/O ports of the host" },
+    DangerousSyscall { name: "kexec_file_load",   severity: Severity::Critical, reason: "replaces the running kernel" },
+    DangerousSyscall { name: "kexec_load",        severity: Severity::Critical, reason: "replaces the running kernel" },
+    DangerousSyscall { name: "keyctl",            severity: Severity::Medium,   reason: "the kernel keyring is not namespaced" },
+    DangerousSyscall { name: "lookup_dcookie",    severity: Severity::Medium,   reason: "tracing facility which may leak kernel information" },
+    DangerousSyscall { name: "mount",             severity: Severity::High,     reason: "changes the mount table, e.g. to remount read-only paths" },
+    DangerousSyscall { name: "move_mount",        severity: Severity::High,     reason: "attaches mounts with the new mount API" },
+    DangerousSyscall { name: "name_to_handle_at", severity: Severity::Medium,   reason: "exposes file handles used by open_by_handle_at" },
+    DangerousSyscall { name: "open_by_handle_at", severity: Severity::Critical, reason: "opens host files by handle, known container breakout (shocker)" },
+    DangerousSyscall { name: "open_tree",         severity: Severity::High,     reason: "clones mounts with the new mount API" },
+    DangerousSyscall { name: "perf_event_open",   severity: Severity::High,     reason: "large kernel attack surface and may leak host information" },
+    DangerousSyscall { name: "personality",       severity: Severity::Low,      reason: "may disable ASLR and enable legacy execution domains" },
+    DangerousSyscall { name: "pidfd_getfd",       severity: Severity::High,     reason: "steals file descriptors from other processes" },
+    DangerousSyscall { name: "pivot_root",        severity: Severity::High,     reason: "changes the root filesystem" },
+    DangerousSyscall { name: "process_vm_readv",  severity: Severity::High,     reason: "reads memory of other processes" },
+    DangerousSyscall { name: "process_vm_writev", severity: Severity::High,     reason: "writes memory of other processes" },
+    DangerousSyscall { name: "ptrace",            severity: Severity::High,     reason: "inspects and modifies other processes, bypasses seccomp before Linux 4.8" },
+    DangerousSyscall { name: "quotactl",          severity: Severity::Medium,   reason: "quotas are not namespaced" },
+    DangerousSyscall { name: "reboot",            severity: Severity::High,     reason: "reboots the host outside of a PID namespace" },
+    DangerousSyscall { name: "request_key",       severity: Severity::Medium,   reason: "the kernel keyring is not namespaced" },
+    DangerousSyscall { name: "setns",             severity: Severity::High,     reason: "joins other namespaces" },
+    DangerousSyscall { name: "settimeofday",      severity: Severity::Medium,   reason: "the system clock is not namespaced" },
+    DangerousSyscall { name: "swapoff",           severity: Severity::Medium,   reason: "changes swap of the host" },
+    DangerousSyscall { name: "swapon",            severity: Severity::Medium,   reason: "changes swap of the host" },
+    DangerousSyscall { name: "syslog",            severity: Severity::Medium,   reason: "reads the kernel log which may leak kernel addresses" },
+    DangerousSyscall { name: "umount2",           severity: Severity::High,     reason: "changes the mount table, e.g. to reveal masked paths" },
+    DangerousSyscall { name: "unshare",           severity: Severity::High,     reason: "creates namespaces, user namespaces expose a large kernel attack surface" },
+    DangerousSyscall { name: "userfaultfd",       severity: Severity::Medium,   reason: "widens race windows used by kernel exploits" },
+    DangerousSyscall { name: "uselib",            severity: Severity::Medium,   reason: "obsolete, loads shared libraries in kernel" },
+    DangerousSyscall { name: "vm86",              severity: Severity::High,     reason: "obsolete virtual 8086 mode with a history of vulnerabilities" },
+];
+
+/// CLONE_NEWNS | CLONE_NEWCGROUP | CLONE_NEWUTS | CLONE_NEWIPC | CLONE_NEWUSER | CLONE_NEWPID | CLONE_NEWNET
+const CLONE_NAMESPACE_FLAGS: u64 = 0x7E02_0000;
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Finding {
+    pub severity: Severity,
+    pub check: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub syscall: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.syscall {
+            Some(syscall) => write!(f, "[{}] {}: {}", self.severity, syscall, self.message),
+            None => write!(f, "[{}] {}", self.severity, self.message),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LintReport {
+    /// 0 (no findings) to 100
+    pub score: u32,
+    pub findings: Vec<Finding>,
+}
+
+fn check_default_action(profile: &LinuxSeccomp, findings: &mut Vec<Finding>) {
+    let (severity, message) = match profile.default_action() {
+        LinuxSeccompAction::ScmpActAllow => (
+            Severity::Critical,
+            "default action SCMP_ACT_ALLOW allows every syscall which is not listed",
+        ),
+        LinuxSeccompAction::ScmpActLog => (
+            Severity::High,
+            "default action SCMP_ACT_LOG only logs syscalls which are not listed",
+        ),
+        _ => return,
+    };
+
+    findings.push(Finding {
+        severity,
+        check: "default-action",
+        syscall: None,
+        message: message.to_string(),
+    });
+}
+
+/// Risk of a dangerous syscall, lowered if the profile allows it only for some arguments
+pub fn syscall_risk(name: &str, access: SyscallAccess) -> Option<Severity> {
+    let dangerous = DANGEROUS_SYSCALLS
+        .iter()
+        .find(|dangerous| dangerous.name == name)?;
+    match access {
+        SyscallAccess::Allowed => Some(dangerous.severity),
+        SyscallAccess::Conditional => Some(dangerous.severity.lower()),
+        SyscallAccess::Denied => None,
+    }
+}
+
+fn check_dangerous_syscalls(profile: &LinuxSeccomp, findings: &mut Vec<Finding>) {
+    for dangerous in DANGEROUS_SYSCALLS {
+        let (severity, message) = match profile_util::syscall_access(profile, dangerous.name) {
+            SyscallAccess::Allowed => {
+                (dangerous.severity, format!("allowed, {}", dangerous.reason))
+            }
+            SyscallAccess::Conditional => (
+                dangerous.severity.lower(),
+                format!("allowed for some arguments, {}", dangerous.reason),
+            ),
+            SyscallAccess::Denied => continue,
+        };
+
+        findings.push(Finding {
+            severity,
+            check: "dangerous-syscall",
+            syscall: Some(dangerous.name.to_string()),
+            message,
+        });
+    }
+}
+
+/// clone(2) is allowed for every process, but its flags can create namespaces like unshare(2)
+fn check_clone_namespaces(profile: &LinuxSeccomp, findings: &mut Vec<Finding>) {
+    let message = match profile_util::syscall_access(profile, "clone") {
+        SyscallAccess::Allowed => {
+            "allowed with any flags, namespace flags like CLONE_NEWUSER aren't masked"
+        }
+        SyscallAccess::Conditional => {
+            // e.g. {"index": 0, "value": 2114060288, "valueTwo": 0, "op": "SCMP_CMP_MASKED_EQ"}
+            let masks_namespaces = profile_util::rules_for(profile, "clone")
+                .into_iter()
+                .filter(|rule| profile_util::is_allowing(rule.action()))
+                .all(|rule| {
+                    rule.args().iter().flatten().any(|arg| {
+                        arg.index() == 0
+                            && arg.op() == LinuxSeccompOperator::ScmpCmpMaskedEq
+                            && arg.value() & CLONE_NAMESPACE_FLAGS == CLONE_NAMESPACE_FLAGS
+                            && arg.value_two().unwrap_or(0) & CLONE_NAMESPACE_FLAGS == 0
+                    })
+                });
+            if masks_namespaces {
+                return;
+            }
+            "allowed by a rule which doesn't mask namespace flags like CLONE_NEWUSER"
+        }
+        SyscallAccess::Denied => return,
+    };
+
+    findings.push(Finding {
+        severity: Severity::High,
+        check: "clone-namespaces",
+        syscall: Some("clone".to_string()),
+        message: message.to_string(),
+    });
+}
+
+pub fn lint(profile: &LinuxSeccomp) -> LintReport {
+    let mut findings = vec![];
+
+    check_default_action(profile, &mut findings);
+    check_dangerous_syscalls(profile, &mut findings);
+    check_clone_namespaces(profile, &mut findings);
+
+    findings.sort_by(|a, b| b.severity.cmp(&a.severity).then(a.syscall.cmp(&b.syscall)));
+
+    let score = findings
+        .iter()
+        .map(|finding| finding.severity.weight())
+        .sum::<u32>()
+        .min(100);
+
+    LintReport { score, findings }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::gen_seccomp_profile;
+    use anyhow::Result;
+    use oci_spec::runtime::{LinuxSeccompArgBuilder, LinuxSyscallBuilder};
+
+    #[test]
+    fn lint_default_allow() -> Result<()> {
+        let mut profile = gen_seccomp_profile(vec!["read"])?;
+        profile.set_default_action(LinuxSeccompAction::ScmpActAllow);
+        let report = lint(&profile);
+
+        assert_eq!(report.score, 100);
+        assert_eq!(report.findings[0].check, "default-action");
+        assert_eq!(report.findings[0].severity, Severity::Critical);
+
+        Ok(())
+    }
+
+    #[test]
+    fn lint_dangerous_syscalls() -> Result<()> {
+        let report = lint(&gen_seccomp_profile(vec!["read", "ptrace", "personality"])?);
+
+        assert_eq!(report.score, 12);
+        assert_eq!(report.findings.len(), 2);
+        assert_eq!(report.findings[0].syscall, Some("ptrace".to_string()));
+        assert_eq!(report.findings[0].severity, Severity::High);
+        assert_eq!(report.findings[1].syscall, Some("personality".to_string()));
+
+        let report = lint(&gen_seccomp_profile(vec!["read"])?);
+        assert_eq!(report.score, 0);
+        assert!(report.findings.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn lint_clone_namespace_flags() -> Result<()> {
+        let report = lint(&gen_seccomp_profile(vec!["clone"])?);
+        assert_eq!(report.findings.len(), 1);
+        assert_eq!(report.findings[0].check, "clone-namespaces");
+
+        let masked_clone = LinuxSyscallBuilder::default()
+            .names(vec!["clone".to_string()])
+            .action(LinuxSeccompAction::ScmpActAllow)
+            .args(vec![LinuxSeccompArgBuilder::default()
+                .index(0usize)
+                .value(CLONE_NAMESPACE_FLAGS)
+                .value_two(0u64)
+                .op(LinuxSeccompOperator::ScmpCmpMaskedEq)
+                .build()?])
+            .build()?;
+        let mut profile = gen_seccomp_profile(vec![])?;
+        profile.set_syscalls(Some(vec![masked_clone]));
+
+        assert!(lint(&profile).findings.is_empty());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,714 @@
+pub mod check;
+pub mod explain;
+pub mod form;
+pub mod lint;
+pub mod port;
+pub mod reference;
+pub mod stats;
+pub mod validate;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use structopt::StructOpt;
+
+use crate::cbpf::{self, disasm};
+use crate::kubernetes::{self, ProfileName, SeccompProfileResource, SecurityContextSnippet};
+use crate::moby::Target;
+use crate::profile_util::{self, ProfileFormat, SyscallAccess};
+use crate::provenance::Provenance;
+use crate::systemd;
+use explain::{ExplainFormat, Explanation};
+use port::TargetArch;
+use reference::Reference;
+use stats::FleetProfile;
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "profile", about = "Inspect seccomp profiles")]
+pub enum ProfileSubCommand {
+    /// Report dangerous syscalls and rules which weaken isolation with a risk score
+    Lint {
+        #[structopt(parse(from_os_str))]
+        path: PathBuf,
+        /// Output the report as JSON
+        #[structopt(long)]
+        json: bool,
+    },
+    /// Check syscall names, architectures and argument rules of a profile
+    Validate {
+        #[structopt(parse(from_os_str))]
+        path: PathBuf,
+        /// Output the issues as JSON
+        #[structopt(long)]
+        json: bool,
+        /// Exit with an error on warnings too
+        #[structopt(long)]
+        strict: bool,
+    },
+    /// Rewrite profiles in place as canonical pretty JSON or YAML (.yaml, .yml)
+    Fmt {
+        #[structopt(parse(from_os_str), required = true)]
+        paths: Vec<PathBuf>,
+        /// List the profiles which aren't formatted instead of rewriting them
+        #[structopt(long)]
+        check: bool,
+    },
+    /// Show where a generated profile came from, recorded in <profile>.meta.json
+    Info {
+        #[structopt(parse(from_os_str))]
+        path: PathBuf,
+        /// Output the provenance as JSON
+        #[structopt(long)]
+        json: bool,
+    },
+    /// Describe each allowed syscall with its category, risk and the processes which
+    /// needed it while tracing
+    Explain {
+        #[structopt(parse(from_os_str))]
+        path: PathBuf,
+        /// Output format (text, json, markdown)
+        #[structopt(long, default_value = "text")]
+        format: ExplainFormat,
+    },
+    /// Syscall frequencies, outliers and clusters of similar profiles of a directory
+    /// of profiles, to design shared baselines
+    Stats {
+        #[structopt(parse(from_os_str))]
+        dir: PathBuf,
+        /// Syscalls allowed by at most this percentage of the profiles are rare
+        #[structopt(long, default_value = "5")]
+        rare_percent: f64,
+        /// Minimum share of syscalls two profiles have in common to cluster them (0-1)
+        #[structopt(long, default_value = "0.8")]
+        similarity: f64,
+        /// Write the syscall x profile matrix as CSV
+        #[structopt(long, parse(from_os_str))]
+        matrix: Option<PathBuf>,
+        /// Output the statistics as JSON
+        #[structopt(long)]
+        json: bool,
+    },
+    /// Compare a profile with the default profile of a container engine
+    Compare {
+        #[structopt(parse(from_os_str))]
+        path: PathBuf,
+        /// Reference profile (podman, moby)
+        #[structopt(short, long, default_value = "podman")]
+        reference: Reference,
+        /// Output the comparison as JSON
+        #[structopt(long)]
+        json: bool,
+        /// Write a profile which allows only what both profiles allow
+        #[structopt(long, parse(from_os_str))]
+        intersect: Option<PathBuf>,
+    },
+    /// Convert a profile between the OCI and the moby format
+    Convert {
+        #[structopt(parse(from_os_str))]
+        path: PathBuf,
+        /// Output profile format (oci, moby)
+        #[structopt(long, default_value = "oci")]
+        to: ProfileFormat,
+        /// Architecture (GOARCH) to resolve moby profiles for, defaults to the host
+        #[structopt(long)]
+        arch: Option<String>,
+        /// Capabilities to resolve moby profiles for, defaults to the default capabilities
+        #[structopt(long = "cap")]
+        caps: Vec<String>,
+        /// Output seccomp profile path
+        #[structopt(short, long, parse(from_os_str))]
+        out: PathBuf,
+    },
+    /// Rewrite a profile for another architecture, e.g. open to openat for aarch64
+    Port {
+        #[structopt(parse(from_os_str))]
+        path: PathBuf,
+        /// Target architecture (x86_64, aarch64)
+        #[structopt(long)]
+        to: TargetArch,
+        /// Output seccomp profile path
+        #[structopt(short, long, parse(from_os_str))]
+        out: PathBuf,
+        /// Output the renamed and unmappable syscalls as JSON
+        #[structopt(long)]
+        json: bool,
+    },
+    /// Evaluate the syscalls of a recorded trace with a profile like the kernel does
+    Check {
+        #[structopt(parse(from_os_str))]
+        path: PathBuf,
+        /// Trace as a JSON array, JSON lines or lines of "<syscall> [args...]"
+        #[structopt(parse(from_os_str))]
+        trace: PathBuf,
+        /// Output the denied calls as JSON
+        #[structopt(long)]
+        json: bool,
+        /// Exit with an error if the profile denies any call of the trace
+        #[structopt(long)]
+        fail_on_denied: bool,
+    },
+    /// Compile a profile to the seccomp BPF program, e.g. for bwrap --seccomp
+    Compile {
+        #[structopt(parse(from_os_str))]
+        path: PathBuf,
+        /// Output path of the array of struct sock_filter
+        #[structopt(short, long, parse(from_os_str))]
+        out: PathBuf,
+    },
+    /// Print the seccomp BPF program of a profile
+    Disasm {
+        #[structopt(parse(from_os_str))]
+        path: PathBuf,
+    },
+    /// Export a profile as a security-profiles-operator SeccompProfile resource and
+    /// a kubelet localhost profile
+    Kubernetes {
+        #[structopt(parse(from_os_str))]
+        path: PathBuf,
+        /// Profile name, defaults to the file name of the profile
+        #[structopt(long)]
+        name: Option<String>,
+        /// Namespace of the SeccompProfile resource
+        #[structopt(short, long, default_value = "default")]
+        namespace: String,
+        /// Output SeccompProfile YAML path, printed to stdout if not set
+        #[structopt(long, parse(from_os_str))]
+        crd: Option<PathBuf>,
+        /// Write the profile under the kubelet seccomp root (e.g. /var/lib/kubelet/seccomp)
+        #[structopt(long, parse(from_os_str))]
+        localhost_dir: Option<PathBuf>,
+    },
+    /// Export a profile as a systemd unit drop-in with SystemCallFilter=
+    Systemd {
+        #[structopt(parse(from_os_str))]
+        path: PathBuf,
+        /// Replace syscalls by the systemd sets (@basic-io, ...) allowed as a whole.
+        /// The sets are copied from systemd, check them with `systemd-analyze syscall-filter`
+        #[structopt(long)]
+        groups: bool,
+        /// Output drop-in path (e.g. /etc/systemd/system/<unit>.d/seccomp.conf)
+        #[structopt(short, long, parse(from_os_str))]
+        out: Option<PathBuf>,
+    },
+    /// Output the default profile of a container engine (podman, moby)
+    Reference {
+        reference: Reference,
+        /// Output seccomp profile path
+        #[structopt(short, long, parse(from_os_str))]
+        out: Option<PathBuf>,
+    },
+}
+
+pub fn do_lint(path: PathBuf, json: bool) -> Result<()> {
+    let profile = profile_util::read_seccomp_profile(&path)?;
+    let report = lint::lint(&profile);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    println!("risk score: {}/100", report.score);
+    for finding in &report.findings {
+        println!("{}", finding);
+    }
+
+    Ok(())
+}
+
+pub fn do_validate(path: PathBuf, json: bool, strict: bool) -> Result<()> {
+    let profile = profile_util::read_seccomp_profile(&path)?;
+    let validation = validate::validate(&profile);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&validation)?);
+    } else {
+        for issue in &validation.issues {
+            println!("{}", issue);
+        }
+    }
+
+    if validation.has_errors() || (strict && !validation.issues.is_empty()) {
+        bail!("{} is invalid", path.display());
+    }
+
+    Ok(())
+}
+
+pub fn do_fmt(paths: Vec<PathBuf>, check: bool) -> Result<()> {
+    let mut unformatted = 0;
+    for path in &paths {
+        let content = fs::read_to_string(path)?;
+        let formatted = profile_util::format_profile(path)?;
+        if content == formatted {
+            continue;
+        }
+
+        unformatted += 1;
+        if check {
+            println!("{}", path.display());
+        } else {
+            fs::write(path, formatted)?;
+        }
+    }
+
+    if check && unformatted > 0 {
+        bail!(
+            "{} of {} profiles are not formatted",
+            unformatted,
+            paths.len()
+        );
+    }
+
+    Ok(())
+}
+
+pub fn do_info(path: PathBuf, json: bool) -> Result<()> {
+    let provenance = match Provenance::load(&path)? {
+        Some(provenance) => provenance,
+        None => bail!("{} has no provenance", path.display()),
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&provenance)?);
+    } else {
+        print!("{}", provenance);
+    }
+
+    Ok(())
+}
+
+fn print_markdown_explanations(path: &Path, explanations: &[Explanation]) {
+    println!("## Syscalls allowed by `{}`", path.display());
+    println!();
+    println!("| Syscall | Category | Risk | Description | Needed by |");
+    println!("| --- | --- | --- | --- | --- |");
+    for explanation in explanations {
+        let syscall = match explanation.access {
+            SyscallAccess::Conditional => format!("`{}` (some arguments)", explanation.syscall),
+            _ => format!("`{}`", explanation.syscall),
+        };
+        println!(
+            "| {} | {} | {} | {} | {} |",
+            syscall,
+            explanation.category,
+            explanation
+                .risk
+                .map(|risk| format!("**{}**", risk))
+                .unwrap_or_default(),
+            explanation.description,
+            explanation.processes.join(", ")
+        );
+    }
+}
+
+fn print_explanations(explanations: &[Explanation]) {
+    let width = explanations
+        .iter()
+        .map(|explanation| explanation.syscall.len())
+        .max()
+        .unwrap_or(0)
+        .max("SYSCALL".len());
+    println!(
+        "{:width$}  {:11}  {:8}  {:8}  DESCRIPTION",
+        "SYSCALL",
+        "ACCESS",
+        "CATEGORY",
+        "RISK",
+        width = width
+    );
+    for explanation in explanations {
+        let access = match explanation.access {
+            SyscallAccess::Conditional => "conditional",
+            _ => "allowed",
+        };
+        let mut description = explanation.description.to_string();
+        if !explanation.processes.is_empty() {
+            description = format!(
+                "{} (needed by {})",
+                description,
+                explanation.processes.join(", ")
+            );
+        }
+        println!(
+            "{:width$}  {:11}  {:8}  {:8}  {}",
+            explanation.syscall,
+            access,
+            explanation.category.to_string(),
+            explanation
+                .risk
+                .map(|risk| risk.to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            description,
+            width = width
+        );
+    }
+}
+
+pub fn do_explain(path: PathBuf, format: ExplainFormat) -> Result<()> {
+    let profile = profile_util::read_seccomp_profile(&path)?;
+    let provenance = Provenance::load(&path)?;
+    let explanations = explain::explain(&profile, provenance.as_ref());
+
+    match format {
+        ExplainFormat::Text => print_explanations(&explanations),
+        ExplainFormat::Json => println!("{}", serde_json::to_string_pretty(&explanations)?),
+        ExplainFormat::Markdown => print_markdown_explanations(&path, &explanations),
+    }
+
+    Ok(())
+}
+
+pub fn do_stats(
+    dir: PathBuf,
+    rare_percent: f64,
+    similarity: f64,
+    matrix: Option<PathBuf>,
+    json: bool,
+) -> Result<()> {
+    let mut profiles = Vec::new();
+    for path in stats::profile_paths(&dir)? {
+        let profile = match profile_util::read_profile_value(&path) {
+            Ok(value) if stats::is_profile(&value) => profile_util::parse_seccomp_profile(value),
+            Ok(_) => {
+                eprintln!("{}: skip, not a seccomp profile", path.display());
+                continue;
+            }
+            Err(e) => Err(e),
+        };
+        let profile = match profile {
+            Ok(profile) => profile,
+            Err(e) => {
+                eprintln!("{}: skip, {}", path.display(), e);
+                continue;
+            }
+        };
+
+        let name = path
+            .strip_prefix(&dir)
+            .unwrap_or(&path)
+            .display()
+            .to_string();
+        match FleetProfile::new(name, &profile) {
+            Some(profile) => profiles.push(profile),
+            None => eprintln!("{}: skip the deny list profile", path.display()),
+        }
+    }
+    if profiles.is_empty() {
+        bail!("{} has no allow list profiles", dir.display());
+    }
+
+    if let Some(matrix) = matrix {
+        fs::write(&matrix, stats::matrix_csv(&profiles))?;
+    }
+
+    let stats = stats::stats(&profiles, rare_percent, similarity);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+
+    println!("{} profiles", stats.profiles);
+    println!();
+    println!("Syscalls:");
+    for frequency in &stats.frequencies {
+        println!(
+            "  {:24} {:5} {:5.1}%",
+            frequency.syscall, frequency.profiles, frequency.percent
+        );
+    }
+    println!();
+    println!("Outliers:");
+    for outlier in &stats.outliers {
+        println!("  {}: {}", outlier.profile, outlier.rare.join(", "));
+    }
+    println!();
+    println!("Clusters:");
+    for (i, cluster) in stats.clusters.iter().enumerate() {
+        println!("  {}: {}", i + 1, cluster.profiles.join(", "));
+        println!(
+            "     {} common syscalls: {}",
+            cluster.common.len(),
+            cluster.common.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+pub fn do_compare(
+    path: PathBuf,
+    reference: Reference,
+    json: bool,
+    intersect: Option<PathBuf>,
+) -> Result<()> {
+    let profile = profile_util::read_seccomp_profile(&path)?;
+    let comparison = reference::compare(&profile, reference)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&comparison)?);
+    } else {
+        if comparison.stricter {
+            println!(
+                "{} is stricter than the {} default",
+                path.display(),
+                reference
+            );
+        } else {
+            println!(
+                "{} allows more than the {} default",
+                path.display(),
+                reference
+            );
+        }
+        println!(
+            "Additionally allowed syscalls: {:?}",
+            comparison.additionally_allowed
+        );
+        println!(
+            "Additionally denied syscalls: {:?}",
+            comparison.additionally_denied
+        );
+    }
+
+    if let Some(out) = intersect {
+        let result = profile_util::intersect_profiles(vec![reference.profile()?, profile])?;
+        for conflict in &result.conflicts {
+            eprintln!("conflict: {}", conflict);
+        }
+
+        for issue in profile_util::write_seccomp_profile(&out, &result.profile, ProfileFormat::Oci)?
+        {
+            eprintln!("{}", issue);
+        }
+    }
+
+    Ok(())
+}
+
+pub fn do_convert(
+    path: PathBuf,
+    to: ProfileFormat,
+    arch: Option<String>,
+    caps: Vec<String>,
+    out: PathBuf,
+) -> Result<()> {
+    let mut target = Target::default();
+    if let Some(arch) = arch {
+        target.arch = arch;
+    }
+    if !caps.is_empty() {
+        target.caps = caps;
+    }
+    let (profile, resolution) = profile_util::read_seccomp_profile_for(&path, &target)?;
+    if let Some(resolution) = resolution {
+        eprintln!("{}", resolution);
+    }
+
+    for issue in profile_util::write_seccomp_profile(&out, &profile, to)? {
+        eprintln!("{}", issue);
+    }
+
+    Ok(())
+}
+
+pub fn do_port(path: PathBuf, to: TargetArch, out: PathBuf, json: bool) -> Result<()> {
+    let profile = profile_util::read_seccomp_profile(&path)?;
+    let result = port::port(&profile, &to.0)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    } else {
+        for renamed in &result.renamed {
+            eprintln!("{} -> {}", renamed.syscall, renamed.to.join(", "));
+        }
+        for unmappable in &result.unmappable {
+            eprintln!("unmappable: {}", unmappable);
+        }
+    }
+
+    for issue in profile_util::write_seccomp_profile(&out, &result.profile, ProfileFormat::Oci)? {
+        eprintln!("{}", issue);
+    }
+
+    Ok(())
+}
+
+pub fn do_check(path: PathBuf, trace: PathBuf, json: bool, fail_on_denied: bool) -> Result<()> {
+    let profile = profile_util::read_seccomp_profile(&path)?;
+    let calls = check::parse_trace(&fs::read_to_string(&trace)?)?;
+    let report = check::check(&profile, &calls)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        for arch in &report.skipped_architectures {
+            eprintln!(
+                "WARNING: {:?} has no syscall table, the compiled filter kills its calls unlike runc",
+                arch
+            );
+        }
+        for verdict in &report.denied {
+            println!("{} x{}: {}", verdict.call, verdict.count, verdict.action);
+        }
+        let denied: usize = report.denied.iter().map(|verdict| verdict.count).sum();
+        println!("{} of {} calls would be denied", denied, report.calls);
+    }
+
+    if fail_on_denied && !report.denied.is_empty() {
+        bail!("{} denies calls of {}", path.display(), trace.display());
+    }
+
+    Ok(())
+}
+
+pub fn do_compile(path: PathBuf, out: PathBuf) -> Result<()> {
+    let profile = profile_util::read_seccomp_profile(&path)?;
+    let program = cbpf::compile(&profile)?;
+
+    fs::write(&out, program.to_bytes())?;
+    eprintln!("{} instructions", program.len());
+
+    Ok(())
+}
+
+pub fn do_disasm(path: PathBuf) -> Result<()> {
+    let profile = profile_util::read_seccomp_profile(&path)?;
+    let program = cbpf::compile(&profile)?;
+
+    print!("{}", disasm::disasm(&program));
+    println!("{} instructions", program.len());
+
+    Ok(())
+}
+
+pub fn do_kubernetes(
+    path: PathBuf,
+    name: Option<String>,
+    namespace: String,
+    crd: Option<PathBuf>,
+    localhost_dir: Option<PathBuf>,
+) -> Result<()> {
+    let profile = profile_util::read_seccomp_profile(&path)?;
+    let name = match name {
+        Some(name) => name,
+        None => path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+    };
+    let name = ProfileName::new(&name, Some(&namespace))?;
+
+    let resource = SeccompProfileResource::new(&name, &profile)?;
+    match crd {
+        Some(crd) => fs::write(&crd, resource.to_yaml()?)?,
+        None => print!("{}", resource.to_yaml()?),
+    }
+
+    // Pods refer to the profile installed by the operator unless it is written locally
+    let localhost_profile = match localhost_dir {
+        Some(dir) => {
+            for issue in kubernetes::write_localhost_profile(&dir, &name, &profile)? {
+                eprintln!("{}", issue);
+            }
+            name.localhost_profile()
+        }
+        None => name.operator_profile(),
+    };
+    print!(
+        "{}",
+        SecurityContextSnippet::localhost(&localhost_profile).to_yaml()?
+    );
+
+    Ok(())
+}
+
+pub fn do_systemd(path: PathBuf, groups: bool, out: Option<PathBuf>) -> Result<()> {
+    let profile = profile_util::read_seccomp_profile(&path)?;
+    let drop_in = systemd::export(&profile, groups);
+
+    for name in &drop_in.conditional {
+        eprintln!(
+            "{}: rules depending on arguments are not supported by systemd",
+            name
+        );
+    }
+
+    match out {
+        Some(out) => fs::write(&out, drop_in.to_string())?,
+        None => print!("{}", drop_in),
+    }
+
+    Ok(())
+}
+
+pub fn do_reference(reference: Reference, out: Option<PathBuf>) -> Result<()> {
+    let profile = reference.profile()?;
+
+    match out {
+        Some(out) => {
+            for issue in profile_util::write_seccomp_profile(&out, &profile, ProfileFormat::Oci)? {
+                eprintln!("{}", issue);
+            }
+        }
+        None => println!("{}", serde_json::to_string_pretty(&profile)?),
+    }
+
+    Ok(())
+}
+
+pub fn handle_profile_command(profile: ProfileSubCommand) -> Result<()> {
+    match profile {
+        ProfileSubCommand::Lint { path, json } => do_lint(path, json)?,
+        ProfileSubCommand::Validate { path, json, strict } => do_validate(path, json, strict)?,
+        ProfileSubCommand::Fmt { paths, check } => do_fmt(paths, check)?,
+        ProfileSubCommand::Info { path, json } => do_info(path, json)?,
+        ProfileSubCommand::Explain { path, format } => do_explain(path, format)?,
+        ProfileSubCommand::Stats {
+            dir,
+            rare_percent,
+            similarity,
+            matrix,
+            json,
+        } => do_stats(dir, rare_percent, similarity, matrix, json)?,
+        ProfileSubCommand::Compare {
+            path,
+            reference,
+            json,
+            intersect,
+        } => do_compare(path, reference, json, intersect)?,
+        ProfileSubCommand::Convert {
+            path,
+            to,
+            arch,
+            caps,
+            out,
+        } => do_convert(path, to, arch, caps, out)?,
+        ProfileSubCommand::Port {
+            path,
+            to,
+            out,
+            json,
+        } => do_port(path, to, out, json)?,
+        ProfileSubCommand::Check {
+            path,
+            trace,
+            json,
+            fail_on_denied,
+        } => do_check(path, trace, json, fail_on_denied)?,
+        ProfileSubCommand::Compile { path, out } => do_compile(path, out)?,
+        ProfileSubCommand::Disasm { path } => do_disasm(path)?,
+        ProfileSubCommand::Kubernetes {
+            path,
+            name,
+            namespace,
+            crd,
+            localhost_dir,
+        } => do_kubernetes(path, name, namespace, crd, localhost_dir)?,
+        ProfileSubCommand::Systemd { path, groups, out } => do_systemd(path, groups, out)?,
+        ProfileSubCommand::Reference { reference, out } => do_reference(reference, out)?,
+    };
+
+    Ok(())
+}
@@ -0,0 +1,273 @@
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use oci_spec::runtime::{Arch, LinuxSeccomp, LinuxSyscall, LinuxSyscallBuilder};
+use serde::Serialize;
+
+use crate::cbpf::{self, ArchTable};
+use crate::profile_util;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TargetArch(pub Arch);
+
+impl FromStr for TargetArch {
+    type Err = String;
+
+    #[rustfmt::skip]
+    fn from_str(arch: &str) -> Result<TargetArch, Self::Err> {
+        match arch.to_lowercase().as_str() {
+            "x86_64" | "amd64"  => Ok(TargetArch(Arch::ScmpArchX86_64)),
+            "aarch64" | "arm64" => Ok(TargetArch(Arch::ScmpArchAarch64)),
+            _                   => Err(format!("unsupported architecture: {}", arch)),
+        }
+    }
+}
+
+/// Legacy syscalls of x86_64 and what libc calls instead on architectures without them
+#[rustfmt::skip]
+const EQUIVALENTS: &[(&str, &[&str])] = &[
+    ("access",       &["faccessat"]),
+    ("alarm",        &["setitimer"]),
+    ("chmod",        &["fchmodat"]),
+    ("chown",        &["fchownat"]),
+    ("creat",        &["openat"]),
+    ("dup2",         &["dup3"]),
+    ("epoll_create", &["epoll_create1"]),
+    ("epoll_wait",   &["epoll_pwait"]),
+    ("eventfd",      &["eventfd2"]),
+    ("fork",         &["clone"]),
+    ("futimesat",    &["utimensat"]),
+    ("getdents",     &["getdents64"]),
+    ("getpgrp",      &["getpgid"]),
+    ("inotify_init", &["inotify_init1"]),
+    ("lchown",       &["fchownat"]),
+    ("link",         &["linkat"]),
+    ("lstat",        &["newfstatat"]),
+    ("mkdir",        &["mkdirat"]),
+    ("mknod",        &["mknodat"]),
+    ("open",         &["openat"]),
+    ("pause",        &["ppoll"]),
+    ("pipe",         &["pipe2"]),
+    ("poll",         &["ppoll"]),
+    ("readlink",     &["readlinkat"]),
+    ("rename",       &["renameat"]),
+    ("rmdir",        &["unlinkat"]),
+    ("select",       &["pselect6"]),
+    ("signalfd",     &["signalfd4"]),
+    ("stat",         &["newfstatat"]),
+    ("symlink",      &["symlinkat"]),
+    ("time",         &["clock_gettime"]),
+    ("unlink",       &["unlinkat"]),
+    ("utime",        &["utimensat"]),
+    ("utimes",       &["utimensat"]),
+    ("vfork",        &["clone"]),
+];
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Renamed {
+    pub syscall: String,
+    pub to: Vec<String>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Unmappable {
+    pub syscall: String,
+    pub reason: String,
+}
+
+impl fmt::Display for Unmappable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.syscall, self.reason)
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct PortResult {
+    #[serde(skip)]
+    pub profile: LinuxSeccomp,
+    pub renamed: Vec<Renamed>,
+    /// Dropped from the profile, calls of them are handled by the default action
+    pub unmappable: Vec<Unmappable>,
+}
+
+fn has_syscall(table: &ArchTable, name: &str) -> bool {
+    table.syscalls.values().any(|known| *known == name)
+}
+
+fn port_rule(
+    syscall: &LinuxSyscall,
+    table: &ArchTable,
+    renamed: &mut Vec<Renamed>,
+    unmappable: &mut Vec<Unmappable>,
+) -> Result<Option<LinuxSyscall>> {
+    let mut names: Vec<String> = Vec::new();
+    for name in syscall.names() {
+        if has_syscall(table, name) {
+            names.push(name.clone());
+            continue;
+        }
+
+        let equivalents: Vec<String> = EQUIVALENTS
+            .iter()
+            .find(|(legacy, _)| legacy == name)
+            .map(|(_, equivalents)| equivalents.iter())
+            .into_iter()
+            .flatten()
+            .filter(|equivalent| has_syscall(table, equivalent))
+            .map(|equivalent| equivalent.to_string())
+            .collect();
+
+        let reason = if name.parse::<u32>().is_ok() {
+            Some("syscall numbers differ between architectures")
+        } else if equivalents.is_empty() {
+            Some("no equivalent syscall")
+        } else if syscall.args().is_some() {
+            // e.g. the flags of open(2) are the third argument of openat(2)
+            Some("argument rules can't be ported to the equivalent syscall")
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            unmappable.push(Unmappable {
+                syscall: name.clone(),
+                reason: format!("{} on {}", reason, table.name),
+            });
+            continue;
+        }
+
+        renamed.push(Renamed {
+            syscall: name.clone(),
+            to: equivalents.clone(),
+        });
+        names.extend(equivalents);
+    }
+
+    if names.is_empty() {
+        return Ok(None);
+    }
+    names.sort();
+    names.dedup();
+
+    let mut builder = LinuxSyscallBuilder::default()
+        .names(names)
+        .action(syscall.action());
+    if let Some(errno_ret) = syscall.errno_ret() {
+        builder = builder.errno_ret(errno_ret);
+    }
+    if let Some(args) = syscall.args() {
+        builder = builder.args(args.clone());
+    }
+    Ok(Some(builder.build()?))
+}
+
+/// Rewrites syscall names the target architecture doesn't have to their
+/// equivalents and restricts the profile to the target architecture
+pub fn port(profile: &LinuxSeccomp, target: &Arch) -> Result<PortResult> {
+    let table =
+        cbpf::arch_table(target).with_context(|| format!("{:?} is not supported", target))?;
+
+    let mut renamed = Vec::new();
+    let mut unmappable = Vec::new();
+    let mut syscalls = Vec::new();
+    for syscall in profile.syscalls().iter().flatten() {
+        if let Some(syscall) = port_rule(syscall, &table, &mut renamed, &mut unmappable)? {
+            syscalls.push(syscall);
+        }
+    }
+
+    let ported = profile_util::header_builder(profile)
+        .architectures(vec![*target])
+        .syscalls(syscalls)
+        .build()?;
+
+    Ok(PortResult {
+        profile: profile_util::canonicalize(&ported)?,
+        renamed,
+        unmappable,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oci_spec::runtime::{
+        LinuxSeccompAction, LinuxSeccompArgBuilder, LinuxSeccompBuilder, LinuxSeccompOperator,
+    };
+
+    #[test]
+    fn port_to_aarch64() -> Result<()> {
+        let profile = LinuxSeccompBuilder::default()
+            .default_action(LinuxSeccompAction::ScmpActErrno)
+            .architectures(vec![Arch::ScmpArchX86_64, Arch::ScmpArchX86])
+            .syscalls(vec![
+                LinuxSyscallBuilder::default()
+                    .names(vec![
+                        "read".to_string(),
+                        "open".to_string(),
+                        "openat".to_string(),
+                        "stat".to_string(),
+                        "arch_prctl".to_string(),
+                        "439".to_string(),
+                    ])
+                    .action(LinuxSeccompAction::ScmpActAllow)
+                    .build()?,
+                LinuxSyscallBuilder::default()
+                    .names(vec!["chmod".to_string()])
+                    .action(LinuxSeccompAction::ScmpActAllow)
+                    .args(vec![LinuxSeccompArgBuilder::default()
+                        .index(1usize)
+                        .value(0o644u64)
+                        .op(LinuxSeccompOperator::ScmpCmpEq)
+                        .build()?])
+                    .build()?,
+            ])
+            .build()?;
+
+        let result = port(&profile, &Arch::ScmpArchAarch64)?;
+        assert_eq!(
+            result.profile.architectures(),
+            &Some(vec![Arch::ScmpArchAarch64])
+        );
+        assert_eq!(
+            result
+                .profile
+                .syscalls()
+                .as_ref()
+                .map(|syscalls| syscalls[0].names().clone()),
+            Some(vec![
+                "newfstatat".to_string(),
+                "openat".to_string(),
+                "read".to_string(),
+            ])
+        );
+        assert_eq!(result.profile.syscalls().as_ref().map(Vec::len), Some(1));
+        assert_eq!(
+            result.renamed,
+            vec![
+                Renamed {
+                    syscall: "open".to_string(),
+                    to: vec!["openat".to_string()],
+                },
+                Renamed {
+                    syscall: "stat".to_string(),
+                    to: vec!["newfstatat".to_string()],
+                },
+            ]
+        );
+        assert_eq!(
+            result
+                .unmappable
+                .iter()
+                .map(|unmappable| unmappable.syscall.as_str())
+                .collect::<Vec<_>>(),
+            vec!["arch_prctl", "439", "chmod"]
+        );
+
+        // Every syscall of aarch64 exists on x86_64
+        let back = port(&result.profile, &Arch::ScmpArchX86_64)?;
+        assert!(back.renamed.is_empty() && back.unmappable.is_empty());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,131 @@
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use oci_spec::runtime::LinuxSeccomp;
+use serde::Serialize;
+
+use crate::profile_util;
+
+/// Default seccomp profiles of container engines, resolved for x86_64, the default
+/// capabilities of the engine and a kernel newer than 4.8.
+/// podman.json is derived from containers/common pkg/seccomp/seccomp.json,
+/// moby.json from moby profiles/seccomp/default.json.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Reference {
+    Podman,
+    Moby,
+}
+
+impl FromStr for Reference {
+    type Err = String;
+
+    #[rustfmt::skip]
+    fn from_str(reference: &str) -> Result<Reference, Self::Err> {
+        match reference.to_lowercase().as_str() {
+            "podman" | "containers-common" => Ok(Reference::Podman),
+            "moby" | "docker"              => Ok(Reference::Moby),
+            _                              => Err(format!("unknown reference profile: {}", reference)),
+        }
+    }
+}
+
+impl fmt::Display for Reference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Reference::Podman => "podman",
+            Reference::Moby => "moby",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Reference {
+    fn data(&self) -> &'static str {
+        match self {
+            Reference::Podman => include_str!("../../profiles/podman.json"),
+            Reference::Moby => include_str!("../../profiles/moby.json"),
+        }
+    }
+
+    pub fn profile(&self) -> Result<LinuxSeccomp> {
+        serde_json::from_str(self.data())
+            .with_context(|| format!("Failed to parse the {} reference profile", self))
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Comparison {
+    pub reference: Reference,
+    /// The profile permits nothing which the reference doesn't
+    pub stricter: bool,
+    /// Syscalls the profile permits more than the reference does
+    pub additionally_allowed: Vec<String>,
+    /// Syscalls the reference permits more than the profile does
+    pub additionally_denied: Vec<String>,
+}
+
+pub fn compare(profile: &LinuxSeccomp, reference: Reference) -> Result<Comparison> {
+    let reference_profile = reference.profile()?;
+
+    let allowed = profile_util::diff_profiles(reference_profile.clone(), profile.clone())?;
+    let denied = profile_util::diff_profiles(profile.clone(), reference_profile)?;
+
+    Ok(Comparison {
+        reference,
+        stricter: !allowed.allows_more(),
+        additionally_allowed: allowed.newly_allowed,
+        additionally_denied: denied.newly_allowed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profile_util::SyscallAccess;
+    use crate::test_util::gen_seccomp_profile;
+
+    #[test]
+    fn load_reference_profiles() -> Result<()> {
+        for reference in [Reference::Podman, Reference::Moby] {
+            let profile = reference.profile()?;
+            assert_eq!(
+                profile_util::syscall_access(&profile, "read"),
+                SyscallAccess::Allowed
+            );
+            assert_eq!(
+                profile_util::syscall_access(&profile, "mount"),
+                SyscallAccess::Denied
+            );
+            assert_eq!(
+                profile_util::syscall_access(&profile, "clone"),
+                SyscallAccess::Conditional
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn compare_with_reference() -> Result<()> {
+        let profile = gen_seccomp_profile(vec!["read", "write", "exit_group"])?;
+        let comparison = compare(&profile, Reference::Moby)?;
+        assert!(comparison.stricter);
+        assert!(comparison.additionally_allowed.is_empty());
+        assert!(comparison
+            .additionally_denied
+            .contains(&"openat".to_string()));
+
+        let profile = gen_seccomp_profile(vec!["read", "mount", "clone"])?;
+        let comparison = compare(&profile, Reference::Podman)?;
+        assert!(!comparison.stricter);
+        assert_eq!(
+            comparison.additionally_allowed,
+            vec!["clone".to_string(), "mount".to_string()]
+        );
+
+        Ok(())
+    }
+}
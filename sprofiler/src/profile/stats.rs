@@ -0,0 +1,306 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use oci_spec::runtime::LinuxSeccomp;
+use serde::Serialize;
+
+use crate::moby;
+use crate::profile_util::{self, SyscallAccess};
+
+/// Syscalls allowed by a profile of the fleet
+#[derive(Debug, PartialEq)]
+pub struct FleetProfile {
+    /// Path relative to the fleet directory
+    pub name: String,
+    pub syscalls: BTreeSet<String>,
+}
+
+impl FleetProfile {
+    /// None for deny list profiles, which don't tell what the workload needs
+    pub fn new(name: String, profile: &LinuxSeccomp) -> Option<Self> {
+        if profile_util::is_allowing(profile.default_action()) {
+            return None;
+        }
+
+        let syscalls = profile
+            .syscalls()
+            .iter()
+            .flatten()
+            .flat_map(|syscall| syscall.names())
+            .filter(|name| profile_util::syscall_access(profile, name) != SyscallAccess::Denied)
+            .cloned()
+            .collect();
+        Some(FleetProfile { name, syscalls })
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Frequency {
+    pub syscall: String,
+    /// Number of profiles allowing the syscall
+    pub profiles: usize,
+    pub percent: f64,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Outlier {
+    pub profile: String,
+    /// Syscalls allowed by few other profiles
+    pub rare: Vec<String>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Cluster {
+    pub profiles: Vec<String>,
+    /// Syscalls every profile of the cluster allows, a candidate shared baseline
+    pub common: Vec<String>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct FleetStats {
+    pub profiles: usize,
+    /// Most common first
+    pub frequencies: Vec<Frequency>,
+    /// Most rare syscalls first
+    pub outliers: Vec<Outlier>,
+    /// Clusters of more than one profile, largest first
+    pub clusters: Vec<Cluster>,
+}
+
+/// Profile files (.json, .yaml, .yml) under the directory, without provenance sidecars.
+/// Symlinked directories aren't followed.
+pub fn profile_paths(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            paths.extend(profile_paths(&path)?);
+            continue;
+        }
+        if file_type.is_symlink() && path.is_dir() {
+            continue;
+        }
+
+        let is_profile = matches!(
+            path.extension().and_then(|extension| extension.to_str()),
+            Some("json") | Some("yaml") | Some("yml")
+        );
+        let is_sidecar = path
+            .file_name()
+            .map(|name| name.to_string_lossy().ends_with(".meta.json"))
+            .unwrap_or(false);
+        if is_profile && !is_sidecar {
+            paths.push(path);
+        }
+    }
+
+    paths.sort();
+    Ok(paths)
+}
+
+/// OCI and moby profiles have defaultAction, reports written next to them
+/// (e.g. .runtime.json, .seccompprofile.yaml) don't
+pub fn is_profile(value: &serde_json::Value) -> bool {
+    value.get("defaultAction").is_some() || moby::is_moby_profile(value)
+}
+
+/// Intersection over union of the allowed syscalls
+fn similarity(a: &BTreeSet<String>, b: &BTreeSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 1.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+fn find(parents: &mut [usize], i: usize) -> usize {
+    if parents[i] != i {
+        parents[i] = find(parents, parents[i]);
+    }
+    parents[i]
+}
+
+/// Single linkage clustering, profiles at least `threshold` similar to any member
+/// join the cluster
+fn cluster(profiles: &[FleetProfile], threshold: f64) -> Vec<Cluster> {
+    let mut parents: Vec<usize> = (0..profiles.len()).collect();
+    for i in 0..profiles.len() {
+        for j in i + 1..profiles.len() {
+            if similarity(&profiles[i].syscalls, &profiles[j].syscalls) >= threshold {
+                let (root_i, root_j) = (find(&mut parents, i), find(&mut parents, j));
+                parents[root_j] = root_i;
+            }
+        }
+    }
+
+    let mut members: BTreeMap<usize, Vec<&FleetProfile>> = BTreeMap::new();
+    for (i, profile) in profiles.iter().enumerate() {
+        let root = find(&mut parents, i);
+        members.entry(root).or_default().push(profile);
+    }
+
+    let mut clusters: Vec<Cluster> = members
+        .into_values()
+        .filter(|members| members.len() > 1)
+        .map(|members| {
+            let mut common = members[0].syscalls.clone();
+            for member in &members[1..] {
+                common.retain(|syscall| member.syscalls.contains(syscall));
+            }
+            Cluster {
+                profiles: members.iter().map(|member| member.name.clone()).collect(),
+                common: common.into_iter().collect(),
+            }
+        })
+        .collect();
+    clusters.sort_by_key(|cluster| Reverse(cluster.profiles.len()));
+
+    clusters
+}
+
+/// Syscalls allowed by at most `rare_percent` percent of the profiles are rare
+pub fn stats(profiles: &[FleetProfile], rare_percent: f64, similarity: f64) -> FleetStats {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for profile in profiles {
+        for syscall in &profile.syscalls {
+            *counts.entry(syscall).or_default() += 1;
+        }
+    }
+
+    let percent = |count: usize| count as f64 * 100.0 / profiles.len() as f64;
+    let mut frequencies: Vec<Frequency> = counts
+        .iter()
+        .map(|(syscall, count)| Frequency {
+            syscall: syscall.to_string(),
+            profiles: *count,
+            percent: percent(*count),
+        })
+        .collect();
+    frequencies.sort_by_key(|frequency| Reverse(frequency.profiles));
+
+    let mut outliers: Vec<Outlier> = profiles
+        .iter()
+        .map(|profile| Outlier {
+            profile: profile.name.clone(),
+            rare: profile
+                .syscalls
+                .iter()
+                .filter(|syscall| percent(counts[syscall.as_str()]) <= rare_percent)
+                .cloned()
+                .collect(),
+        })
+        .filter(|outlier| !outlier.rare.is_empty())
+        .collect();
+    outliers.sort_by_key(|outlier| Reverse(outlier.rare.len()));
+
+    FleetStats {
+        profiles: profiles.len(),
+        frequencies,
+        outliers,
+        clusters: cluster(profiles, similarity),
+    }
+}
+
+/// CSV with a row per profile and a column per syscall, 1 if the profile allows it
+pub fn matrix_csv(profiles: &[FleetProfile]) -> String {
+    let syscalls: BTreeSet<&String> = profiles
+        .iter()
+        .flat_map(|profile| profile.syscalls.iter())
+        .collect();
+
+    let mut csv = String::from("profile");
+    for syscall in &syscalls {
+        csv.push(',');
+        csv.push_str(syscall);
+    }
+    csv.push('\n');
+
+    for profile in profiles {
+        csv.push_str(&profile.name);
+        for syscall in &syscalls {
+            csv.push_str(if profile.syscalls.contains(*syscall) {
+                ",1"
+            } else {
+                ",0"
+            });
+        }
+        csv.push('\n');
+    }
+
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fleet_profile(name: &str, syscalls: &[&str]) -> FleetProfile {
+        FleetProfile {
+            name: name.to_string(),
+            syscalls: syscalls.iter().map(|syscall| syscall.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn fleet_stats() {
+        let profiles = vec![
+            fleet_profile("nginx", &["read", "write", "accept4", "epoll_wait"]),
+            fleet_profile("envoy", &["read", "write", "accept4", "epoll_wait", "mmap"]),
+            fleet_profile("batch", &["read", "write", "mmap"]),
+            fleet_profile("debug", &["read", "write", "mmap", "ptrace"]),
+        ];
+
+        let stats = stats(&profiles, 25.0, 0.75);
+        assert_eq!(stats.profiles, 4);
+        assert_eq!(
+            stats
+                .frequencies
+                .iter()
+                .map(|frequency| (frequency.syscall.as_str(), frequency.profiles))
+                .collect::<Vec<_>>(),
+            vec![
+                ("read", 4),
+                ("write", 4),
+                ("mmap", 3),
+                ("accept4", 2),
+                ("epoll_wait", 2),
+                ("ptrace", 1),
+            ]
+        );
+        assert_eq!(
+            stats.outliers,
+            vec![Outlier {
+                profile: "debug".to_string(),
+                rare: vec!["ptrace".to_string()],
+            }]
+        );
+        assert_eq!(
+            stats.clusters,
+            vec![
+                Cluster {
+                    profiles: vec!["nginx".to_string(), "envoy".to_string()],
+                    common: vec![
+                        "accept4".to_string(),
+                        "epoll_wait".to_string(),
+                        "read".to_string(),
+                        "write".to_string(),
+                    ],
+                },
+                Cluster {
+                    profiles: vec!["batch".to_string(), "debug".to_string()],
+                    common: vec!["mmap".to_string(), "read".to_string(), "write".to_string()],
+                },
+            ]
+        );
+
+        assert_eq!(
+            matrix_csv(&profiles[2..]),
+            "profile,mmap,ptrace,read,write\nbatch,1,0,1,1\ndebug,1,1,1,1\n"
+        );
+    }
+}
@@ -0,0 +1,328 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use oci_spec::runtime::{Arch, LinuxSeccomp, LinuxSeccompAction, LinuxSeccompOperator};
+use serde::Serialize;
+
+use crate::cbpf::{self, ArchTable};
+
+/// Architectures sprofiler has syscall tables for
+pub(crate) const ARCHITECTURES: [Arch; 2] = [Arch::ScmpArchX86_64, Arch::ScmpArchAarch64];
+/// Errno values are 1..=4095, larger return values are not errors for libc
+const MAX_ERRNO: u32 = 4095;
+const MAX_ARG_INDEX: usize = 5;
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Level::Warning => "warning",
+            Level::Error => "error",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Issue {
+    pub level: Level,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub syscall: Option<String>,
+    pub message: String,
+}
+
+impl Issue {
+    fn new(level: Level, syscall: Option<String>, message: String) -> Self {
+        Issue {
+            level,
+            syscall,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.syscall {
+            Some(syscall) => write!(f, "{}: {}: {}", self.level, syscall, self.message),
+            None => write!(f, "{}: {}", self.level, self.message),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Default, PartialEq)]
+pub struct Validation {
+    pub issues: Vec<Issue>,
+}
+
+impl Validation {
+    pub fn has_errors(&self) -> bool {
+        self.issues.iter().any(|issue| issue.level == Level::Error)
+    }
+}
+
+/// Optimal string alignment distance, a swap of adjacent characters counts as one edit
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    d[a.len()][b.len()]
+}
+
+fn suggest<'a>(name: &str, known: &BTreeSet<&'a str>) -> Option<&'a str> {
+    known
+        .iter()
+        .map(|candidate| (edit_distance(name, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE && *distance < name.len())
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+pub(crate) fn uses_errno(action: LinuxSeccompAction) -> bool {
+    matches!(
+        action,
+        LinuxSeccompAction::ScmpActErrno | LinuxSeccompAction::ScmpActTrace
+    )
+}
+
+fn check_errno(
+    issues: &mut Vec<Issue>,
+    syscall: Option<String>,
+    action: LinuxSeccompAction,
+    errno_ret: Option<u32>,
+) {
+    match errno_ret {
+        Some(errno_ret) if !uses_errno(action) => issues.push(Issue::new(
+            Level::Warning,
+            syscall,
+            format!("errnoRet {} is ignored by {:?}", errno_ret, action),
+        )),
+        Some(errno_ret) if errno_ret > MAX_ERRNO => issues.push(Issue::new(
+            Level::Error,
+            syscall,
+            format!("errnoRet {} is not an errno", errno_ret),
+        )),
+        _ => {}
+    }
+}
+
+/// Checks syscall names against the syscall tables of the architectures of the
+/// profile, and errno values and argument rules which runtimes reject or ignore.
+pub fn validate(profile: &LinuxSeccomp) -> Validation {
+    let mut issues = Vec::new();
+
+    let tables: Vec<ArchTable> = match profile.architectures() {
+        Some(architectures) if !architectures.is_empty() => {
+            architectures.iter().filter_map(cbpf::arch_table).collect()
+        }
+        _ => ARCHITECTURES.iter().filter_map(cbpf::arch_table).collect(),
+    };
+    let known: BTreeSet<&str> = ARCHITECTURES
+        .iter()
+        .filter_map(cbpf::arch_table)
+        .flat_map(|table| table.syscalls.values().copied())
+        .collect();
+
+    check_errno(
+        &mut issues,
+        None,
+        profile.default_action(),
+        profile.default_errno_ret(),
+    );
+
+    let notifies = profile.default_action() == LinuxSeccompAction::ScmpActNotify
+        || profile
+            .syscalls()
+            .iter()
+            .flatten()
+            .any(|syscall| syscall.action() == LinuxSeccompAction::ScmpActNotify);
+    if notifies && profile.listener_path().is_none() {
+        issues.push(Issue::new(
+            Level::Warning,
+            None,
+            "SCMP_ACT_NOTIFY needs a listenerPath, runtimes reject the profile".to_string(),
+        ));
+    }
+
+    let mut unavailable: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for (i, syscall) in profile.syscalls().iter().flatten().enumerate() {
+        let names = syscall.names().join(",");
+        if syscall.names().is_empty() {
+            issues.push(Issue::new(
+                Level::Warning,
+                None,
+                format!("rule {} has no syscall names", i),
+            ));
+        }
+
+        check_errno(
+            &mut issues,
+            Some(names.clone()),
+            syscall.action(),
+            syscall.errno_ret(),
+        );
+
+        for arg in syscall.args().iter().flatten() {
+            if arg.index() > MAX_ARG_INDEX {
+                issues.push(Issue::new(
+                    Level::Error,
+                    Some(names.clone()),
+                    format!("argument index {} is out of range 0..=5", arg.index()),
+                ));
+            }
+            match (arg.op(), arg.value_two()) {
+                (LinuxSeccompOperator::ScmpCmpMaskedEq, value_two) => {
+                    let value_two = value_two.unwrap_or(0);
+                    if value_two & !arg.value() != 0 {
+                        issues.push(Issue::new(
+                            Level::Error,
+                            Some(names.clone()),
+                            format!(
+                                "args[{}] & {:#x} == {:#x} never matches",
+                                arg.index(),
+                                arg.value(),
+                                value_two
+                            ),
+                        ));
+                    }
+                }
+                (op, Some(value_two)) if value_two != 0 => issues.push(Issue::new(
+                    Level::Warning,
+                    Some(names.clone()),
+                    format!("valueTwo {:#x} is ignored by {:?}", value_two, op),
+                )),
+                _ => {}
+            }
+        }
+
+        for name in syscall.names() {
+            if !known.contains(name.as_str()) {
+                let message = match suggest(name, &known) {
+                    Some(suggestion) => format!("unknown syscall, did you mean {}?", suggestion),
+                    None => "unknown syscall".to_string(),
+                };
+                issues.push(Issue::new(Level::Warning, Some(name.clone()), message));
+                continue;
+            }
+
+            for table in &tables {
+                if !table.syscalls.values().any(|known| known == name) {
+                    unavailable
+                        .entry(table.name)
+                        .or_default()
+                        .insert(known.get(name.as_str()).copied().unwrap_or_default());
+                }
+            }
+        }
+    }
+
+    for (arch, names) in unavailable {
+        issues.push(Issue::new(
+            Level::Warning,
+            None,
+            format!(
+                "{} syscalls are not available on {}: {}",
+                names.len(),
+                arch,
+                names.into_iter().collect::<Vec<_>>().join(", ")
+            ),
+        ));
+    }
+
+    Validation { issues }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use oci_spec::runtime::{LinuxSeccompArgBuilder, LinuxSeccompBuilder, LinuxSyscallBuilder};
+
+    #[test]
+    fn suggest_typos() {
+        let known: BTreeSet<&str> = ["openat", "open", "read", "readv"].into_iter().collect();
+        assert_eq!(suggest("opeant", &known), Some("openat"));
+        assert_eq!(suggest("raed", &known), Some("read"));
+        assert_eq!(suggest("mount", &known), None);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("opeant", "openat"), 1);
+    }
+
+    #[test]
+    fn validate_profile() -> Result<()> {
+        let profile = LinuxSeccompBuilder::default()
+            .default_action(LinuxSeccompAction::ScmpActErrno)
+            .architectures(vec![Arch::ScmpArchX86_64, Arch::ScmpArchAarch64])
+            .syscalls(vec![
+                LinuxSyscallBuilder::default()
+                    .names(vec![
+                        "read".to_string(),
+                        "open".to_string(),
+                        "opeant".to_string(),
+                    ])
+                    .action(LinuxSeccompAction::ScmpActAllow)
+                    .errno_ret(1u32)
+                    .build()?,
+                LinuxSyscallBuilder::default()
+                    .names(vec!["clone".to_string()])
+                    .action(LinuxSeccompAction::ScmpActAllow)
+                    .args(vec![
+                        LinuxSeccompArgBuilder::default()
+                            .index(6usize)
+                            .value(0u64)
+                            .op(LinuxSeccompOperator::ScmpCmpEq)
+                            .build()?,
+                        LinuxSeccompArgBuilder::default()
+                            .index(0usize)
+                            .value(0x0fu64)
+                            .value_two(0x10u64)
+                            .op(LinuxSeccompOperator::ScmpCmpMaskedEq)
+                            .build()?,
+                    ])
+                    .build()?,
+            ])
+            .build()?;
+
+        let validation = validate(&profile);
+        let issues: Vec<String> = validation.issues.iter().map(|i| i.to_string()).collect();
+        assert_eq!(
+            issues,
+            vec![
+                "warning: read,open,opeant: errnoRet 1 is ignored by ScmpActAllow",
+                "warning: opeant: unknown syscall, did you mean openat?",
+                "error: clone: argument index 6 is out of range 0..=5",
+                "error: clone: args[0] & 0xf == 0x10 never matches",
+                "warning: 1 syscalls are not available on aarch64: open",
+            ]
+        );
+        assert!(validation.has_errors());
+
+        Ok(())
+    }
+}
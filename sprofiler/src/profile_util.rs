@@ -1,11 +1,20 @@
-use std::collections::HashMap;
-use std::fs::File;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::fs::{self, File};
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 use anyhow::{bail, Result};
 use oci_spec::runtime::{
-    Arch, LinuxSeccomp, LinuxSeccompAction, LinuxSeccompBuilder, LinuxSyscallBuilder,
+    Arch, LinuxSeccomp, LinuxSeccompAction, LinuxSeccompArg, LinuxSeccompBuilder, LinuxSyscall,
+    LinuxSyscallBuilder,
 };
+use serde::Serialize;
+use structopt::StructOpt;
+
+use crate::moby::{self, MobyProfile, Resolution, Target};
+use crate::profile::form::{self, ProfileForm};
+use crate::profile::validate::{self, Issue, Level};
 
 #[derive(Debug, PartialEq)]
 pub enum DiffStatus {
@@ -44,81 +53,1288 @@ pub fn read_seccomp_profiles(paths: Vec<PathBuf>) -> Result<Vec<LinuxSeccomp>> {
     Ok(profiles)
 }
 
-pub fn read_seccomp_profile(path: &Path) -> Result<LinuxSeccomp> {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Encoding {
+    Json,
+    Yaml,
+}
+
+impl Encoding {
+    /// YAML for .yaml and .yml files, JSON otherwise
+    pub fn from_path(path: &Path) -> Encoding {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("yaml") | Some("yml") => Encoding::Yaml,
+            _ => Encoding::Json,
+        }
+    }
+
+    /// JSON is pretty printed with a trailing newline
+    pub fn encode<T: Serialize>(&self, value: &T) -> Result<String> {
+        match self {
+            Encoding::Json => Ok(format!("{}\n", serde_json::to_string_pretty(value)?)),
+            Encoding::Yaml => Ok(serde_yaml::to_string(value)?),
+        }
+    }
+}
+
+/// Read a profile file of either encoding
+pub fn read_profile_value(path: &Path) -> Result<serde_json::Value> {
     let file = File::open(path)?;
-    let profile: LinuxSeccomp = serde_json::from_reader(&file)?;
+    let value = match Encoding::from_path(path) {
+        Encoding::Json => serde_json::from_reader(&file)?,
+        Encoding::Yaml => serde_yaml::from_reader(&file)?,
+    };
+    Ok(value)
+}
+
+/// Top-level "annotations" object of a profile file
+pub fn profile_annotations(value: &serde_json::Value) -> Result<BTreeMap<String, String>> {
+    match value.get("annotations") {
+        Some(annotations) => Ok(serde_json::from_value(annotations.clone())?),
+        None => Ok(BTreeMap::new()),
+    }
+}
+
+/// Read an OCI profile, or a moby profile resolved for the host and the default capabilities
+pub fn read_seccomp_profile(path: &Path) -> Result<LinuxSeccomp> {
+    parse_seccomp_profile(read_profile_value(path)?)
+}
+
+/// Read an OCI profile, or a moby profile resolved for the target together with
+/// how it was resolved
+pub fn read_seccomp_profile_for(
+    path: &Path,
+    target: &Target,
+) -> Result<(LinuxSeccomp, Option<Resolution>)> {
+    parse_seccomp_profile_for(read_profile_value(path)?, target)
+}
+
+/// An OCI profile, or a moby profile resolved for the host and the default capabilities
+pub fn parse_seccomp_profile(value: serde_json::Value) -> Result<LinuxSeccomp> {
+    let (profile, _) = parse_seccomp_profile_for(value, &Target::default())?;
     Ok(profile)
 }
 
-pub fn merge(profiles: Vec<LinuxSeccomp>) -> Result<LinuxSeccomp> {
-    if profiles.is_empty() {
-        return Ok(LinuxSeccomp::default());
+/// An OCI profile, or a moby profile resolved for the target. The resolution is
+/// returned for moby profiles since their rules may not apply to the target.
+pub fn parse_seccomp_profile_for(
+    value: serde_json::Value,
+    target: &Target,
+) -> Result<(LinuxSeccomp, Option<Resolution>)> {
+    if moby::is_moby_profile(&value) {
+        let profile: MobyProfile = serde_json::from_value(value)?;
+        return Ok((profile.resolve(target)?, Some(profile.resolution(target))));
+    }
+
+    let profile: LinuxSeccomp = serde_json::from_value(value)?;
+    Ok((profile, None))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProfileFormat {
+    Oci,
+    Moby,
+}
+
+impl FromStr for ProfileFormat {
+    type Err = String;
+
+    #[rustfmt::skip]
+    fn from_str(format: &str) -> Result<ProfileFormat, Self::Err> {
+        match format.to_lowercase().as_str() {
+            "oci"             => Ok(ProfileFormat::Oci),
+            "moby" | "docker" => Ok(ProfileFormat::Moby),
+            _                 => Err(format!("unsupported profile format: {}", format)),
+        }
+    }
+}
+
+/// Action of syscalls a generated profile doesn't allow
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DefaultAction(pub LinuxSeccompAction);
+
+impl FromStr for DefaultAction {
+    type Err = String;
+
+    #[rustfmt::skip]
+    fn from_str(action: &str) -> Result<DefaultAction, Self::Err> {
+        let action = match action.to_lowercase().replace('_', "-").as_str() {
+            "errno"                => LinuxSeccompAction::ScmpActErrno,
+            "kill-process"         => LinuxSeccompAction::ScmpActKillProcess,
+            // SCMP_ACT_KILL kills the thread like SCMP_ACT_KILL_THREAD
+            "kill-thread" | "kill" => LinuxSeccompAction::ScmpActKill,
+            "trap"                 => LinuxSeccompAction::ScmpActTrap,
+            "log"                  => LinuxSeccompAction::ScmpActLog,
+            "notify"               => LinuxSeccompAction::ScmpActNotify,
+            _                      => return Err(format!("unsupported default action: {}", action)),
+        };
+        Ok(DefaultAction(action))
+    }
+}
+
+/// Seccomp filter flag of a generated profile, e.g. SECCOMP_FILTER_FLAG_LOG
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterFlag(pub String);
+
+impl FromStr for FilterFlag {
+    type Err = String;
+
+    #[rustfmt::skip]
+    fn from_str(flag: &str) -> Result<FilterFlag, Self::Err> {
+        let name = flag.to_lowercase().replace('_', "-");
+        let flag = match name.trim_start_matches("seccomp-filter-flag-") {
+            "log"                => "SECCOMP_FILTER_FLAG_LOG",
+            "spec-allow"         => "SECCOMP_FILTER_FLAG_SPEC_ALLOW",
+            "wait-killable-recv" => "SECCOMP_FILTER_FLAG_WAIT_KILLABLE_RECV",
+            _                    => return Err(format!("unsupported seccomp filter flag: {}", flag)),
+        };
+        Ok(FilterFlag(flag.to_string()))
+    }
+}
+
+/// Overrides of the default action, defaultErrnoRet and flags of generated profiles
+#[derive(Debug, Clone, Default, PartialEq, StructOpt)]
+pub struct ProfileDefaults {
+    /// Action of syscalls the profile doesn't allow (errno, kill-process, kill-thread, trap, log, notify)
+    #[structopt(long)]
+    pub default_action: Option<DefaultAction>,
+    /// errno returned by the default action, e.g. 38 (ENOSYS) to let callers fall back
+    #[structopt(long)]
+    pub default_errno: Option<u32>,
+    /// Seccomp filter flags (log, spec-allow, wait-killable-recv)
+    #[structopt(long = "seccomp-flag", number_of_values = 1)]
+    pub flags: Vec<FilterFlag>,
+    /// Profile form (allowlist, denylist, auto), auto picks the one with fewer syscall entries
+    #[structopt(long)]
+    pub form: Option<ProfileForm>,
+}
+
+impl ProfileDefaults {
+    pub fn is_empty(&self) -> bool {
+        self == &ProfileDefaults::default()
+    }
+
+    /// Overrides which have no effect on the profile
+    pub fn warnings(&self, profile: &LinuxSeccomp) -> Vec<String> {
+        let default_action = self
+            .default_action
+            .map(|action| action.0)
+            .unwrap_or_else(|| profile.default_action());
+        let mut warnings = vec![];
+        if self.default_errno.is_some() && !validate::uses_errno(default_action) {
+            warnings.push(format!(
+                "--default-errno is ignored by the default action {:?}",
+                default_action
+            ));
+        }
+        warnings
+    }
+
+    /// defaultErrnoRet of the profile is dropped when the default action no longer
+    /// returns an errno, flags replace those of the profile, and the form is applied last
+    /// so deny rules of a denylist get the default action.
+    /// The default action of a profile which allows by default isn't replaced with
+    /// a denying one, which would deny everything the profile doesn't list.
+    pub fn apply(&self, profile: &LinuxSeccomp) -> Result<LinuxSeccomp> {
+        if self.is_empty() {
+            return Ok(profile.clone());
+        }
+
+        let default_action = self
+            .default_action
+            .map(|action| action.0)
+            .unwrap_or_else(|| profile.default_action());
+        if is_allowing(profile.default_action()) && !is_allowing(default_action) {
+            bail!(
+                "the profile allows syscalls by default, {:?} would deny every syscall it doesn't list",
+                default_action
+            );
+        }
+        let mut builder = LinuxSeccompBuilder::default().default_action(default_action);
+
+        let default_errno_ret = if validate::uses_errno(default_action) {
+            self.default_errno.or_else(|| profile.default_errno_ret())
+        } else {
+            None
+        };
+        if let Some(errno_ret) = default_errno_ret {
+            builder = builder.default_errno_ret(errno_ret);
+        }
+
+        if !self.flags.is_empty() {
+            let mut flags = vec![];
+            for flag in &self.flags {
+                push_unique(&mut flags, flag.0.clone());
+            }
+            builder = builder.flags(flags);
+        } else if let Some(flags) = profile.flags() {
+            builder = builder.flags(flags.clone());
+        }
+
+        if let Some(archs) = profile.architectures() {
+            builder = builder.architectures(archs.clone());
+        }
+        if let Some(path) = profile.listener_path() {
+            builder = builder.listener_path(path.clone());
+        }
+        if let Some(metadata) = profile.listener_metadata() {
+            builder = builder.listener_metadata(metadata.clone());
+        }
+        if let Some(syscalls) = profile.syscalls() {
+            builder = builder.syscalls(syscalls.clone());
+        }
+
+        let profile = builder.build()?;
+        match self.form {
+            Some(form) => form::select(&profile, form),
+            None => Ok(profile),
+        }
+    }
+}
+
+/// Validate the profile and write it, profiles with errors are not written.
+/// The warnings of the written profile are returned.
+pub fn write_seccomp_profile(
+    path: &Path,
+    profile: &LinuxSeccomp,
+    format: ProfileFormat,
+) -> Result<Vec<Issue>> {
+    write_annotated_seccomp_profile(path, profile, format, &BTreeMap::new())
+}
+
+/// Profile with a top-level "annotations" object, which runtimes and engines ignore
+#[derive(Serialize)]
+struct AnnotatedProfile<'a, T> {
+    #[serde(flatten)]
+    profile: &'a T,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    annotations: &'a BTreeMap<String, String>,
+}
+
+pub fn write_annotated_seccomp_profile(
+    path: &Path,
+    profile: &LinuxSeccomp,
+    format: ProfileFormat,
+    annotations: &BTreeMap<String, String>,
+) -> Result<Vec<Issue>> {
+    let validation = validate::validate(profile);
+    if validation.has_errors() {
+        let errors: Vec<String> = validation
+            .issues
+            .iter()
+            .filter(|issue| issue.level == Level::Error)
+            .map(|issue| issue.to_string())
+            .collect();
+        bail!(
+            "{} is not written, the profile is invalid:\n{}",
+            path.display(),
+            errors.join("\n")
+        );
+    }
+
+    let profile = canonicalize(profile)?;
+    let encoding = Encoding::from_path(path);
+    let content = match format {
+        ProfileFormat::Oci => encoding.encode(&AnnotatedProfile {
+            profile: &profile,
+            annotations,
+        })?,
+        ProfileFormat::Moby => encoding.encode(&AnnotatedProfile {
+            profile: &MobyProfile::from_oci(&profile),
+            annotations,
+        })?,
+    };
+    fs::write(path, content)?;
+
+    Ok(validation.issues)
+}
+
+/// The canonical content of a profile file, moby profiles stay in the moby format
+pub fn format_profile(path: &Path) -> Result<String> {
+    let value = read_profile_value(path)?;
+    let annotations = profile_annotations(&value)?;
+    let encoding = Encoding::from_path(path);
+
+    if moby::is_moby_profile(&value) {
+        let mut profile: MobyProfile = serde_json::from_value(value)?;
+        profile.canonicalize();
+        return encoding.encode(&AnnotatedProfile {
+            profile: &profile,
+            annotations: &annotations,
+        });
+    }
+
+    let profile: LinuxSeccomp = serde_json::from_value(value)?;
+    encoding.encode(&AnnotatedProfile {
+        profile: &canonicalize(&profile)?,
+        annotations: &annotations,
+    })
+}
+
+/// Higher is more permissive
+pub fn action_permissiveness(action: LinuxSeccompAction) -> u8 {
+    match action {
+        LinuxSeccompAction::ScmpActKillProcess => 0,
+        LinuxSeccompAction::ScmpActKill => 1,
+        LinuxSeccompAction::ScmpActTrap => 2,
+        LinuxSeccompAction::ScmpActErrno => 3,
+        LinuxSeccompAction::ScmpActNotify => 4,
+        LinuxSeccompAction::ScmpActTrace => 5,
+        LinuxSeccompAction::ScmpActLog => 6,
+        LinuxSeccompAction::ScmpActAllow => 7,
     }
+}
 
-    let mut names: Vec<String> = profiles
+fn most_permissive(actions: &[LinuxSeccompAction]) -> Option<LinuxSeccompAction> {
+    actions
         .iter()
-        .filter_map(|profile| extract_syscall_names(profile.clone()).ok())
-        .flatten()
+        .copied()
+        .max_by_key(|action| action_permissiveness(*action))
+}
+
+fn push_unique<T: PartialEq>(values: &mut Vec<T>, value: T) {
+    if !values.contains(&value) {
+        values.push(value);
+    }
+}
+
+/// Information which can't be merged without a decision
+#[derive(Debug, PartialEq)]
+pub enum MergeConflict {
+    DefaultAction {
+        actions: Vec<LinuxSeccompAction>,
+        resolved: LinuxSeccompAction,
+    },
+    DefaultErrnoRet {
+        values: Vec<u32>,
+        resolved: u32,
+    },
+    Architectures {
+        architectures: Vec<Vec<Arch>>,
+        resolved: Vec<Arch>,
+    },
+    SyscallAction {
+        name: String,
+        actions: Vec<LinuxSeccompAction>,
+        resolved: LinuxSeccompAction,
+    },
+    ErrnoRet {
+        name: String,
+        values: Vec<u32>,
+        resolved: u32,
+    },
+    ListenerPath {
+        values: Vec<PathBuf>,
+    },
+    ListenerMetadata {
+        values: Vec<String>,
+    },
+}
+
+impl fmt::Display for MergeConflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MergeConflict::DefaultAction { actions, resolved } => write!(
+                f,
+                "default actions differ {:?}, use {:?}",
+                actions, resolved
+            ),
+            MergeConflict::DefaultErrnoRet { values, resolved } => write!(
+                f,
+                "default errno values differ {:?}, use {}",
+                values, resolved
+            ),
+            MergeConflict::Architectures {
+                architectures,
+                resolved,
+            } => write!(
+                f,
+                "architectures differ {:?}, use {:?}",
+                architectures, resolved
+            ),
+            MergeConflict::SyscallAction {
+                name,
+                actions,
+                resolved,
+            } => write!(
+                f,
+                "{}: actions differ {:?}, use {:?}",
+                name, actions, resolved
+            ),
+            MergeConflict::ErrnoRet {
+                name,
+                values,
+                resolved,
+            } => write!(
+                f,
+                "{}: errno values differ {:?}, use {}",
+                name, values, resolved
+            ),
+            MergeConflict::ListenerPath { values } => {
+                write!(f, "listener paths differ {:?}, use the first", values)
+            }
+            MergeConflict::ListenerMetadata { values } => {
+                write!(f, "listener metadata differ {:?}, use the first", values)
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct MergeResult {
+    pub profile: LinuxSeccomp,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+/// Unconditional rules first, then more permissive actions first
+type RuleKey = (bool, u8, Option<u32>, String);
+
+/// A rule for a single syscall
+#[derive(Clone, Debug)]
+struct SyscallRule {
+    action: LinuxSeccompAction,
+    errno_ret: Option<u32>,
+    args: Option<Vec<LinuxSeccompArg>>,
+}
+
+impl SyscallRule {
+    /// Rules with the same key can share a LinuxSyscall entry
+    fn key(&self) -> RuleKey {
+        (
+            self.args.is_some(),
+            u8::MAX - action_permissiveness(self.action),
+            self.errno_ret,
+            self.args_key(),
+        )
+    }
+
+    fn args_key(&self) -> String {
+        serde_json::to_string(&self.args).unwrap_or_default()
+    }
+
+    /// The rule only repeats the default action of the profile
+    fn is_default(&self, profile: &LinuxSeccomp) -> bool {
+        // runtimes return EPERM if errnoRet is not set
+        self.action == profile.default_action()
+            && self.errno_ret.unwrap_or(EPERM) == profile.default_errno_ret().unwrap_or(EPERM)
+    }
+
+    /// Every call matching `other` is at least as permitted by this rule
+    fn covers(&self, other: &SyscallRule) -> bool {
+        (self.args.is_none() || self.args_key() == other.args_key())
+            && action_permissiveness(self.action) >= action_permissiveness(other.action)
+    }
+
+    /// A rule matching calls which match both rules, with the less permissive action.
+    /// None if libseccomp can't express it because both rules compare the same argument.
+    fn intersect(&self, other: &SyscallRule) -> Option<SyscallRule> {
+        let mut args = self.args.clone().unwrap_or_default();
+        for arg in other.args.iter().flatten() {
+            match args.iter().find(|a| a.index() == arg.index()) {
+                Some(a) if a == arg => {}
+                Some(_) => return None,
+                None => args.push(*arg),
+            }
+        }
+
+        let rule = if action_permissiveness(other.action) < action_permissiveness(self.action) {
+            other
+        } else {
+            self
+        };
+
+        Some(SyscallRule {
+            action: rule.action,
+            errno_ret: rule.errno_ret,
+            args: if args.is_empty() { None } else { Some(args) },
+        })
+    }
+}
+
+pub const EPERM: u32 = 1;
+
+fn least_permissive(actions: &[LinuxSeccompAction]) -> Option<LinuxSeccompAction> {
+    actions
+        .iter()
+        .copied()
+        .min_by_key(|action| action_permissiveness(*action))
+}
+
+fn collect_rules(profile: &LinuxSeccomp) -> BTreeMap<String, Vec<SyscallRule>> {
+    let mut rules_by_name: BTreeMap<String, Vec<SyscallRule>> = BTreeMap::new();
+
+    for syscall in profile.syscalls().iter().flatten() {
+        for name in syscall.names() {
+            rules_by_name
+                .entry(name.clone())
+                .or_default()
+                .push(SyscallRule {
+                    action: syscall.action(),
+                    errno_ret: syscall.errno_ret(),
+                    args: syscall.args().clone(),
+                });
+        }
+    }
+
+    rules_by_name
+}
+
+/// Rules applied to the syscall, calls matching no unconditional rule get the default action
+fn effective_rules(profile: &LinuxSeccomp, rules: Option<&Vec<SyscallRule>>) -> Vec<SyscallRule> {
+    let mut rules = rules.cloned().unwrap_or_default();
+    if !rules.iter().any(|rule| rule.args.is_none()) {
+        rules.push(SyscallRule {
+            action: profile.default_action(),
+            errno_ret: profile.default_errno_ret(),
+            args: None,
+        });
+    }
+    rules
+}
+
+/// Group syscalls sharing action, errnoRet and args into one rule.
+/// The conditional rules of a syscall are checked in order and the first
+/// unconditional one applies to other calls, so each syscall keeps the order of its
+/// rules. Groups are sorted by rule key otherwise.
+fn build_syscalls(rules_by_name: BTreeMap<String, Vec<SyscallRule>>) -> Result<Vec<LinuxSyscall>> {
+    let mut queues: BTreeMap<String, VecDeque<SyscallRule>> = BTreeMap::new();
+    for (name, rules) in rules_by_name {
+        // a rule identical to an earlier one never applies
+        let mut unique: Vec<SyscallRule> = vec![];
+        for rule in rules {
+            if !unique.iter().any(|u| u.key() == rule.key()) {
+                unique.push(rule);
+            }
+        }
+        // conditional and unconditional rules don't depend on each other's order
+        unique.sort_by_key(|rule| rule.args.is_some());
+        queues.insert(name, unique.into());
+    }
+
+    let mut groups: Vec<(SyscallRule, Vec<String>)> = vec![];
+    while let Some(key) = queues
+        .values()
+        .filter_map(|queue| queue.front())
+        .map(|rule| rule.key())
+        .min()
+    {
+        let mut group: Option<(SyscallRule, Vec<String>)> = None;
+        for (name, queue) in queues.iter_mut() {
+            if queue.front().map(|rule| rule.key()).as_ref() != Some(&key) {
+                continue;
+            }
+            if let Some(rule) = queue.pop_front() {
+                group
+                    .get_or_insert_with(|| (rule, vec![]))
+                    .1
+                    .push(name.clone());
+            }
+        }
+        groups.extend(group);
+    }
+
+    let mut syscalls = vec![];
+    for (rule, names) in groups {
+        let mut builder = LinuxSyscallBuilder::default()
+            .names(names)
+            .action(rule.action);
+        if let Some(errno_ret) = rule.errno_ret {
+            builder = builder.errno_ret(errno_ret);
+        }
+        if let Some(args) = rule.args {
+            builder = builder.args(args);
+        }
+        syscalls.push(builder.build()?);
+    }
+
+    Ok(syscalls)
+}
+
+/// Builder with everything of the profile except syscalls, duplicates removed
+pub(crate) fn header_builder(profile: &LinuxSeccomp) -> LinuxSeccompBuilder {
+    let mut builder = LinuxSeccompBuilder::default().default_action(profile.default_action());
+    if let Some(errno_ret) = profile.default_errno_ret() {
+        builder = builder.default_errno_ret(errno_ret);
+    }
+    if let Some(archs) = profile.architectures() {
+        let mut architectures = vec![];
+        for arch in archs {
+            push_unique(&mut architectures, *arch);
+        }
+        builder = builder.architectures(architectures);
+    }
+    if let Some(profile_flags) = profile.flags() {
+        let mut flags = vec![];
+        for flag in profile_flags.iter().cloned() {
+            push_unique(&mut flags, flag);
+        }
+        builder = builder.flags(flags);
+    }
+    if let Some(path) = profile.listener_path() {
+        builder = builder.listener_path(path.clone());
+    }
+    if let Some(metadata) = profile.listener_metadata() {
+        builder = builder.listener_metadata(metadata.clone());
+    }
+    builder
+}
+
+fn resolve_default_errno_ret(
+    profiles: &[LinuxSeccomp],
+    conflicts: &mut Vec<MergeConflict>,
+) -> Option<u32> {
+    let mut values = vec![];
+    for profile in profiles {
+        if let Some(errno_ret) = profile.default_errno_ret() {
+            push_unique(&mut values, errno_ret);
+        }
+    }
+
+    let resolved = values.first().copied()?;
+    if values.len() > 1 {
+        conflicts.push(MergeConflict::DefaultErrnoRet { values, resolved });
+    }
+    Some(resolved)
+}
+
+/// Listener settings are taken from the first profile which has them
+fn resolve_listener(
+    profiles: &[LinuxSeccomp],
+    mut builder: LinuxSeccompBuilder,
+    conflicts: &mut Vec<MergeConflict>,
+) -> LinuxSeccompBuilder {
+    let mut listener_paths = vec![];
+    let mut listener_metadata = vec![];
+    for profile in profiles {
+        if let Some(path) = profile.listener_path() {
+            push_unique(&mut listener_paths, path.clone());
+        }
+        if let Some(metadata) = profile.listener_metadata() {
+            push_unique(&mut listener_metadata, metadata.clone());
+        }
+    }
+
+    if listener_paths.len() > 1 {
+        conflicts.push(MergeConflict::ListenerPath {
+            values: listener_paths.clone(),
+        });
+    }
+    if listener_metadata.len() > 1 {
+        conflicts.push(MergeConflict::ListenerMetadata {
+            values: listener_metadata.clone(),
+        });
+    }
+
+    if let Some(path) = listener_paths.into_iter().next() {
+        builder = builder.listener_path(path);
+    }
+    if let Some(metadata) = listener_metadata.into_iter().next() {
+        builder = builder.listener_metadata(metadata);
+    }
+    builder
+}
+
+fn merge_syscall_rules(
+    name: &str,
+    rules: Vec<SyscallRule>,
+    conflicts: &mut Vec<MergeConflict>,
+) -> Vec<SyscallRule> {
+    let mut actions = vec![];
+    for rule in &rules {
+        push_unique(&mut actions, rule.action);
+    }
+
+    // the union of profiles allows what any profile allows
+    let action = match most_permissive(&actions) {
+        Some(action) => action,
+        None => return vec![],
+    };
+    if actions.len() > 1 {
+        conflicts.push(MergeConflict::SyscallAction {
+            name: name.to_string(),
+            actions,
+            resolved: action,
+        });
+    }
+
+    let mut rules: Vec<SyscallRule> = rules
+        .into_iter()
+        .filter(|rule| rule.action == action)
         .collect();
 
+    // an unconditional rule covers every argument condition
+    if rules.iter().any(|rule| rule.args.is_none()) {
+        rules.retain(|rule| rule.args.is_none());
+    }
+
+    let mut merged: Vec<SyscallRule> = vec![];
+    for rule in rules {
+        let same_args = merged.iter().position(|m| m.args_key() == rule.args_key());
+
+        match same_args {
+            Some(i) if merged[i].errno_ret != rule.errno_ret => {
+                let mut values = vec![];
+                values.extend(merged[i].errno_ret);
+                values.extend(rule.errno_ret);
+                if let Some(&resolved) = values.first() {
+                    conflicts.push(MergeConflict::ErrnoRet {
+                        name: name.to_string(),
+                        values,
+                        resolved,
+                    });
+                }
+                merged[i].errno_ret = merged[i].errno_ret.or(rule.errno_ret);
+            }
+            Some(_) => {}
+            None => merged.push(rule),
+        }
+    }
+
+    merged
+}
+
+/// Merge profiles into a profile which allows everything any of them allows.
+/// Actions, args, errnoRet, architectures and flags are preserved,
+/// information which can't be merged is resolved and reported as conflicts.
+pub fn merge_profiles(profiles: Vec<LinuxSeccomp>) -> Result<MergeResult> {
+    let mut conflicts = vec![];
+
+    if profiles.is_empty() {
+        return Ok(MergeResult {
+            profile: LinuxSeccomp::default(),
+            conflicts,
+        });
+    }
+
+    let mut default_actions = vec![];
+    let mut architecture_sets: Vec<Vec<Arch>> = vec![];
+    let mut architectures = vec![];
+    let mut flags = vec![];
+
+    for profile in &profiles {
+        push_unique(&mut default_actions, profile.default_action());
+        if let Some(archs) = profile.architectures() {
+            push_unique(&mut architecture_sets, archs.clone());
+            for arch in archs {
+                push_unique(&mut architectures, *arch);
+            }
+        }
+        if let Some(profile_flags) = profile.flags() {
+            for flag in profile_flags.iter().cloned() {
+                push_unique(&mut flags, flag);
+            }
+        }
+    }
+
+    let default_action = most_permissive(&default_actions).unwrap_or_default();
+    if default_actions.len() > 1 {
+        conflicts.push(MergeConflict::DefaultAction {
+            actions: default_actions,
+            resolved: default_action,
+        });
+    }
+
+    let default_errno_ret = resolve_default_errno_ret(&profiles, &mut conflicts);
+
+    if architecture_sets.len() > 1 {
+        conflicts.push(MergeConflict::Architectures {
+            architectures: architecture_sets,
+            resolved: architectures.clone(),
+        });
+    }
+
+    let mut builder = LinuxSeccompBuilder::default().default_action(default_action);
+    builder = resolve_listener(&profiles, builder, &mut conflicts);
+    if let Some(errno_ret) = default_errno_ret {
+        builder = builder.default_errno_ret(errno_ret);
+    }
+    if !architectures.is_empty() {
+        builder = builder.architectures(architectures);
+    }
+    if !flags.is_empty() {
+        builder = builder.flags(flags);
+    }
+
+    let header = builder.build()?;
+    let rules: Vec<BTreeMap<String, Vec<SyscallRule>>> =
+        profiles.iter().map(collect_rules).collect();
+
+    let mut names: Vec<&String> = rules.iter().flat_map(|r| r.keys()).collect();
     names.sort();
     names.dedup();
 
-    let arch: Vec<Arch> = vec![Arch::ScmpArchX86, Arch::ScmpArchX86_64];
+    let mut rules_by_name = BTreeMap::new();
+    for name in names {
+        let explicit: Vec<SyscallRule> = rules
+            .iter()
+            .filter_map(|profile_rules| profile_rules.get(name))
+            .flatten()
+            .cloned()
+            .collect();
+
+        // profiles without an unconditional rule apply their default action to the
+        // syscall, which overrides less permissive rules of the other profiles
+        let mut candidates = explicit.clone();
+        for (profile, profile_rules) in profiles.iter().zip(&rules) {
+            let profile_rules = profile_rules.get(name);
+            if profile_rules
+                .into_iter()
+                .flatten()
+                .any(|rule| rule.args.is_none())
+            {
+                continue;
+            }
+            let default = effective_rules(profile, profile_rules)
+                .into_iter()
+                .filter(|rule| rule.args.is_none());
+            for rule in default {
+                if explicit
+                    .iter()
+                    .any(|e| action_permissiveness(rule.action) > action_permissiveness(e.action))
+                {
+                    candidates.push(rule);
+                }
+            }
+        }
+
+        let merged: Vec<SyscallRule> = merge_syscall_rules(name, candidates, &mut conflicts)
+            .into_iter()
+            .filter(|rule| {
+                !rule.is_default(&header) || explicit.iter().any(|e| e.key() == rule.key())
+            })
+            .collect();
+        if !merged.is_empty() {
+            rules_by_name.insert(name.clone(), merged);
+        }
+    }
+
+    let mut builder = header_builder(&header);
+    let syscalls = build_syscalls(rules_by_name)?;
+    if !syscalls.is_empty() {
+        builder = builder.syscalls(syscalls);
+    }
+
+    Ok(MergeResult {
+        profile: builder.build()?,
+        conflicts,
+    })
+}
+
+pub fn merge(profiles: Vec<LinuxSeccomp>) -> Result<LinuxSeccomp> {
+    Ok(merge_profiles(profiles)?.profile)
+}
+
+/// Intersect profiles into a profile which allows only what all of them allow.
+/// Each call gets the least permissive action any of the profiles applies to it.
+pub fn intersect_profiles(profiles: Vec<LinuxSeccomp>) -> Result<MergeResult> {
+    let mut conflicts = vec![];
+
+    if profiles.is_empty() {
+        return Ok(MergeResult {
+            profile: LinuxSeccomp::default(),
+            conflicts,
+        });
+    }
+
+    let mut default_actions = vec![];
+    let mut architecture_sets: Vec<Vec<Arch>> = vec![];
+    for profile in &profiles {
+        push_unique(&mut default_actions, profile.default_action());
+        if let Some(archs) = profile.architectures() {
+            push_unique(&mut architecture_sets, archs.clone());
+        }
+    }
+
+    let default_action = least_permissive(&default_actions).unwrap_or_default();
+    if default_actions.len() > 1 {
+        conflicts.push(MergeConflict::DefaultAction {
+            actions: default_actions,
+            resolved: default_action,
+        });
+    }
+
+    let mut architectures = vec![];
+    for arch in architecture_sets.iter().flatten() {
+        if architecture_sets.iter().all(|archs| archs.contains(arch)) {
+            push_unique(&mut architectures, *arch);
+        }
+    }
+    if architecture_sets.len() > 1 {
+        conflicts.push(MergeConflict::Architectures {
+            architectures: architecture_sets,
+            resolved: architectures.clone(),
+        });
+    }
+
+    let mut flags = vec![];
+    for flag in profiles.iter().filter_map(|p| p.flags().clone()).flatten() {
+        if profiles
+            .iter()
+            .all(|p| p.flags().iter().flatten().any(|f| *f == flag))
+        {
+            push_unique(&mut flags, flag);
+        }
+    }
+
+    let mut builder = LinuxSeccompBuilder::default().default_action(default_action);
+    if let Some(errno_ret) = resolve_default_errno_ret(&profiles, &mut conflicts) {
+        builder = builder.default_errno_ret(errno_ret);
+    }
+    builder = resolve_listener(&profiles, builder, &mut conflicts);
+    if !architectures.is_empty() {
+        builder = builder.architectures(architectures);
+    }
+    if !flags.is_empty() {
+        builder = builder.flags(flags);
+    }
+
+    let header = builder.build()?;
+    let rules: Vec<BTreeMap<String, Vec<SyscallRule>>> =
+        profiles.iter().map(collect_rules).collect();
+
+    let mut names: Vec<&String> = rules.iter().flat_map(|r| r.keys()).collect();
+    names.sort();
+    names.dedup();
+
+    let mut rules_by_name = BTreeMap::new();
+    for name in names {
+        // a call is allowed if it matches an allowing rule in every profile,
+        // so combine the effective rules of the profiles one by one
+        let mut combined = effective_rules(&profiles[0], rules[0].get(name));
+        for (profile, profile_rules) in profiles.iter().zip(&rules).skip(1) {
+            let effective = effective_rules(profile, profile_rules.get(name));
+            combined = combined
+                .iter()
+                .flat_map(|a| effective.iter().filter_map(move |b| a.intersect(b)))
+                .collect();
+        }
+
+        let mut intersected: Vec<SyscallRule> = vec![];
+        for rule in combined {
+            if !rule.is_default(&header) && !intersected.iter().any(|r| r.key() == rule.key()) {
+                intersected.push(rule);
+            }
+        }
+        if !intersected.is_empty() {
+            rules_by_name.insert(name.clone(), intersected);
+        }
+    }
+
+    let mut builder = header_builder(&header);
+    let syscalls = build_syscalls(rules_by_name)?;
+    if !syscalls.is_empty() {
+        builder = builder.syscalls(syscalls);
+    }
+
+    Ok(MergeResult {
+        profile: builder.build()?,
+        conflicts,
+    })
+}
+
+pub fn intersect(profiles: Vec<LinuxSeccomp>) -> Result<LinuxSeccomp> {
+    Ok(intersect_profiles(profiles)?.profile)
+}
+
+/// Rules of the profile which allow more than the baseline does.
+/// The default action, architectures and flags of the profile are kept.
+pub fn subtract(profile: LinuxSeccomp, baseline: LinuxSeccomp) -> Result<LinuxSeccomp> {
+    let baseline_rules = collect_rules(&baseline);
+
+    let mut rules_by_name = BTreeMap::new();
+    for (name, rules) in collect_rules(&profile) {
+        let covering = effective_rules(&baseline, baseline_rules.get(&name));
+        let rules: Vec<SyscallRule> = rules
+            .into_iter()
+            .filter(|rule| !covering.iter().any(|b| b.covers(rule)))
+            .collect();
+        if !rules.is_empty() {
+            rules_by_name.insert(name, rules);
+        }
+    }
+
+    let mut builder = header_builder(&profile);
+    let syscalls = build_syscalls(rules_by_name)?;
+    if !syscalls.is_empty() {
+        builder = builder.syscalls(syscalls);
+    }
+
+    Ok(builder.build()?)
+}
+
+fn sort_by_name<T: Serialize>(values: &mut Vec<T>) {
+    values.sort_by_key(|value| serde_json::to_string(value).unwrap_or_default());
+    values.dedup_by_key(|value| serde_json::to_string(value).unwrap_or_default());
+}
+
+/// Deterministic layout of a profile for reviews and diffs: architectures and flags
+/// sorted, syscalls grouped by rule with sorted names and unconditional rules first.
+/// The rules of a syscall keep their order, so the profile is enforced the same way.
+/// Unlike normalize, only repeated rules of a syscall are dropped.
+pub fn canonicalize(profile: &LinuxSeccomp) -> Result<LinuxSeccomp> {
+    let mut builder = header_builder(profile);
+    if let Some(archs) = profile.architectures() {
+        let mut architectures = archs.clone();
+        sort_by_name(&mut architectures);
+        builder = builder.architectures(architectures);
+    }
+    if let Some(profile_flags) = profile.flags() {
+        let mut flags = profile_flags.clone();
+        sort_by_name(&mut flags);
+        builder = builder.flags(flags);
+    }
+
+    let syscalls = build_syscalls(collect_rules(profile))?;
+    if !syscalls.is_empty() {
+        builder = builder.syscalls(syscalls);
+    }
+
+    Ok(builder.build()?)
+}
+
+/// Sort syscalls, merge duplicated rules and drop rules which have no effect
+pub fn normalize(profile: LinuxSeccomp) -> Result<LinuxSeccomp> {
+    let mut rules_by_name = BTreeMap::new();
+    for (name, rules) in collect_rules(&profile) {
+        let unconditional: Vec<SyscallRule> = rules
+            .iter()
+            .filter(|rule| rule.args.is_none())
+            .cloned()
+            .collect();
+
+        let mut normalized: Vec<SyscallRule> = vec![];
+        for rule in rules {
+            let covered = rule.args.is_some()
+                && unconditional
+                    .iter()
+                    .any(|u| u.action == rule.action && u.errno_ret == rule.errno_ret);
+            if !covered
+                && !rule.is_default(&profile)
+                && !normalized.iter().any(|r| r.key() == rule.key())
+            {
+                normalized.push(rule);
+            }
+        }
+        if !normalized.is_empty() {
+            rules_by_name.insert(name, normalized);
+        }
+    }
+
+    let mut builder = header_builder(&profile);
+    let syscalls = build_syscalls(rules_by_name)?;
+    if !syscalls.is_empty() {
+        builder = builder.syscalls(syscalls);
+    }
+
+    Ok(builder.build()?)
+}
+
+pub fn diff(profile1: LinuxSeccomp, profile2: LinuxSeccomp) -> Result<HashMap<String, DiffStatus>> {
+    let syscalls_from_profile1 = extract_syscall_names(profile1)?;
+    let syscalls_from_profile2 = extract_syscall_names(profile2)?;
+
+    let mut diff_hash = HashMap::<String, DiffStatus>::new();
+
+    for syscall in syscalls_from_profile1 {
+        diff_hash.entry(syscall).or_insert(DiffStatus::OnlyPath1);
+    }
+
+    for syscall in syscalls_from_profile2 {
+        if diff_hash.get(&*syscall).is_some() {
+            diff_hash.insert(syscall.to_string(), DiffStatus::Both);
+        } else {
+            diff_hash.entry(syscall).or_insert(DiffStatus::OnlyPath2);
+        }
+    }
+
+    Ok(diff_hash)
+}
+
+/// How a profile treats calls of a syscall
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SyscallAccess {
+    Denied,
+    /// Allowed only for some argument values
+    Conditional,
+    Allowed,
+}
+
+/// The syscall is executed by the kernel
+pub fn is_allowing(action: LinuxSeccompAction) -> bool {
+    matches!(
+        action,
+        LinuxSeccompAction::ScmpActAllow | LinuxSeccompAction::ScmpActLog
+    )
+}
+
+pub fn syscall_access(profile: &LinuxSeccomp, name: &str) -> SyscallAccess {
+    let rules = collect_rules(profile);
+    let rules = effective_rules(profile, rules.get(name));
+
+    // Argument rules with the other verdict than the unconditional one, e.g. deny
+    // rules of a denylist, make the syscall conditional
+    let allowed = rules
+        .iter()
+        .any(|rule| rule.args.is_none() && is_allowing(rule.action));
+    if rules
+        .iter()
+        .any(|rule| rule.args.is_some() && is_allowing(rule.action) != allowed)
+    {
+        SyscallAccess::Conditional
+    } else if allowed {
+        SyscallAccess::Allowed
+    } else {
+        SyscallAccess::Denied
+    }
+}
+
+/// Rules of the profile which include the syscall
+pub fn rules_for<'a>(profile: &'a LinuxSeccomp, name: &str) -> Vec<&'a LinuxSyscall> {
+    profile
+        .syscalls()
+        .iter()
+        .flatten()
+        .filter(|syscall| syscall.names().iter().any(|n| n == name))
+        .collect()
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Change<T> {
+    pub before: T,
+    pub after: T,
+}
+
+impl<T: PartialEq> Change<T> {
+    fn new(before: T, after: T) -> Option<Self> {
+        if before == after {
+            None
+        } else {
+            Some(Change { before, after })
+        }
+    }
+}
+
+/// Rules of a syscall in both profiles
+#[derive(Serialize, Debug, PartialEq)]
+pub struct SyscallDiff {
+    pub name: String,
+    pub before: Vec<LinuxSyscall>,
+    pub after: Vec<LinuxSyscall>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileDiff {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_action: Option<Change<LinuxSeccompAction>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_errno_ret: Option<Change<Option<u32>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub architectures: Option<Change<Vec<Arch>>>,
+    /// Syscalls which have rules only in the second profile
+    pub added: Vec<SyscallDiff>,
+    /// Syscalls which have rules only in the first profile
+    pub removed: Vec<SyscallDiff>,
+    /// Syscalls whose rules differ
+    pub changed: Vec<SyscallDiff>,
+    /// Syscalls the second profile permits more than the first one does
+    pub newly_allowed: Vec<String>,
+}
 
-    let seccomp = LinuxSeccompBuilder::default()
-        .default_action(LinuxSeccompAction::ScmpActErrno)
-        .architectures(arch)
-        .syscalls(vec![LinuxSyscallBuilder::default()
-            .names(names)
-            .action(LinuxSeccompAction::ScmpActAllow)
-            .build()?])
-        .build()?;
+impl ProfileDiff {
+    pub fn is_empty(&self) -> bool {
+        self.default_action.is_none()
+            && self.default_errno_ret.is_none()
+            && self.architectures.is_none()
+            && self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+    }
 
-    Ok(seccomp)
+    /// The second profile permits something the first one doesn't
+    pub fn allows_more(&self) -> bool {
+        let default_allows_more = match &self.default_action {
+            Some(change) => {
+                action_permissiveness(change.after) > action_permissiveness(change.before)
+            }
+            None => false,
+        };
+        default_allows_more || !self.newly_allowed.is_empty()
+    }
 }
 
-pub fn diff(profile1: LinuxSeccomp, profile2: LinuxSeccomp) -> Result<HashMap<String, DiffStatus>> {
-    let syscalls_from_profile1 = extract_syscall_names(profile1)?;
-    let syscalls_from_profile2 = extract_syscall_names(profile2)?;
+fn syscall_rules(name: &str, rules: Option<&Vec<SyscallRule>>) -> Result<Vec<LinuxSyscall>> {
+    let mut rules_by_name = BTreeMap::new();
+    if let Some(rules) = rules {
+        rules_by_name.insert(name.to_string(), rules.clone());
+    }
+    build_syscalls(rules_by_name)
+}
 
-    let mut diff_hash = HashMap::<String, DiffStatus>::new();
+/// Compare default actions, architectures and the rules of every syscall.
+/// Profiles are normalized first, so only differences which change the behavior are reported.
+pub fn diff_profiles(profile1: LinuxSeccomp, profile2: LinuxSeccomp) -> Result<ProfileDiff> {
+    let profile1 = normalize(profile1)?;
+    let profile2 = normalize(profile2)?;
+    let rules1 = collect_rules(&profile1);
+    let rules2 = collect_rules(&profile2);
 
-    for syscall in syscalls_from_profile1 {
-        diff_hash.entry(syscall).or_insert(DiffStatus::OnlyPath1);
-    }
+    let mut names: Vec<&String> = rules1.keys().chain(rules2.keys()).collect();
+    names.sort();
+    names.dedup();
 
-    for syscall in syscalls_from_profile2 {
-        if diff_hash.get(&*syscall).is_some() {
-            diff_hash.insert(syscall.to_string(), DiffStatus::Both);
-        } else {
-            diff_hash.entry(syscall).or_insert(DiffStatus::OnlyPath2);
+    let mut added = vec![];
+    let mut removed = vec![];
+    let mut changed = vec![];
+    let mut newly_allowed = vec![];
+
+    for name in names {
+        let syscall_diff = SyscallDiff {
+            name: name.clone(),
+            before: syscall_rules(name, rules1.get(name))?,
+            after: syscall_rules(name, rules2.get(name))?,
+        };
+
+        if syscall_diff.before.is_empty() && !syscall_diff.after.is_empty() {
+            added.push(syscall_diff);
+        } else if !syscall_diff.before.is_empty() && syscall_diff.after.is_empty() {
+            removed.push(syscall_diff);
+        } else if syscall_diff.before != syscall_diff.after {
+            changed.push(syscall_diff);
+        }
+
+        let before = effective_rules(&profile1, rules1.get(name));
+        let after = effective_rules(&profile2, rules2.get(name));
+        if after
+            .iter()
+            .any(|rule| !before.iter().any(|b| b.covers(rule)))
+        {
+            newly_allowed.push(name.clone());
         }
     }
 
-    Ok(diff_hash)
+    Ok(ProfileDiff {
+        default_action: Change::new(profile1.default_action(), profile2.default_action()),
+        default_errno_ret: Change::new(profile1.default_errno_ret(), profile2.default_errno_ret()),
+        architectures: Change::new(
+            profile1.architectures().clone().unwrap_or_default(),
+            profile2.architectures().clone().unwrap_or_default(),
+        ),
+        added,
+        removed,
+        changed,
+        newly_allowed,
+    })
 }
 
 #[cfg(test)]
 mod tests {
 
     use super::*;
-
-    fn gen_seccomp_profile(allow_syscalls: Vec<&str>) -> Result<LinuxSeccomp> {
-        let mut names: Vec<String> = allow_syscalls.into_iter().map(String::from).collect();
-        names.sort();
-
-        let seccomp = LinuxSeccompBuilder::default()
-            .default_action(LinuxSeccompAction::ScmpActErrno)
-            .architectures(vec![Arch::ScmpArchX86, Arch::ScmpArchX86_64])
-            .syscalls(vec![LinuxSyscallBuilder::default()
-                .names(names)
-                .action(LinuxSeccompAction::ScmpActAllow)
-                .build()?])
-            .build()?;
-
-        Ok(seccomp)
-    }
+    use crate::cbpf;
+    use crate::test_util::gen_seccomp_profile;
+    use oci_spec::runtime::{LinuxSeccompArgBuilder, LinuxSeccompOperator};
 
     fn gen_duplicate_syscalls_profile(allow_syscalls: Vec<&str>) -> Result<LinuxSeccomp> {
         let mut names: Vec<String> = allow_syscalls.into_iter().map(String::from).collect();
@@ -239,6 +1455,379 @@ mod tests {
         Ok(())
     }
 
+    fn gen_arg(index: usize, value: u64) -> Result<LinuxSeccompArg> {
+        Ok(LinuxSeccompArgBuilder::default()
+            .index(index)
+            .value(value)
+            .op(LinuxSeccompOperator::ScmpCmpEq)
+            .build()?)
+    }
+
+    #[test]
+    fn merge_profile_keep_args_and_errno_ret() -> Result<()> {
+        let personality = LinuxSyscallBuilder::default()
+            .names(vec!["personality".to_string()])
+            .action(LinuxSeccompAction::ScmpActAllow)
+            .args(vec![gen_arg(0, 0)?])
+            .build()?;
+        let profile1 = LinuxSeccompBuilder::default()
+            .default_action(LinuxSeccompAction::ScmpActErrno)
+            .architectures(vec![Arch::ScmpArchX86_64])
+            .syscalls(vec![personality.clone()])
+            .build()?;
+
+        let mkdir = LinuxSyscallBuilder::default()
+            .names(vec!["mkdir".to_string()])
+            .action(LinuxSeccompAction::ScmpActErrno)
+            .errno_ret(1u32)
+            .build()?;
+        let profile2 = LinuxSeccompBuilder::default()
+            .default_action(LinuxSeccompAction::ScmpActErrno)
+            .architectures(vec![Arch::ScmpArchX86_64])
+            .syscalls(vec![mkdir.clone()])
+            .build()?;
+
+        let result = merge_profiles(vec![profile1, profile2])?;
+
+        assert!(result.conflicts.is_empty());
+        assert_eq!(result.profile.syscalls(), &Some(vec![mkdir, personality]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn merge_profile_unconditional_rule_covers_args() -> Result<()> {
+        let conditional = LinuxSyscallBuilder::default()
+            .names(vec!["personality".to_string()])
+            .action(LinuxSeccompAction::ScmpActAllow)
+            .args(vec![gen_arg(0, 8)?])
+            .build()?;
+        let profile1 = LinuxSeccompBuilder::default()
+            .default_action(LinuxSeccompAction::ScmpActErrno)
+            .syscalls(vec![conditional])
+            .build()?;
+        let profile2 = gen_seccomp_profile(vec!["personality"])?;
+
+        let result = merge_profiles(vec![profile1, profile2])?;
+        let expect = gen_seccomp_profile(vec!["personality"])?;
+
+        assert_eq!(result.profile.syscalls(), expect.syscalls());
+
+        Ok(())
+    }
+
+    #[test]
+    fn merge_profile_report_conflicts() -> Result<()> {
+        let deny_ptrace = LinuxSyscallBuilder::default()
+            .names(vec!["ptrace".to_string()])
+            .action(LinuxSeccompAction::ScmpActKillProcess)
+            .build()?;
+        let profile1 = LinuxSeccompBuilder::default()
+            .default_action(LinuxSeccompAction::ScmpActAllow)
+            .architectures(vec![Arch::ScmpArchAarch64])
+            .syscalls(vec![deny_ptrace])
+            .build()?;
+        let profile2 = gen_seccomp_profile(vec!["ptrace"])?;
+
+        let result = merge_profiles(vec![profile1, profile2])?;
+
+        assert_eq!(
+            result.profile.default_action(),
+            LinuxSeccompAction::ScmpActAllow
+        );
+        assert_eq!(
+            result.profile.architectures(),
+            &Some(vec![
+                Arch::ScmpArchAarch64,
+                Arch::ScmpArchX86,
+                Arch::ScmpArchX86_64
+            ])
+        );
+        assert_eq!(
+            result.profile.syscalls(),
+            gen_seccomp_profile(vec!["ptrace"])?.syscalls()
+        );
+
+        assert_eq!(result.conflicts.len(), 3);
+        assert_eq!(
+            result.conflicts[0],
+            MergeConflict::DefaultAction {
+                actions: vec![
+                    LinuxSeccompAction::ScmpActAllow,
+                    LinuxSeccompAction::ScmpActErrno
+                ],
+                resolved: LinuxSeccompAction::ScmpActAllow,
+            }
+        );
+        assert!(matches!(
+            result.conflicts[1],
+            MergeConflict::Architectures { .. }
+        ));
+        assert_eq!(
+            result.conflicts[2],
+            MergeConflict::SyscallAction {
+                name: "ptrace".to_string(),
+                actions: vec![
+                    LinuxSeccompAction::ScmpActKillProcess,
+                    LinuxSeccompAction::ScmpActAllow
+                ],
+                resolved: LinuxSeccompAction::ScmpActAllow,
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn merge_profile_union_of_denylists() -> Result<()> {
+        let denylist = |name: &str| -> Result<LinuxSeccomp> {
+            Ok(LinuxSeccompBuilder::default()
+                .default_action(LinuxSeccompAction::ScmpActAllow)
+                .architectures(vec![Arch::ScmpArchX86_64])
+                .syscalls(vec![LinuxSyscallBuilder::default()
+                    .names(vec![name.to_string(), "kexec_load".to_string()])
+                    .action(LinuxSeccompAction::ScmpActKillProcess)
+                    .build()?])
+                .build()?)
+        };
+
+        let result = merge_profiles(vec![denylist("ptrace")?, denylist("mount")?])?;
+
+        // each profile allows what the other kills
+        assert_eq!(
+            syscall_access(&result.profile, "ptrace"),
+            SyscallAccess::Allowed
+        );
+        assert_eq!(
+            syscall_access(&result.profile, "mount"),
+            SyscallAccess::Allowed
+        );
+        assert_eq!(
+            syscall_access(&result.profile, "kexec_load"),
+            SyscallAccess::Denied
+        );
+        assert_eq!(
+            result
+                .profile
+                .syscalls()
+                .as_ref()
+                .map(|syscalls| syscalls[0].names().clone()),
+            Some(vec!["kexec_load".to_string()])
+        );
+        assert_eq!(result.conflicts.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn normalize_duplicated_syscalls() -> Result<()> {
+        let profile = gen_duplicate_syscalls_profile(vec!["ptrace", "chroot"])?;
+
+        let act = normalize(profile)?;
+        let expect = gen_seccomp_profile(vec!["chroot", "ptrace"])?;
+
+        assert_eq!(act, expect);
+
+        Ok(())
+    }
+
+    #[test]
+    fn normalize_drop_rules_without_effect() -> Result<()> {
+        let conditional = LinuxSyscallBuilder::default()
+            .names(vec!["personality".to_string()])
+            .action(LinuxSeccompAction::ScmpActAllow)
+            .args(vec![gen_arg(0, 8)?])
+            .build()?;
+        let eperm = LinuxSyscallBuilder::default()
+            .names(vec!["mkdir".to_string()])
+            .action(LinuxSeccompAction::ScmpActErrno)
+            .errno_ret(1u32)
+            .build()?;
+        let mut syscalls = gen_seccomp_profile(vec!["personality"])?
+            .syscalls()
+            .clone()
+            .unwrap();
+        syscalls.push(conditional);
+        syscalls.push(eperm);
+
+        let profile = LinuxSeccompBuilder::default()
+            .default_action(LinuxSeccompAction::ScmpActErrno)
+            .architectures(vec![Arch::ScmpArchX86, Arch::ScmpArchX86_64])
+            .syscalls(syscalls)
+            .build()?;
+
+        let act = normalize(profile)?;
+
+        assert_eq!(act, gen_seccomp_profile(vec!["personality"])?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn intersect_profiles_allow_common_syscalls() -> Result<()> {
+        let profile1 = gen_seccomp_profile(vec!["chdir", "getpid", "mkdir"])?;
+        let profile2 = gen_seccomp_profile(vec!["getpid", "mkdir", "unshare"])?;
+
+        let result = intersect_profiles(vec![profile1, profile2])?;
+
+        assert!(result.conflicts.is_empty());
+        assert_eq!(
+            result.profile,
+            gen_seccomp_profile(vec!["getpid", "mkdir"])?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn intersect_profiles_combine_args() -> Result<()> {
+        let conditional = LinuxSyscallBuilder::default()
+            .names(vec!["personality".to_string()])
+            .action(LinuxSeccompAction::ScmpActAllow)
+            .args(vec![gen_arg(0, 8)?])
+            .build()?;
+        let profile1 = LinuxSeccompBuilder::default()
+            .default_action(LinuxSeccompAction::ScmpActErrno)
+            .architectures(vec![Arch::ScmpArchX86, Arch::ScmpArchX86_64])
+            .syscalls(vec![conditional.clone()])
+            .build()?;
+        let profile2 = gen_seccomp_profile(vec!["personality"])?;
+
+        let other_arg = LinuxSyscallBuilder::default()
+            .names(vec!["personality".to_string()])
+            .action(LinuxSeccompAction::ScmpActAllow)
+            .args(vec![gen_arg(0, 0)?])
+            .build()?;
+        let profile3 = LinuxSeccompBuilder::default()
+            .default_action(LinuxSeccompAction::ScmpActErrno)
+            .architectures(vec![Arch::ScmpArchX86_64])
+            .syscalls(vec![other_arg])
+            .build()?;
+
+        let result = intersect_profiles(vec![profile1.clone(), profile2.clone()])?;
+        assert_eq!(result.profile.syscalls(), &Some(vec![conditional]));
+
+        // personality(0) and personality(8) never match together
+        let result = intersect_profiles(vec![profile1, profile2, profile3])?;
+        assert!(result.profile.syscalls().is_none());
+        assert_eq!(
+            result.profile.default_action(),
+            LinuxSeccompAction::ScmpActErrno
+        );
+        assert_eq!(
+            result.profile.architectures(),
+            &Some(vec![Arch::ScmpArchX86_64])
+        );
+        assert_eq!(result.conflicts.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn subtract_baseline() -> Result<()> {
+        let profile = gen_seccomp_profile(vec!["getpid", "mkdir", "ptrace"])?;
+        let baseline = gen_seccomp_profile(vec!["getpid", "mkdir"])?;
+
+        let act = subtract(profile, baseline)?;
+
+        assert_eq!(act, gen_seccomp_profile(vec!["ptrace"])?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn subtract_permissive_baseline() -> Result<()> {
+        let conditional = LinuxSyscallBuilder::default()
+            .names(vec!["personality".to_string()])
+            .action(LinuxSeccompAction::ScmpActAllow)
+            .args(vec![gen_arg(0, 8)?])
+            .build()?;
+        let profile = LinuxSeccompBuilder::default()
+            .default_action(LinuxSeccompAction::ScmpActErrno)
+            .syscalls(vec![conditional.clone()])
+            .build()?;
+        let deny_personality = LinuxSyscallBuilder::default()
+            .names(vec!["personality".to_string()])
+            .action(LinuxSeccompAction::ScmpActErrno)
+            .build()?;
+        let baseline = LinuxSeccompBuilder::default()
+            .default_action(LinuxSeccompAction::ScmpActAllow)
+            .syscalls(vec![deny_personality])
+            .build()?;
+
+        // the unconditional rule of the baseline overrides its default action
+        let act = subtract(profile.clone(), baseline)?;
+        assert_eq!(act.syscalls(), &Some(vec![conditional]));
+
+        let allow_all = LinuxSeccompBuilder::default()
+            .default_action(LinuxSeccompAction::ScmpActAllow)
+            .build()?;
+        let act = subtract(profile, allow_all)?;
+        assert!(act.syscalls().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn diff_profiles_report_rule_changes() -> Result<()> {
+        let profile1 = gen_seccomp_profile(vec!["getpid", "mkdir", "ptrace"])?;
+
+        let conditional = LinuxSyscallBuilder::default()
+            .names(vec!["personality".to_string()])
+            .action(LinuxSeccompAction::ScmpActAllow)
+            .args(vec![gen_arg(0, 8)?])
+            .build()?;
+        let kill_ptrace = LinuxSyscallBuilder::default()
+            .names(vec!["ptrace".to_string()])
+            .action(LinuxSeccompAction::ScmpActKillProcess)
+            .build()?;
+        let mut syscalls = gen_seccomp_profile(vec!["mkdir", "getpid"])?
+            .syscalls()
+            .clone()
+            .unwrap();
+        syscalls.push(conditional.clone());
+        syscalls.push(kill_ptrace.clone());
+        let profile2 = LinuxSeccompBuilder::default()
+            .default_action(LinuxSeccompAction::ScmpActErrno)
+            .architectures(vec![Arch::ScmpArchX86_64])
+            .syscalls(syscalls)
+            .build()?;
+
+        let diff = diff_profiles(profile1, profile2)?;
+
+        assert_eq!(diff.default_action, None);
+        assert_eq!(
+            diff.architectures,
+            Some(Change {
+                before: vec![Arch::ScmpArchX86, Arch::ScmpArchX86_64],
+                after: vec![Arch::ScmpArchX86_64],
+            })
+        );
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].after, vec![conditional]);
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].name, "ptrace");
+        assert_eq!(diff.changed[0].after, vec![kill_ptrace]);
+        assert_eq!(diff.newly_allowed, vec!["personality".to_string()]);
+        assert!(diff.allows_more());
+
+        Ok(())
+    }
+
+    #[test]
+    fn diff_profiles_ignore_rule_order() -> Result<()> {
+        let profile1 = gen_duplicate_syscalls_profile(vec!["mkdir", "getpid"])?;
+        let profile2 = gen_seccomp_profile(vec!["getpid", "mkdir"])?;
+
+        let diff = diff_profiles(profile1, profile2)?;
+
+        assert!(diff.is_empty());
+        assert!(!diff.allows_more());
+
+        Ok(())
+    }
+
     #[test]
     fn diff_equal_profile() -> Result<()> {
         let profile1 = gen_seccomp_profile(vec!["mkdir"])?;
@@ -298,4 +1887,208 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn parse_profile_defaults() {
+        assert_eq!(
+            DefaultAction::from_str("KILL_PROCESS"),
+            Ok(DefaultAction(LinuxSeccompAction::ScmpActKillProcess))
+        );
+        assert_eq!(
+            DefaultAction::from_str("kill-thread"),
+            Ok(DefaultAction(LinuxSeccompAction::ScmpActKill))
+        );
+        assert!(DefaultAction::from_str("allow").is_err());
+
+        assert_eq!(
+            FilterFlag::from_str("SECCOMP_FILTER_FLAG_SPEC_ALLOW"),
+            Ok(FilterFlag("SECCOMP_FILTER_FLAG_SPEC_ALLOW".to_string()))
+        );
+        assert_eq!(
+            FilterFlag::from_str("wait-killable-recv"),
+            Ok(FilterFlag(
+                "SECCOMP_FILTER_FLAG_WAIT_KILLABLE_RECV".to_string()
+            ))
+        );
+        assert!(FilterFlag::from_str("tsync").is_err());
+    }
+
+    #[test]
+    fn apply_profile_defaults() -> Result<()> {
+        let profile = LinuxSeccompBuilder::default()
+            .default_action(LinuxSeccompAction::ScmpActErrno)
+            .default_errno_ret(38u32)
+            .architectures(vec![Arch::ScmpArchX86_64])
+            .syscalls(vec![LinuxSyscallBuilder::default()
+                .names(vec!["read".to_string()])
+                .action(LinuxSeccompAction::ScmpActAllow)
+                .build()?])
+            .build()?;
+
+        assert_eq!(ProfileDefaults::default().apply(&profile)?, profile);
+
+        let errno = ProfileDefaults {
+            default_errno: Some(1),
+            flags: vec![FilterFlag("SECCOMP_FILTER_FLAG_LOG".to_string())],
+            ..Default::default()
+        }
+        .apply(&profile)?;
+        assert_eq!(errno.default_action(), LinuxSeccompAction::ScmpActErrno);
+        assert_eq!(errno.default_errno_ret(), Some(1));
+        assert_eq!(
+            errno.flags(),
+            &Some(vec!["SECCOMP_FILTER_FLAG_LOG".to_string()])
+        );
+        assert_eq!(errno.syscalls(), profile.syscalls());
+
+        let kill = ProfileDefaults {
+            default_action: Some(DefaultAction(LinuxSeccompAction::ScmpActKillProcess)),
+            ..Default::default()
+        }
+        .apply(&profile)?;
+        assert_eq!(
+            kill.default_action(),
+            LinuxSeccompAction::ScmpActKillProcess
+        );
+        assert_eq!(kill.default_errno_ret(), None);
+        assert_eq!(kill.architectures(), profile.architectures());
+
+        let ignored = ProfileDefaults {
+            default_action: Some(DefaultAction(LinuxSeccompAction::ScmpActKillProcess)),
+            default_errno: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(ignored.warnings(&profile).len(), 1);
+        assert_eq!(ignored.apply(&profile)?.default_errno_ret(), None);
+        assert!(ProfileDefaults {
+            default_errno: Some(1),
+            ..Default::default()
+        }
+        .warnings(&profile)
+        .is_empty());
+
+        let denylist = LinuxSeccompBuilder::default()
+            .default_action(LinuxSeccompAction::ScmpActAllow)
+            .syscalls(vec![LinuxSyscallBuilder::default()
+                .names(vec!["ptrace".to_string()])
+                .action(LinuxSeccompAction::ScmpActErrno)
+                .build()?])
+            .build()?;
+        assert!(ProfileDefaults {
+            default_action: Some(DefaultAction(LinuxSeccompAction::ScmpActKillProcess)),
+            ..Default::default()
+        }
+        .apply(&denylist)
+        .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn canonicalize_profile() -> Result<()> {
+        let profile = LinuxSeccompBuilder::default()
+            .default_action(LinuxSeccompAction::ScmpActErrno)
+            .architectures(vec![
+                Arch::ScmpArchX86_64,
+                Arch::ScmpArchAarch64,
+                Arch::ScmpArchX86_64,
+            ])
+            .syscalls(vec![
+                LinuxSyscallBuilder::default()
+                    .names(vec!["personality".to_string()])
+                    .action(LinuxSeccompAction::ScmpActAllow)
+                    .args(vec![LinuxSeccompArgBuilder::default()
+                        .index(0usize)
+                        .value(8u64)
+                        .op(LinuxSeccompOperator::ScmpCmpEq)
+                        .build()?])
+                    .build()?,
+                LinuxSyscallBuilder::default()
+                    .names(vec!["write".to_string(), "read".to_string()])
+                    .action(LinuxSeccompAction::ScmpActAllow)
+                    .build()?,
+                LinuxSyscallBuilder::default()
+                    .names(vec!["close".to_string()])
+                    .action(LinuxSeccompAction::ScmpActAllow)
+                    .build()?,
+            ])
+            .build()?;
+
+        let canonical = canonicalize(&profile)?;
+        assert_eq!(
+            canonical.architectures(),
+            &Some(vec![Arch::ScmpArchAarch64, Arch::ScmpArchX86_64])
+        );
+        let rules: Vec<(Vec<String>, bool)> = canonical
+            .syscalls()
+            .iter()
+            .flatten()
+            .map(|syscall| (syscall.names().clone(), syscall.args().is_some()))
+            .collect();
+        assert_eq!(
+            rules,
+            vec![
+                (
+                    vec!["close".to_string(), "read".to_string(), "write".to_string()],
+                    false
+                ),
+                (vec!["personality".to_string()], true),
+            ]
+        );
+        assert_eq!(canonicalize(&canonical)?, canonical);
+
+        assert_eq!(
+            Encoding::from_path(Path::new("profile.yml")),
+            Encoding::Yaml
+        );
+        assert_eq!(
+            Encoding::from_path(Path::new("profile.json")),
+            Encoding::Json
+        );
+        assert!(Encoding::Json.encode(&canonical)?.ends_with("}\n"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn canonicalize_keep_rule_order() -> Result<()> {
+        let rule = |action, errno_ret: Option<u32>, args: Option<LinuxSeccompArg>| {
+            let mut builder = LinuxSyscallBuilder::default()
+                .names(vec!["personality".to_string()])
+                .action(action);
+            if let Some(errno_ret) = errno_ret {
+                builder = builder.errno_ret(errno_ret);
+            }
+            if let Some(arg) = args {
+                builder = builder.args(vec![arg]);
+            }
+            builder.build()
+        };
+        // personality(8, 0) matches both conditional rules, the first one applies
+        let profile = LinuxSeccompBuilder::default()
+            .default_action(LinuxSeccompAction::ScmpActErrno)
+            .architectures(vec![Arch::ScmpArchX86_64])
+            .syscalls(vec![
+                rule(
+                    LinuxSeccompAction::ScmpActErrno,
+                    Some(13),
+                    Some(gen_arg(0, 8)?),
+                )?,
+                rule(LinuxSeccompAction::ScmpActAllow, None, Some(gen_arg(1, 0)?))?,
+                rule(LinuxSeccompAction::ScmpActErrno, Some(38), None)?,
+                rule(LinuxSeccompAction::ScmpActAllow, None, None)?,
+                LinuxSyscallBuilder::default()
+                    .names(vec!["read".to_string()])
+                    .action(LinuxSeccompAction::ScmpActAllow)
+                    .args(vec![gen_arg(1, 0)?])
+                    .build()?,
+            ])
+            .build()?;
+
+        let canonical = canonicalize(&profile)?;
+        assert_eq!(cbpf::compile(&canonical)?, cbpf::compile(&profile)?);
+        assert_eq!(canonicalize(&canonical)?, canonical);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,280 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::profile::form::ProfileForm;
+use crate::profile_util::{self, Encoding};
+
+/// Prefix of the provenance annotations embedded in a profile
+const ANNOTATION_PREFIX: &str = "io.sprofiler.provenance.";
+/// Image annotations set by sprofiler users, CRI-O and containerd respectively
+const IMAGE_NAME_ANNOTATIONS: [&str; 3] = [
+    "io.sprofiler.image",
+    "io.kubernetes.cri-o.ImageName",
+    "io.kubernetes.cri.image-name",
+];
+const IMAGE_DIGEST_ANNOTATIONS: [&str; 2] =
+    ["io.sprofiler.image_digest", "io.kubernetes.cri-o.ImageRef"];
+const KERNEL_RELEASE_PATH: &str = "/proc/sys/kernel/osrelease";
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Generator {
+    Static,
+    Dynamic,
+}
+
+impl fmt::Display for Generator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Generator::Static => "static",
+            Generator::Dynamic => "dynamic",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Where a generated profile came from
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Provenance {
+    pub generator: Generator,
+    pub sprofiler_version: String,
+    /// Language of the analyzed binary
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binary: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_digest: Option<String>,
+    /// Container runtime and its version, e.g. "crun 1.8.4"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runtime: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kernel: Option<String>,
+    /// Architecture of the host sprofiler ran on
+    pub arch: String,
+    /// Whether the profile is an allowlist or a denylist
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub form: Option<ProfileForm>,
+    /// RFC 3339 timestamps in UTC
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_start: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_end: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub events: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lost_events: Option<u64>,
+    /// Processes (comm) which called each syscall while tracing, kept in the
+    /// sidecar only
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub syscall_processes: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl Provenance {
+    pub fn new(generator: Generator) -> Self {
+        Provenance {
+            generator,
+            sprofiler_version: env!("CARGO_PKG_VERSION").to_string(),
+            language: None,
+            binary: None,
+            container_id: None,
+            image: None,
+            image_digest: None,
+            runtime: None,
+            kernel: None,
+            arch: std::env::consts::ARCH.to_string(),
+            form: None,
+            trace_start: None,
+            trace_end: None,
+            events: None,
+            lost_events: None,
+            syscall_processes: BTreeMap::new(),
+        }
+    }
+
+    /// Image name and digest from the annotations of the OCI state
+    pub fn with_image(mut self, annotations: &HashMap<String, String>) -> Self {
+        let get = |keys: &[&str]| keys.iter().find_map(|key| annotations.get(*key)).cloned();
+        self.image = get(&IMAGE_NAME_ANNOTATIONS);
+        self.image_digest = get(&IMAGE_DIGEST_ANNOTATIONS);
+        self
+    }
+
+    pub fn with_kernel(mut self) -> Self {
+        self.kernel = fs::read_to_string(KERNEL_RELEASE_PATH)
+            .ok()
+            .map(|release| release.trim().to_string());
+        self
+    }
+
+    /// Annotations like io.sprofiler.provenance.generator=dynamic
+    pub fn annotations(&self) -> Result<BTreeMap<String, String>> {
+        let value = serde_json::to_value(self)?;
+        let mut annotations = BTreeMap::new();
+        for (key, value) in value.as_object().into_iter().flatten() {
+            let value = match value {
+                serde_json::Value::String(value) => value.clone(),
+                serde_json::Value::Object(_) | serde_json::Value::Array(_) => continue,
+                value => value.to_string(),
+            };
+            annotations.insert(format!("{}{}", ANNOTATION_PREFIX, key), value);
+        }
+        Ok(annotations)
+    }
+
+    /// None if the annotations have no provenance
+    pub fn from_annotations(annotations: &BTreeMap<String, String>) -> Result<Option<Self>> {
+        let mut fields = serde_json::Map::new();
+        for (key, value) in annotations {
+            let key = match key.strip_prefix(ANNOTATION_PREFIX) {
+                Some(key) => key,
+                None => continue,
+            };
+            let value = match key {
+                "events" | "lost_events" => serde_json::Value::from(value.parse::<u64>()?),
+                _ => serde_json::Value::from(value.as_str()),
+            };
+            fields.insert(key.to_string(), value);
+        }
+
+        if fields.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_value(serde_json::Value::Object(
+            fields,
+        ))?))
+    }
+
+    /// The sidecar, or the annotations embedded in the profile
+    pub fn load(profile_path: &Path) -> Result<Option<Self>> {
+        let path = meta_path(profile_path);
+        if path.exists() {
+            let file = File::open(&path)?;
+            let provenance = serde_json::from_reader(file)
+                .with_context(|| format!("failed to parse {}", path.display()))?;
+            return Ok(Some(provenance));
+        }
+
+        let value = profile_util::read_profile_value(profile_path)?;
+        Provenance::from_annotations(&profile_util::profile_annotations(&value)?)
+    }
+
+    pub fn write(&self, profile_path: &Path) -> Result<()> {
+        fs::write(meta_path(profile_path), Encoding::Json.encode(self)?)?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for Provenance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let annotations = self.annotations().map_err(|_| fmt::Error)?;
+        for (key, value) in annotations {
+            writeln!(
+                f,
+                "{}: {}",
+                key.trim_start_matches(ANNOTATION_PREFIX),
+                value
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// e.g. seccomp-profile.json -> seccomp-profile.meta.json
+pub fn meta_path(profile_path: &Path) -> PathBuf {
+    profile_path.with_extension("meta.json")
+}
+
+/// RFC 3339 timestamp in UTC, e.g. 2022-03-01T12:34:56Z
+pub fn format_timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    let (days, secs_of_day) = (secs / 86400, secs % 86400);
+
+    // civil_from_days of http://howardhinnant.github.io/date_algorithms.html
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn format_timestamps() {
+        assert_eq!(format_timestamp(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(
+            format_timestamp(UNIX_EPOCH + Duration::from_secs(1_646_138_096)),
+            "2022-03-01T12:34:56Z"
+        );
+        assert_eq!(
+            format_timestamp(UNIX_EPOCH + Duration::from_secs(951_782_400)),
+            "2000-02-29T00:00:00Z"
+        );
+    }
+
+    #[test]
+    fn provenance_annotations() -> Result<()> {
+        let mut annotations = HashMap::new();
+        annotations.insert(
+            "io.kubernetes.cri-o.ImageName".to_string(),
+            "docker.io/library/nginx:latest".to_string(),
+        );
+
+        let mut provenance = Provenance::new(Generator::Dynamic).with_image(&annotations);
+        provenance.container_id = Some("0123456789".to_string());
+        provenance.events = Some(42);
+        provenance.lost_events = Some(0);
+
+        let annotations = provenance.annotations()?;
+        assert_eq!(
+            annotations.get("io.sprofiler.provenance.generator"),
+            Some(&"dynamic".to_string())
+        );
+        assert_eq!(
+            annotations.get("io.sprofiler.provenance.image"),
+            Some(&"docker.io/library/nginx:latest".to_string())
+        );
+        assert_eq!(
+            annotations.get("io.sprofiler.provenance.events"),
+            Some(&"42".to_string())
+        );
+        assert_eq!(
+            Provenance::from_annotations(&annotations)?,
+            Some(provenance)
+        );
+        assert_eq!(Provenance::from_annotations(&BTreeMap::new())?, None);
+
+        Ok(())
+    }
+}
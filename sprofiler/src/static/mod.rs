@@ -1,12 +1,37 @@
-use std::fs::File;
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
+use oci_spec::runtime::{LinuxSeccomp, LinuxSyscall};
+use serde::Serialize;
 use sprofiler_sys::lang::{Language, SeccompProfilerBuilder};
 use structopt::StructOpt;
 
-use crate::profile_util::{self, DiffStatus};
+use crate::profile::form::ProfileForm;
+use crate::profile_util::{self, DiffStatus, ProfileDefaults, ProfileDiff, ProfileFormat};
+use crate::provenance::{Generator, Provenance};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiffFormat {
+    Text,
+    Json,
+    Markdown,
+}
+
+impl FromStr for DiffFormat {
+    type Err = String;
+
+    #[rustfmt::skip]
+    fn from_str(format: &str) -> Result<DiffFormat, Self::Err> {
+        match format.to_lowercase().as_str() {
+            "text"              => Ok(DiffFormat::Text),
+            "json"              => Ok(DiffFormat::Json),
+            "markdown" | "md"   => Ok(DiffFormat::Markdown),
+            _                   => Err(format!("unsupported diff format: {}", format)),
+        }
+    }
+}
 
 #[derive(Debug, StructOpt)]
 #[structopt(name = "static", about = "Static Analyzer")]
@@ -25,6 +50,14 @@ pub enum StaticSubCommand {
         /// Analyze Binary Language (e.g. c, go)
         #[structopt(short, long)]
         lang: String,
+        /// Output profile format (oci, moby)
+        #[structopt(long, default_value = "oci")]
+        format: ProfileFormat,
+        #[structopt(flatten)]
+        defaults: ProfileDefaults,
+        /// Embed the provenance as annotations in the profile besides the .meta.json sidecar
+        #[structopt(long)]
+        embed_provenance: bool,
     },
     /// Output the difference between the two profiles
     Diff {
@@ -32,6 +65,12 @@ pub enum StaticSubCommand {
         path1: PathBuf,
         #[structopt(parse(from_os_str))]
         path2: PathBuf,
+        /// Output format (text, json, markdown)
+        #[structopt(short, long, default_value = "text")]
+        format: DiffFormat,
+        /// Exit with an error if path2 permits syscalls path1 doesn't
+        #[structopt(long)]
+        fail_on_added: bool,
     },
     /// Combine two profiles
     Merge {
@@ -41,27 +80,91 @@ pub enum StaticSubCommand {
         /// Output seccomp profile path
         #[structopt(short, long, parse(from_os_str))]
         out: PathBuf,
+        /// Output profile format (oci, moby)
+        #[structopt(long, default_value = "oci")]
+        format: ProfileFormat,
+        #[structopt(flatten)]
+        defaults: ProfileDefaults,
+    },
+    /// Output a profile which allows only what every profile allows
+    Intersect {
+        /// Source seccomp profile
+        #[structopt(short, long, parse(from_os_str))]
+        paths: Vec<PathBuf>,
+        /// Output seccomp profile path
+        #[structopt(short, long, parse(from_os_str))]
+        out: PathBuf,
+        #[structopt(flatten)]
+        defaults: ProfileDefaults,
+    },
+    /// Output the rules of a profile which allow more than the baseline
+    Subtract {
+        #[structopt(parse(from_os_str))]
+        path: PathBuf,
+        /// Baseline seccomp profile
+        #[structopt(short, long, parse(from_os_str))]
+        baseline: PathBuf,
+        /// Output seccomp profile path
+        #[structopt(short, long, parse(from_os_str))]
+        out: PathBuf,
+    },
+    /// Sort syscalls and collapse duplicated rules
+    Normalize {
+        #[structopt(parse(from_os_str))]
+        path: PathBuf,
+        /// Output seccomp profile path
+        #[structopt(short, long, parse(from_os_str))]
+        out: PathBuf,
     },
 }
 
-pub fn do_run(bin: PathBuf, out: PathBuf, map: Option<PathBuf>, lang: &str) -> Result<()> {
+pub fn do_run(
+    bin: PathBuf,
+    out: PathBuf,
+    map: Option<PathBuf>,
+    lang: &str,
+    format: ProfileFormat,
+    defaults: &ProfileDefaults,
+    embed_provenance: bool,
+) -> Result<()> {
+    let mut provenance = Provenance::new(Generator::Static);
+    provenance.language = Some(lang.to_lowercase());
+    provenance.binary = Some(bin.clone());
+
     let mut sprofiler_builder =
-        SeccompProfilerBuilder::new(bin, out, Language::from_str(lang).unwrap());
+        SeccompProfilerBuilder::new(bin, out.clone(), Language::from_str(lang).unwrap());
 
     if let Some(map) = map {
         sprofiler_builder.set_syscall_map(map);
     }
 
-    let sprofiler = sprofiler_builder.build();
-    sprofiler.output()?;
+    let profile = sprofiler_builder.build().analyze()?;
+    for warning in defaults.warnings(&profile) {
+        eprintln!("{}", warning);
+    }
+    let profile = defaults.apply(&profile)?;
+    provenance.form = Some(ProfileForm::of(&profile));
+    let annotations = if embed_provenance {
+        provenance.annotations()?
+    } else {
+        BTreeMap::new()
+    };
+    for issue in
+        profile_util::write_annotated_seccomp_profile(&out, &profile, format, &annotations)?
+    {
+        eprintln!("{}", issue);
+    }
+    provenance.write(&out)?;
 
     Ok(())
 }
 
-pub fn do_diff(path1: PathBuf, path2: PathBuf) -> Result<()> {
-    let profile1 = profile_util::read_seccomp_profile(&path1)?;
-    let profile2 = profile_util::read_seccomp_profile(&path2)?;
-
+fn print_text_diff(
+    path1: &Path,
+    path2: &Path,
+    profile1: LinuxSeccomp,
+    profile2: LinuxSeccomp,
+) -> Result<()> {
     let map = profile_util::diff(profile1, profile2)?;
 
     let mut profile1_only = vec![];
@@ -104,12 +207,212 @@ pub fn do_diff(path1: PathBuf, path2: PathBuf) -> Result<()> {
     Ok(())
 }
 
-pub fn do_merge(paths: Vec<PathBuf>, out: PathBuf) -> Result<()> {
+/// Name used in the profile JSON, e.g. SCMP_ACT_ALLOW
+fn serialized_name<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => String::new(),
+    }
+}
+
+fn markdown_rules(syscalls: &[LinuxSyscall]) -> String {
+    if syscalls.is_empty() {
+        return "-".to_string();
+    }
+
+    let rules: Vec<String> = syscalls
+        .iter()
+        .map(|syscall| {
+            let mut rule = serialized_name(&syscall.action());
+            if let Some(errno_ret) = syscall.errno_ret() {
+                rule.push_str(&format!("({})", errno_ret));
+            }
+            let args: Vec<String> = syscall
+                .args()
+                .iter()
+                .flatten()
+                .map(|arg| {
+                    format!(
+                        "arg{} {} {}",
+                        arg.index(),
+                        serialized_name(&arg.op()),
+                        arg.value()
+                    )
+                })
+                .collect();
+            if !args.is_empty() {
+                rule.push_str(&format!(" if {}", args.join(" && ")));
+            }
+            rule
+        })
+        .collect();
+
+    rules.join("<br>")
+}
+
+fn print_markdown_diff(path1: &Path, path2: &Path, diff: &ProfileDiff) {
+    println!(
+        "## Seccomp profile diff: `{}` → `{}`",
+        path1.display(),
+        path2.display()
+    );
+    println!();
+
+    if diff.is_empty() {
+        println!("No changes.");
+        return;
+    }
+
+    if let Some(change) = &diff.default_action {
+        println!(
+            "- Default action: `{}` → `{}`",
+            serialized_name(&change.before),
+            serialized_name(&change.after)
+        );
+    }
+    if let Some(change) = &diff.default_errno_ret {
+        println!(
+            "- Default errno: `{:?}` → `{:?}`",
+            change.before, change.after
+        );
+    }
+    if let Some(change) = &diff.architectures {
+        let names = |archs: &Vec<_>| -> Vec<String> { archs.iter().map(serialized_name).collect() };
+        println!(
+            "- Architectures: `{}` → `{}`",
+            names(&change.before).join(", "),
+            names(&change.after).join(", ")
+        );
+    }
+
+    for (title, syscalls) in [
+        ("Added", &diff.added),
+        ("Removed", &diff.removed),
+        ("Changed", &diff.changed),
+    ] {
+        if syscalls.is_empty() {
+            continue;
+        }
+        println!();
+        println!("### {} syscalls ({})", title, syscalls.len());
+        println!();
+        println!("| syscall | before | after |");
+        println!("|---|---|---|");
+        for syscall in syscalls {
+            println!(
+                "| `{}` | {} | {} |",
+                syscall.name,
+                markdown_rules(&syscall.before),
+                markdown_rules(&syscall.after)
+            );
+        }
+    }
+
+    if !diff.newly_allowed.is_empty() {
+        println!();
+        println!(
+            "**Newly allowed:** {}",
+            diff.newly_allowed
+                .iter()
+                .map(|name| format!("`{}`", name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+}
+
+pub fn do_diff(
+    path1: PathBuf,
+    path2: PathBuf,
+    format: DiffFormat,
+    fail_on_added: bool,
+) -> Result<()> {
+    let profile1 = profile_util::read_seccomp_profile(&path1)?;
+    let profile2 = profile_util::read_seccomp_profile(&path2)?;
+
+    let diff = profile_util::diff_profiles(profile1.clone(), profile2.clone())?;
+
+    match format {
+        DiffFormat::Text => print_text_diff(&path1, &path2, profile1, profile2)?,
+        DiffFormat::Json => println!("{}", serde_json::to_string_pretty(&diff)?),
+        DiffFormat::Markdown => print_markdown_diff(&path1, &path2, &diff),
+    }
+
+    if fail_on_added && diff.allows_more() {
+        bail!(
+            "{} permits more than {}: {:?}",
+            path2.display(),
+            path1.display(),
+            diff.newly_allowed
+        );
+    }
+
+    Ok(())
+}
+
+pub fn do_merge(
+    paths: Vec<PathBuf>,
+    out: PathBuf,
+    format: ProfileFormat,
+    defaults: &ProfileDefaults,
+) -> Result<()> {
     let profiles = profile_util::read_seccomp_profiles(paths)?;
-    let profile = profile_util::merge(profiles)?;
+    let result = profile_util::merge_profiles(profiles)?;
 
-    let target_file = File::create(&out)?;
-    serde_json::to_writer(target_file, &profile)?;
+    for conflict in &result.conflicts {
+        eprintln!("conflict: {}", conflict);
+    }
+
+    for warning in defaults.warnings(&result.profile) {
+        eprintln!("{}", warning);
+    }
+    let profile = defaults.apply(&result.profile)?;
+    for issue in profile_util::write_seccomp_profile(&out, &profile, format)? {
+        eprintln!("{}", issue);
+    }
+
+    Ok(())
+}
+
+pub fn do_intersect(paths: Vec<PathBuf>, out: PathBuf, defaults: &ProfileDefaults) -> Result<()> {
+    let profiles = profile_util::read_seccomp_profiles(paths)?;
+    let result = profile_util::intersect_profiles(profiles)?;
+
+    for conflict in &result.conflicts {
+        eprintln!("conflict: {}", conflict);
+    }
+
+    for warning in defaults.warnings(&result.profile) {
+        eprintln!("{}", warning);
+    }
+    let profile = defaults.apply(&result.profile)?;
+    for issue in profile_util::write_seccomp_profile(&out, &profile, ProfileFormat::Oci)? {
+        eprintln!("{}", issue);
+    }
+
+    Ok(())
+}
+
+pub fn do_subtract(path: PathBuf, baseline: PathBuf, out: PathBuf) -> Result<()> {
+    let profile = profile_util::read_seccomp_profile(&path)?;
+    let baseline = profile_util::read_seccomp_profile(&baseline)?;
+
+    let profile = profile_util::subtract(profile, baseline)?;
+
+    for issue in profile_util::write_seccomp_profile(&out, &profile, ProfileFormat::Oci)? {
+        eprintln!("{}", issue);
+    }
+
+    Ok(())
+}
+
+pub fn do_normalize(path: PathBuf, out: PathBuf) -> Result<()> {
+    let profile = profile_util::read_seccomp_profile(&path)?;
+    let profile = profile_util::normalize(profile)?;
+
+    for issue in profile_util::write_seccomp_profile(&out, &profile, ProfileFormat::Oci)? {
+        eprintln!("{}", issue);
+    }
 
     Ok(())
 }
@@ -121,9 +424,33 @@ pub fn handle_static_analyzer(static_: StaticSubCommand) -> Result<()> {
             out,
             lang,
             map,
-        } => do_run(bin, out, map, &lang)?,
-        StaticSubCommand::Diff { path1, path2 } => do_diff(path1, path2)?,
-        StaticSubCommand::Merge { paths, out } => do_merge(paths, out)?,
+            format,
+            defaults,
+            embed_provenance,
+        } => do_run(bin, out, map, &lang, format, &defaults, embed_provenance)?,
+        StaticSubCommand::Diff {
+            path1,
+            path2,
+            format,
+            fail_on_added,
+        } => do_diff(path1, path2, format, fail_on_added)?,
+        StaticSubCommand::Merge {
+            paths,
+            out,
+            format,
+            defaults,
+        } => do_merge(paths, out, format, &defaults)?,
+        StaticSubCommand::Intersect {
+            paths,
+            out,
+            defaults,
+        } => do_intersect(paths, out, &defaults)?,
+        StaticSubCommand::Subtract {
+            path,
+            baseline,
+            out,
+        } => do_subtract(path, baseline, out)?,
+        StaticSubCommand::Normalize { path, out } => do_normalize(path, out)?,
     };
 
     Ok(())
@@ -0,0 +1,439 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use crate::cbpf::{self, ArchTable};
+use crate::profile_util::{self, SyscallAccess, EPERM};
+use nix::errno::Errno;
+use oci_spec::runtime::{Arch, LinuxSeccomp, LinuxSeccompAction};
+
+/// Syscall sets of systemd (src/shared/seccomp-util.c) which don't include other sets.
+/// Syscalls unknown on the architectures of the profile are ignored when a set is compressed.
+#[rustfmt::skip]
+const SYSCALL_GROUPS: &[(&str, &[&str])] = &[
+    ("@aio", &[
+        "io_cancel", "io_destroy", "io_getevents", "io_pgetevents", "io_setup", "io_submit",
+        "io_uring_enter", "io_uring_register", "io_uring_setup",
+    ]),
+    ("@basic-io", &[
+        "_llseek", "close", "close_range", "dup", "dup2", "dup3", "lseek", "pread64", "preadv",
+        "preadv2", "pwrite64", "pwritev", "pwritev2", "read", "readv", "write", "writev",
+    ]),
+    ("@chown", &[
+        "chown", "chown32", "fchown", "fchown32", "fchownat", "lchown", "lchown32",
+    ]),
+    ("@clock", &[
+        "adjtimex", "clock_adjtime", "clock_adjtime64", "clock_settime", "clock_settime64",
+        "settimeofday",
+    ]),
+    ("@cpu-emulation", &[
+        "modify_ldt", "subpage_prot", "switch_endian", "vm86", "vm86old",
+    ]),
+    ("@debug", &[
+        "lookup_dcookie", "perf_event_open", "pidfd_getfd", "ptrace", "rtas",
+        "s390_runtime_instr", "sys_debug_setcontext",
+    ]),
+    ("@file-system", &[
+        "access", "chdir", "chmod", "close", "creat", "faccessat", "faccessat2", "fallocate",
+        "fchdir", "fchmod", "fchmodat", "fcntl", "fcntl64", "fgetxattr", "flistxattr",
+        "fremovexattr", "fsetxattr", "fstat", "fstat64", "fstatat64", "fstatfs", "fstatfs64",
+        "ftruncate", "ftruncate64", "futimesat", "getcwd", "getdents", "getdents64", "getxattr",
+        "inotify_add_watch", "inotify_init", "inotify_init1", "inotify_rm_watch", "lgetxattr",
+        "link", "linkat", "listxattr", "llistxattr", "lremovexattr", "lsetxattr", "lstat",
+        "lstat64", "mkdir", "mkdirat", "mknod", "mknodat", "mmap", "mmap2", "munmap",
+        "newfstatat", "oldfstat", "oldlstat", "oldstat", "open", "openat", "openat2",
+        "readlink", "readlinkat", "removexattr", "rename", "renameat", "renameat2", "rmdir",
+        "setxattr", "stat", "stat64", "statfs", "statfs64", "statx", "symlink", "symlinkat",
+        "truncate", "truncate64", "unlink", "unlinkat", "utime", "utimensat",
+        "utimensat_time64", "utimes",
+    ]),
+    ("@io-event", &[
+        "_newselect", "epoll_create", "epoll_create1", "epoll_ctl", "epoll_ctl_old",
+        "epoll_pwait", "epoll_pwait2", "epoll_wait", "epoll_wait_old", "eventfd", "eventfd2",
+        "poll", "ppoll", "ppoll_time64", "pselect6", "pselect6_time64", "select",
+    ]),
+    ("@ipc", &[
+        "ipc", "memfd_create", "mq_getsetattr", "mq_notify", "mq_open", "mq_timedreceive",
+        "mq_timedreceive_time64", "mq_timedsend", "mq_timedsend_time64", "mq_unlink", "msgctl",
+        "msgget", "msgrcv", "msgsnd", "pipe", "pipe2", "process_madvise", "process_vm_readv",
+        "process_vm_writev", "semctl", "semget", "semop", "semtimedop", "semtimedop_time64",
+        "shmat", "shmctl", "shmdt", "shmget",
+    ]),
+    ("@keyring", &["add_key", "keyctl", "request_key"]),
+    ("@memlock", &["mlock", "mlock2", "mlockall", "munlock", "munlockall"]),
+    ("@module", &["delete_module", "finit_module", "init_module"]),
+    ("@mount", &[
+        "chroot", "fsconfig", "fsmount", "fsopen", "fspick", "mount", "mount_setattr",
+        "move_mount", "open_tree", "pivot_root", "umount", "umount2",
+    ]),
+    ("@network-io", &[
+        "accept", "accept4", "bind", "connect", "getpeername", "getsockname", "getsockopt",
+        "listen", "recv", "recvfrom", "recvmmsg", "recvmmsg_time64", "recvmsg", "send",
+        "sendmmsg", "sendmsg", "sendto", "setsockopt", "shutdown", "socket", "socketcall",
+        "socketpair",
+    ]),
+    ("@obsolete", &[
+        "_sysctl", "afs_syscall", "bdflush", "break", "create_module", "ftime",
+        "get_kernel_syms", "getpmsg", "gtty", "idle", "lock", "mpx", "prof", "profil",
+        "putpmsg", "query_module", "security", "sgetmask", "ssetmask", "stime", "stty", "sysfs",
+        "tuxcall", "ulimit", "uselib", "ustat", "vserver",
+    ]),
+    ("@pkey", &["pkey_alloc", "pkey_free", "pkey_mprotect"]),
+    ("@raw-io", &[
+        "ioperm", "iopl", "pciconfig_iobase", "pciconfig_read", "pciconfig_write",
+        "s390_pci_mmio_read", "s390_pci_mmio_write",
+    ]),
+    ("@reboot", &["kexec_file_load", "kexec_load", "reboot"]),
+    ("@resources", &[
+        "ioprio_set", "mbind", "migrate_pages", "move_pages", "nice", "sched_setaffinity",
+        "sched_setattr", "sched_setparam", "sched_setscheduler", "set_mempolicy", "setpriority",
+        "setrlimit",
+    ]),
+    ("@setuid", &[
+        "setgid", "setgid32", "setgroups", "setgroups32", "setregid", "setregid32", "setresgid",
+        "setresgid32", "setresuid", "setresuid32", "setreuid", "setreuid32", "setuid",
+        "setuid32",
+    ]),
+    ("@swap", &["swapoff", "swapon"]),
+    ("@sync", &[
+        "fdatasync", "fsync", "msync", "sync", "sync_file_range", "sync_file_range2", "syncfs",
+    ]),
+    ("@timer", &[
+        "alarm", "getitimer", "setitimer", "timer_create", "timer_delete", "timer_getoverrun",
+        "timer_gettime", "timer_gettime64", "timer_settime", "timer_settime64",
+        "timerfd_create", "timerfd_gettime", "timerfd_gettime64", "timerfd_settime",
+        "timerfd_settime64", "times",
+    ]),
+];
+
+/// Architecture names of SystemCallArchitectures=
+#[rustfmt::skip]
+fn systemd_arch(arch: &Arch) -> Option<&'static str> {
+    match arch {
+        Arch::ScmpArchX86       => Some("x86"),
+        Arch::ScmpArchX86_64    => Some("x86-64"),
+        Arch::ScmpArchX32       => Some("x32"),
+        Arch::ScmpArchArm       => Some("arm"),
+        Arch::ScmpArchAarch64   => Some("arm64"),
+        Arch::ScmpArchMips      => Some("mips"),
+        Arch::ScmpArchMips64    => Some("mips64"),
+        Arch::ScmpArchMipsel    => Some("mips-le"),
+        Arch::ScmpArchMipsel64  => Some("mips64-le"),
+        Arch::ScmpArchPpc       => Some("ppc"),
+        Arch::ScmpArchPpc64     => Some("ppc64"),
+        Arch::ScmpArchPpc64le   => Some("ppc64-le"),
+        Arch::ScmpArchS390      => Some("s390"),
+        Arch::ScmpArchS390x     => Some("s390x"),
+        _                       => None,
+    }
+}
+
+/// Errno names are accepted by every systemd version, numbers only by newer ones
+fn errno_name(errno_ret: u32) -> String {
+    match Errno::from_i32(errno_ret as i32) {
+        Errno::UnknownErrno => errno_ret.to_string(),
+        errno => format!("{:?}", errno),
+    }
+}
+
+/// A [Service] drop-in with the syscall filter of a profile
+#[derive(Debug, PartialEq)]
+pub struct DropIn {
+    pub architectures: Vec<String>,
+    /// SystemCallFilter=~ lists the denied syscalls, used when the profile allows by default
+    pub deny_list: bool,
+    pub filter: Vec<String>,
+    pub error_number: Option<String>,
+    /// Syscalls whose rules depend on arguments, which systemd can't express.
+    /// They are left out, i.e. denied by allow lists and allowed by deny lists.
+    pub conditional: Vec<String>,
+}
+
+impl fmt::Display for DropIn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "[Service]")?;
+        if !self.conditional.is_empty() {
+            writeln!(
+                f,
+                "# Rules depending on arguments are not supported: {}",
+                self.conditional.join(" ")
+            )?;
+        }
+        if !self.architectures.is_empty() {
+            writeln!(
+                f,
+                "SystemCallArchitectures={}",
+                self.architectures.join(" ")
+            )?;
+        }
+        let prefix = if self.deny_list { "~" } else { "" };
+        writeln!(f, "SystemCallFilter={}{}", prefix, self.filter.join(" "))?;
+        if let Some(error_number) = &self.error_number {
+            writeln!(f, "SystemCallErrorNumber={}", error_number)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Syscall tables of the architectures of the profile, the host table if sprofiler
+/// has none of them
+fn syscall_tables(profile: &LinuxSeccomp) -> Vec<ArchTable> {
+    let tables: Vec<ArchTable> = profile
+        .architectures()
+        .iter()
+        .flatten()
+        .filter_map(cbpf::arch_table)
+        .collect();
+    if !tables.is_empty() {
+        return tables;
+    }
+    cbpf::host_arch()
+        .as_ref()
+        .and_then(cbpf::arch_table)
+        .into_iter()
+        .collect()
+}
+
+/// Replaces syscalls by the systemd sets whose syscalls known on any of the
+/// architectures are all included
+fn compress(names: BTreeSet<String>, tables: &[ArchTable]) -> Vec<String> {
+    let mut groups = Vec::new();
+    let mut covered = BTreeSet::new();
+
+    for (group, members) in SYSCALL_GROUPS {
+        let mut known_members = members
+            .iter()
+            .filter(|member| tables.iter().any(|table| table.number(member).is_some()))
+            .peekable();
+        if known_members.peek().is_some() && known_members.all(|member| names.contains(*member)) {
+            groups.push(group.to_string());
+            covered.extend(members.iter().map(|member| member.to_string()));
+        }
+    }
+
+    groups.extend(names.into_iter().filter(|name| !covered.contains(name)));
+    groups
+}
+
+/// The errno of the deny rules if they all return the same one, systemd has a
+/// single SystemCallErrorNumber=. None if they kill the process.
+fn deny_errno(profile: &LinuxSeccomp, names: &BTreeSet<String>) -> Option<u32> {
+    let mut errnos = BTreeSet::new();
+    for name in names {
+        for rule in profile_util::rules_for(profile, name) {
+            match rule.action() {
+                LinuxSeccompAction::ScmpActErrno => {
+                    errnos.insert(Some(rule.errno_ret().unwrap_or(EPERM)));
+                }
+                action if !profile_util::is_allowing(action) => {
+                    errnos.insert(None);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    match errnos.len() {
+        0 => Some(EPERM),
+        1 => errnos.into_iter().next().flatten(),
+        _ => {
+            eprintln!(
+                "WARNING: deny rules differ in action or errno, systemd returns EPERM for all of them"
+            );
+            Some(EPERM)
+        }
+    }
+}
+
+/// With `compress_groups` syscalls are replaced by systemd sets, which are copied
+/// from systemd and may differ from the installed version
+pub fn export(profile: &LinuxSeccomp, compress_groups: bool) -> DropIn {
+    let default_action = profile.default_action();
+    let deny_list = profile_util::is_allowing(default_action);
+
+    let mut names = BTreeSet::new();
+    let mut conditional = Vec::new();
+    for name in profile
+        .syscalls()
+        .iter()
+        .flatten()
+        .flat_map(|syscall| syscall.names())
+        .collect::<BTreeSet<_>>()
+    {
+        match profile_util::syscall_access(profile, name) {
+            SyscallAccess::Allowed if !deny_list => {
+                names.insert(name.clone());
+            }
+            SyscallAccess::Denied if deny_list => {
+                names.insert(name.clone());
+            }
+            SyscallAccess::Conditional => conditional.push(name.clone()),
+            _ => {}
+        }
+    }
+
+    // systemd kills the process unless SystemCallErrorNumber= is set
+    let error_number = if deny_list {
+        deny_errno(profile, &names).map(errno_name)
+    } else {
+        match default_action {
+            LinuxSeccompAction::ScmpActErrno => {
+                Some(errno_name(profile.default_errno_ret().unwrap_or(EPERM)))
+            }
+            _ => None,
+        }
+    };
+
+    let filter = if compress_groups {
+        compress(names, &syscall_tables(profile))
+    } else {
+        names.into_iter().collect()
+    };
+
+    DropIn {
+        architectures: profile
+            .architectures()
+            .iter()
+            .flatten()
+            .filter_map(systemd_arch)
+            .map(String::from)
+            .collect(),
+        deny_list,
+        filter,
+        error_number,
+        conditional,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use oci_spec::runtime::{
+        LinuxSeccompArgBuilder, LinuxSeccompBuilder, LinuxSeccompOperator, LinuxSyscallBuilder,
+    };
+
+    fn group(name: &str) -> &'static [&'static str] {
+        SYSCALL_GROUPS
+            .iter()
+            .find(|(group, _)| *group == name)
+            .map(|(_, members)| *members)
+            .unwrap()
+    }
+
+    #[test]
+    fn export_allow_list() -> Result<()> {
+        let basic_io: Vec<String> = group("@basic-io")
+            .iter()
+            .map(|name| name.to_string())
+            .chain(["getpid".to_string()])
+            .collect();
+        let profile = LinuxSeccompBuilder::default()
+            .default_action(LinuxSeccompAction::ScmpActErrno)
+            .default_errno_ret(38u32)
+            .architectures(vec![Arch::ScmpArchX86_64])
+            .syscalls(vec![
+                LinuxSyscallBuilder::default()
+                    .names(basic_io)
+                    .action(LinuxSeccompAction::ScmpActAllow)
+                    .build()?,
+                LinuxSyscallBuilder::default()
+                    .names(vec!["personality".to_string()])
+                    .action(LinuxSeccompAction::ScmpActAllow)
+                    .args(vec![LinuxSeccompArgBuilder::default()
+                        .index(0usize)
+                        .value(0u64)
+                        .op(LinuxSeccompOperator::ScmpCmpEq)
+                        .build()?])
+                    .build()?,
+            ])
+            .build()?;
+
+        let drop_in = export(&profile, true);
+        assert!(!drop_in.deny_list);
+        assert_eq!(drop_in.architectures, vec!["x86-64".to_string()]);
+        assert_eq!(
+            drop_in.filter,
+            vec!["@basic-io".to_string(), "getpid".to_string()]
+        );
+        assert_eq!(drop_in.error_number, Some("ENOSYS".to_string()));
+        assert_eq!(drop_in.conditional, vec!["personality".to_string()]);
+        assert_eq!(
+            drop_in.to_string(),
+            "[Service]\n\
+             # Rules depending on arguments are not supported: personality\n\
+             SystemCallArchitectures=x86-64\n\
+             SystemCallFilter=@basic-io getpid\n\
+             SystemCallErrorNumber=ENOSYS\n"
+        );
+
+        assert_eq!(export(&profile, false).filter.len(), 18);
+
+        Ok(())
+    }
+
+    #[test]
+    fn export_deny_list() -> Result<()> {
+        let profile = LinuxSeccompBuilder::default()
+            .default_action(LinuxSeccompAction::ScmpActAllow)
+            .syscalls(vec![LinuxSyscallBuilder::default()
+                .names(vec!["reboot".to_string(), "kexec_load".to_string()])
+                .action(LinuxSeccompAction::ScmpActErrno)
+                .build()?])
+            .build()?;
+
+        let drop_in = export(&profile, true);
+        assert!(drop_in.deny_list);
+        assert_eq!(
+            drop_in.filter,
+            vec!["kexec_load".to_string(), "reboot".to_string()]
+        );
+        assert_eq!(drop_in.error_number, Some("EPERM".to_string()));
+
+        let enosys = LinuxSeccompBuilder::default()
+            .default_action(LinuxSeccompAction::ScmpActAllow)
+            .syscalls(vec![LinuxSyscallBuilder::default()
+                .names(vec!["reboot".to_string(), "kexec_load".to_string()])
+                .action(LinuxSeccompAction::ScmpActErrno)
+                .errno_ret(38u32)
+                .build()?])
+            .build()?;
+        assert_eq!(
+            export(&enosys, false).error_number,
+            Some("ENOSYS".to_string())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn compress_with_profile_architectures() -> Result<()> {
+        // dup2 is missing on aarch64
+        let names: Vec<String> = group("@basic-io")
+            .iter()
+            .filter(|name| **name != "dup2")
+            .map(|name| name.to_string())
+            .collect();
+        let profile = |arch: Arch| -> Result<LinuxSeccomp> {
+            Ok(LinuxSeccompBuilder::default()
+                .default_action(LinuxSeccompAction::ScmpActErrno)
+                .architectures(vec![arch])
+                .syscalls(vec![LinuxSyscallBuilder::default()
+                    .names(names.clone())
+                    .action(LinuxSeccompAction::ScmpActAllow)
+                    .build()?])
+                .build()?)
+        };
+
+        assert_eq!(
+            export(&profile(Arch::ScmpArchAarch64)?, true).filter,
+            vec!["@basic-io".to_string()]
+        );
+        assert_eq!(
+            export(&profile(Arch::ScmpArchX86_64)?, true).filter.len(),
+            names.len()
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,22 @@
+use anyhow::Result;
+use oci_spec::runtime::{
+    Arch, LinuxSeccomp, LinuxSeccompAction, LinuxSeccompBuilder, LinuxSyscallBuilder,
+};
+
+/// Profile like a generated one, which allows the syscalls on x86 and x86_64 and
+/// denies the rest with SCMP_ACT_ERRNO
+pub(crate) fn gen_seccomp_profile(allow_syscalls: Vec<&str>) -> Result<LinuxSeccomp> {
+    let mut names: Vec<String> = allow_syscalls.into_iter().map(String::from).collect();
+    names.sort();
+
+    let seccomp = LinuxSeccompBuilder::default()
+        .default_action(LinuxSeccompAction::ScmpActErrno)
+        .architectures(vec![Arch::ScmpArchX86, Arch::ScmpArchX86_64])
+        .syscalls(vec![LinuxSyscallBuilder::default()
+            .names(names)
+            .action(LinuxSeccompAction::ScmpActAllow)
+            .build()?])
+        .build()?;
+
+    Ok(seccomp)
+}